@@ -97,17 +97,21 @@ macro_rules! integer_definition_from_attribute {
 }
 pub(crate) use integer_definition_from_attribute;
 
-macro_rules! number_array_definition_from_attribute {
-    ( $attribute:expr, $default:expr, $default_size:expr) => {
+/// An array whose elements come from the attribute's entry at the same index.
+/// `$determine` finds an element from the values of that entry,
+/// and `$request` finds the values that would give a desired element.
+macro_rules! array_definition_from_attribute {
+    ( $variant:ident, $attribute:expr, $default:expr, $default_size:expr, $determine:expr, $request:expr ) => {
         {
-            StateVarVariant::NumberArray(StateVarArrayDefinition {
+            StateVarVariant::$variant(StateVarArrayDefinition {
 
                 initial_essential_element_value: $default,
+                initial_essential_size: $default_size,
 
                 return_element_dependency_instructions: |i, _| {
                     let attribute = DependencyInstruction::Attribute{
                         attribute_name: $attribute,
-                        index: crate::state_variables::StateIndex::Element(i),
+                        index: crate::state_variables::StateIndex::Element(vec![i]),
                     };
                     HashMap::from([("attribute", attribute)])
                 },
@@ -115,14 +119,7 @@ macro_rules! number_array_definition_from_attribute {
                 determine_element_from_dependencies: |_, dependency_values| {
                     let (attribute, _) = dependency_values.dep_value("attribute")?;
                     if attribute.len() > 0 {
-
-                        match DETERMINE_NUMBER(attribute) {
-                            Ok(x) => Ok(SetValue(x)),
-                            Err(msg) => {
-                                crate::utils::log!("Error determing number: {}", msg);
-                                Ok(SetValue(f64::NAN))
-                            },
-                        }
+                        ($determine)(attribute).map(crate::state_variables::StateVarUpdateInstruction::SetValue)
                     } else {
                         Ok ( crate::state_variables::StateVarUpdateInstruction::SetValue($default) )
                     }
@@ -141,14 +138,14 @@ macro_rules! number_array_definition_from_attribute {
                         .has_exactly_one_element()?
                         .into_number()?;
 
-                    Ok(SetValue(size_value as usize))
+                    Ok(crate::state_variables::StateVarUpdateInstruction::SetValue(size_value as usize))
                 },
 
                 request_element_dependencies_to_update_value: |_, desired_value, sources| {
                     let attribute_sources = sources.get("attribute")
                         .expect("No instruction named 'attribute'");
                     HashMap::from([
-                        ("attribute", DETERMINE_NUMBER_DEPENDENCIES(desired_value, attribute_sources))
+                        ("attribute", ($request)(desired_value, attribute_sources))
                     ])
                 },
 
@@ -157,8 +154,57 @@ macro_rules! number_array_definition_from_attribute {
         }
     }
 }
+pub(crate) use array_definition_from_attribute;
+
+macro_rules! number_array_definition_from_attribute {
+    ( $attribute:expr, $default:expr, $default_size:expr) => {
+        array_definition_from_attribute!(NumberArray, $attribute, $default, $default_size,
+            |attribute| DETERMINE_NUMBER(attribute).or_else(|msg| {
+                crate::utils::log!("Error determing number: {}", msg);
+                Ok::<f64, String>(f64::NAN)
+            }),
+            |desired_value, sources| DETERMINE_NUMBER_DEPENDENCIES(desired_value, sources)
+        )
+    }
+}
 pub(crate) use number_array_definition_from_attribute;
 
+#[allow(unused_macros)]
+macro_rules! integer_array_definition_from_attribute {
+    ( $attribute:expr, $default:expr, $default_size:expr) => {
+        array_definition_from_attribute!(IntegerArray, $attribute, $default, $default_size,
+            |attribute| DETERMINE_NUMBER(attribute).map(|x| x as i64),
+            |desired_value: i64, sources| DETERMINE_NUMBER_DEPENDENCIES(desired_value as f64, sources)
+        )
+    }
+}
+#[allow(unused_imports)]
+pub(crate) use integer_array_definition_from_attribute;
+
+#[allow(unused_macros)]
+macro_rules! boolean_array_definition_from_attribute {
+    ( $attribute:expr, $default:expr, $default_size:expr) => {
+        array_definition_from_attribute!(BooleanArray, $attribute, $default, $default_size,
+            |attribute| DETERMINE_BOOLEAN(attribute),
+            |_, _| Err("A boolean attribute cannot be updated".to_string())
+        )
+    }
+}
+#[allow(unused_imports)]
+pub(crate) use boolean_array_definition_from_attribute;
+
+#[allow(unused_macros)]
+macro_rules! math_array_definition_from_attribute {
+    ( $attribute:expr, $default:expr, $default_size:expr) => {
+        array_definition_from_attribute!(MathArray, $attribute, $default, $default_size,
+            |attribute| DETERMINE_MATH_EXPRESSION(attribute),
+            |desired_value, sources| DETERMINE_MATH_EXPRESSION_DEPENDENCIES(desired_value, sources)
+        )
+    }
+}
+#[allow(unused_imports)]
+pub(crate) use math_array_definition_from_attribute;
+
 macro_rules! boolean_definition_from_attribute {
     ( $attribute:expr, $default:expr ) => {
         {
@@ -196,6 +242,36 @@ macro_rules! boolean_definition_from_attribute {
 pub(crate) use boolean_definition_from_attribute;
 
 
+/// Copies a boolean state var of the <document>, such as one of the core flags
+macro_rules! boolean_definition_from_document {
+    ( $state_var:expr ) => {
+        {
+            StateVarVariant::Boolean(StateVarDefinition {
+                for_renderer: true,
+
+                return_dependency_instructions: |_| {
+                    let document = DependencyInstruction::Document {
+                        state_var: $state_var,
+                    };
+                    HashMap::from([("document", document)])
+                },
+
+                determine_state_var_from_dependencies: |dependency_values| {
+                    let value = dependency_values.dep_value("document")?
+                        .has_exactly_one_element()?
+                        .into_bool()?;
+
+                    Ok(crate::state_variables::StateVarUpdateInstruction::SetValue(value))
+                },
+
+                ..Default::default()
+            })
+        }
+    }
+}
+pub(crate) use boolean_definition_from_document;
+
+
 macro_rules! string_definition_from_attribute {
     ( $attribute:expr, $default:expr ) => {
         {
@@ -457,6 +533,7 @@ pub fn TEXT_DEFAULT_DEFINITION() -> StateVarVariant {
                 StateVarValue::Integer(v) => Ok(SetValue(v.to_string())),
                 StateVarValue::Number(v) => Ok(SetValue(v.to_string())),
                 StateVarValue::MathExpr(_) => unreachable!(),
+                StateVarValue::Null => Ok(SetNull),
             }
         },
 
@@ -481,6 +558,20 @@ pub fn FIXED_DEFAULT_DEFINITION() -> StateVarVariant {
 }
 
 
+/// For a string state var that holds JSON, the renderer gets the parsed JSON,
+/// or null when the string is not JSON
+#[allow(non_snake_case)]
+pub fn PARSE_JSON_FOR_RENDERER(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            log!("Sending null to the renderer for invalid JSON {}: {}", json, e);
+            serde_json::Value::Null
+        }),
+        other => other,
+    }
+}
+
+
 #[allow(non_snake_case)]
 pub fn DETERMINE_BOOLEAN(dependency_values: Vec<&DependencyValue>)
     -> Result<bool, String> {
@@ -543,9 +634,10 @@ pub fn DETERMINE_NUMBER(dependency_values: Vec<&DependencyValue>)
     if dependency_values.len() == 1
     && dependency_values[0].source != (DependencySource::Essential { value_type: "mathExpression" })  {
         
-        let value = match dependency_values[0].value {
-            StateVarValue::Number(val) => val,
-            StateVarValue::Integer(val) => val as f64,
+        let value = match &dependency_values[0].value {
+            StateVarValue::Number(val) => *val,
+            StateVarValue::Integer(val) => *val as f64,
+            StateVarValue::MathExpr(e) => e.tree.eval_number().unwrap_or(f64::NAN),
             _ => return Err(format!(
                     "A single dependency value must be a number or integer, received {:?}",
                     dependency_values
@@ -664,6 +756,51 @@ pub fn DETERMINE_NUMBER_DEPENDENCIES(desired_value: f64, sources: &Vec<(Dependen
 
 
 
+/// A math expression is kept as it is, unless it has variables, which are evaluated
+#[allow(non_snake_case)]
+pub fn DETERMINE_MATH_EXPRESSION(dependency_values: Vec<&DependencyValue>)
+    -> Result<MathExpression, String> {
+
+    if dependency_values.len() == 1
+    && dependency_values[0].source != (DependencySource::Essential { value_type: "mathExpression" })  {
+
+        return match &dependency_values[0].value {
+            StateVarValue::MathExpr(e) => Ok(e.clone()),
+            StateVarValue::Number(val) => Ok(MathExpression::from(*val)),
+            StateVarValue::Integer(val) => Ok(MathExpression::from(*val as f64)),
+            _ => Err(format!(
+                    "A single dependency value must be a math expression or a number, received {:?}",
+                    dependency_values
+                )),
+        };
+    }
+
+    let (expression, variable_values) = split_dependency_values_into_math_expression_and_values(dependency_values.clone())?;
+    if variable_values.is_empty() {
+        Ok(expression)
+    } else {
+        DETERMINE_NUMBER(dependency_values).map(MathExpression::from)
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn DETERMINE_MATH_EXPRESSION_DEPENDENCIES(desired_value: MathExpression, sources: &Vec<(DependencySource, Option<StateVarValue>)>)
+    -> Result<Vec<DependencyValue>, String> {
+
+    match sources.as_slice() {
+        [(source @ DependencySource::Essential { value_type: "mathExpression" }, _)] => Ok(vec![
+            DependencyValue {
+                source: source.clone(),
+                value: StateVarValue::MathExpr(desired_value),
+            }
+        ]),
+        _ => {
+            let desired_number = desired_value.tree.eval_number().map_err(|e| e.to_string())?;
+            DETERMINE_NUMBER_DEPENDENCIES(desired_number, sources)
+        },
+    }
+}
+
 #[allow(non_snake_case)]
 pub fn DETERMINE_INTEGER(dependency_values: Vec<&DependencyValue>)
     -> Result<i64, String> {
@@ -726,6 +863,7 @@ pub fn DETERMINE_STRING(dependency_values: Vec<DependencyValue>)
             StateVarValue::Integer(v) => v.to_string(),
             StateVarValue::Number(v)  => v.to_string(),
             StateVarValue::MathExpr(_)  => unreachable!(),
+            StateVarValue::Null => String::new(),
         });
     }
 
@@ -733,22 +871,291 @@ pub fn DETERMINE_STRING(dependency_values: Vec<DependencyValue>)
 }
 
 
+// ========== Lists ============
+
+/// The dependency instructions for the items of a list, and for its size
+pub fn list_dependency_instructions(
+    desired_profiles: Vec<crate::ComponentProfile>,
+) -> HashMap<InstructionName, DependencyInstruction> {
+    HashMap::from([
+        ("children", DependencyInstruction::Child {
+            desired_profiles,
+            parse_into_expression: false,
+        }),
+        ("max_number", DependencyInstruction::StateVar {
+            component_ref: None,
+            state_var: StateVarSlice::Single(StateRef::Basic("maxNumber")),
+        }),
+    ])
+}
+
+/// The items of a list from the values of its children.
+/// A string child has an item for each word, and with `merge`, for each
+/// comma-separated entry in a word. Any other child is one item.
+pub fn list_items(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<Vec<StateVarValue>, String> {
+    let merge = list_merges(dependency_values)?;
+    let (children, _) = dependency_values.dep_value("children")?;
+
+    Ok(children.iter().flat_map(|child| match (&child.value, is_string_child(&child.source)) {
+        (StateVarValue::String(string), true) => list_item_spans(string, merge).into_iter()
+            .map(|span| StateVarValue::String(string[span].to_string()))
+            .collect(),
+        _ => vec![child.value.clone()],
+    }).collect())
+}
+
+/// The number of items in a list, which is at most its maxNumber
+pub fn list_size(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<usize, String> {
+    let max_number = dependency_values.dep_value("max_number")?
+        .has_exactly_one_element()?
+        .into_number()?;
+    let size = list_items(dependency_values)?.len();
+
+    Ok(match max_number.is_nan() {
+        true => size,
+        false => size.min(max_number.max(0.0) as usize),
+    })
+}
+
+/// The values of a list's children so that its item at the (1-based) index becomes the desired value.
+/// The other children keep their current value, and a string child only has the word of that item changed.
+pub fn list_item_dependencies(
+    index: usize,
+    desired_value: StateVarValue,
+    sources: &HashMap<InstructionName, Vec<(DependencySource, Option<StateVarValue>)>>,
+) -> Result<Vec<DependencyValue>, String> {
+    let merge = match sources.get("merge").and_then(|merge| merge.first()) {
+        Some((_, Some(merge))) => bool::try_from(merge.clone())?,
+        _ => false,
+    };
+
+    let mut items_before = 0;
+    sources.get("children").unwrap().iter().map(|(source, current)| {
+        let current = current.clone().ok_or(format!("The current value of list child {:?} is unknown", source))?;
+
+        let value = match (&current, is_string_child(source)) {
+            (StateVarValue::String(string), true) => {
+                let spans = list_item_spans(string, merge);
+                let span = index.checked_sub(items_before + 1).and_then(|i| spans.get(i));
+                items_before += spans.len();
+                match span {
+                    Some(span) => {
+                        let mut string = string.clone();
+                        string.replace_range(span.clone(), &list_item_as_string(&desired_value));
+                        StateVarValue::String(string)
+                    },
+                    None => current,
+                }
+            },
+            _ => {
+                items_before += 1;
+                match items_before == index {
+                    true => list_item_like(&desired_value, &current),
+                    false => current,
+                }
+            },
+        };
+
+        Ok(DependencyValue { source: source.clone(), value })
+    }).collect()
+}
+
+
+/// A list item as a number, NaN when it is not numerical
+pub fn list_item_into_number(item: &StateVarValue) -> f64 {
+    match item {
+        StateVarValue::Number(x) => *x,
+        StateVarValue::Integer(x) => *x as f64,
+        StateVarValue::Boolean(x) => *x as i64 as f64,
+        StateVarValue::String(x) => evalexpr::eval_number(x).unwrap_or(f64::NAN),
+        StateVarValue::MathExpr(x) => x.tree.eval_number().unwrap_or(f64::NAN),
+        StateVarValue::Null => f64::NAN,
+    }
+}
+
+/// A list item as a boolean: a word is true when it is "true", ignoring case
+pub fn list_item_into_bool(item: &StateVarValue) -> bool {
+    match item {
+        StateVarValue::Boolean(x) => *x,
+        StateVarValue::Number(x) => *x != 0.0,
+        StateVarValue::Integer(x) => *x != 0,
+        StateVarValue::String(x) => x.trim().eq_ignore_ascii_case("true"),
+        StateVarValue::MathExpr(x) => x.tree.eval_boolean().unwrap_or(false),
+        StateVarValue::Null => false,
+    }
+}
+
+/// A list item as a math expression, which is not evaluated.
+/// A word that is not an expression becomes NaN.
+pub fn list_item_into_math_expression(item: &StateVarValue) -> MathExpression {
+    match item {
+        StateVarValue::MathExpr(x) => x.clone(),
+        StateVarValue::String(x) => match evalexpr::build_operator_tree(x) {
+            Ok(tree) => MathExpression { tree, variable_prefix: String::new(), external_variables_count: 0 },
+            Err(_) => MathExpression::from(f64::NAN),
+        },
+        _ => MathExpression::from(list_item_into_number(item)),
+    }
+}
+
+pub fn list_item_as_string(item: &StateVarValue) -> String {
+    match item {
+        StateVarValue::String(x) => x.clone(),
+        StateVarValue::Boolean(x) => x.to_string(),
+        StateVarValue::Integer(x) => x.to_string(),
+        StateVarValue::Number(x) => x.to_string(),
+        StateVarValue::MathExpr(x) => x.tree.to_string(),
+        StateVarValue::Null => String::new(),
+    }
+}
+
+/// The item converted to the same type as another value
+fn list_item_like(item: &StateVarValue, like: &StateVarValue) -> StateVarValue {
+    match like {
+        StateVarValue::String(_) => StateVarValue::String(list_item_as_string(item)),
+        StateVarValue::Boolean(_) => StateVarValue::Boolean(list_item_into_bool(item)),
+        StateVarValue::Integer(_) => StateVarValue::Integer(list_item_into_number(item).round() as i64),
+        StateVarValue::Number(_) => StateVarValue::Number(list_item_into_number(item)),
+        StateVarValue::MathExpr(_) => StateVarValue::MathExpr(list_item_into_math_expression(item)),
+        StateVarValue::Null => item.clone(),
+    }
+}
+
+/// Whether a list splits the values of this child into words
+fn is_string_child(source: &DependencySource) -> bool {
+    matches!(source,
+        DependencySource::Essential { value_type: "string" } |
+        DependencySource::StateVar { component_type: "string", .. }
+    )
+}
+
+fn list_merges(dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>) -> Result<bool, String> {
+    match dependency_values.get("merge") {
+        Some(_) => dependency_values.dep_value("merge")?.has_exactly_one_element()?.into_bool(),
+        None => Ok(false),
+    }
+}
+
+/// Where each item of a string is
+fn list_item_spans(string: &str, merge: bool) -> Vec<std::ops::Range<usize>> {
+    let is_separator = |c: char| c.is_whitespace() || (merge && c == ',');
+
+    let mut spans = vec![];
+    let mut start = None;
+    for (i, c) in string.char_indices() {
+        match (is_separator(c), start) {
+            (true, Some(s)) => {
+                spans.push(s..i);
+                start = None;
+            },
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+    if let Some(s) = start {
+        spans.push(s..string.len());
+    }
+    spans
+}
+
+
 pub fn members_from_children_of_type(
     component_nodes: &HashMap<crate::ComponentName, crate::ComponentNode>,
     node: &crate::ComponentNode,
     component_type: crate::ComponentType,
 ) -> Vec<CollectionMembersOrCollection> {
-    get_children_of_type(component_nodes, node, component_type, true).map(|c|
-        match c.definition.replacement_components {
-            Some(ReplacementComponents::Batch(_)) =>
-                CollectionMembersOrCollection::Members(CollectionMembers::Batch(crate::ComponentRelative::same_instance(c.name.clone()))),
-            Some(ReplacementComponents::Collection(_)) =>
-                CollectionMembersOrCollection::Collection(crate::ComponentRelative::same_instance(c.name.clone())),
-            Some(ReplacementComponents::Children) =>
-                todo!(),
-            None => CollectionMembersOrCollection::Members(CollectionMembers::Component(crate::ComponentRelative::same_instance(c.name.clone()))),
-        }
-    ).collect()
+    members_from_children_of_types(component_nodes, node, &[component_type], false)
+}
+
+/// The members among the children that are of one of the given types.
+/// With `descend`, the children of any other component are searched as well.
+pub fn members_from_children_of_types(
+    component_nodes: &HashMap<crate::ComponentName, crate::ComponentNode>,
+    node: &crate::ComponentNode,
+    component_types: &[crate::ComponentType],
+    descend: bool,
+) -> Vec<CollectionMembersOrCollection> {
+    crate::get_child_nodes_including_copy(component_nodes, node).into_iter()
+        .filter_map(|(child, _)| child.as_component().map(|c| component_nodes.get(c).unwrap()))
+        .flat_map(|c| {
+            if instances_a_template(c) {
+                return members_from_instances_of_types(component_nodes, c, component_types, descend);
+            }
+            if !component_types.iter().any(|t| t.to_lowercase() == member_component_type(c).to_lowercase()) {
+                if descend && c.definition.replacement_components.is_none() {
+                    return members_from_children_of_types(component_nodes, c, component_types, descend);
+                }
+                return vec![];
+            }
+            vec![match c.definition.replacement_components {
+                Some(ReplacementComponents::Batch(_)) =>
+                    CollectionMembersOrCollection::Members(CollectionMembers::Batch(crate::ComponentRelative::same_instance(c.name.clone()))),
+                Some(ReplacementComponents::Collection(_)) =>
+                    CollectionMembersOrCollection::Collection(crate::ComponentRelative::same_instance(c.name.clone())),
+                Some(ReplacementComponents::Children) =>
+                    todo!(),
+                None => CollectionMembersOrCollection::Members(CollectionMembers::Component(crate::ComponentRelative::same_instance(c.name.clone()))),
+            }]
+        })
+        .collect()
+}
+
+/// The members of a collection of template instances (like a <map>) of the given types:
+/// every instance of each component of those types in the template
+pub fn members_from_instances_of_types(
+    component_nodes: &HashMap<crate::ComponentName, crate::ComponentNode>,
+    node: &crate::ComponentNode,
+    component_types: &[crate::ComponentType],
+    descend: bool,
+) -> Vec<CollectionMembersOrCollection> {
+    let Some(ReplacementComponents::Collection(def)) = &node.definition.replacement_components else {
+        return vec![];
+    };
+    (def.collection_members)(node, component_nodes).into_iter().flat_map(|members| {
+        let CollectionMembersOrCollection::Members(CollectionMembers::InstanceBySources { template, sources, behavior }) = members else {
+            return vec![];
+        };
+        let template_node = component_nodes.get(&template.name).unwrap();
+
+        // Not those in a nested collection, which would need more than one index
+        members_from_children_of_types(component_nodes, template_node, component_types, descend).into_iter()
+            .filter_map(|member| match member {
+                CollectionMembersOrCollection::Members(CollectionMembers::Component(component)) =>
+                    Some(CollectionMembersOrCollection::Members(CollectionMembers::InstanceBySources {
+                        template: component,
+                        sources: sources.clone(),
+                        behavior,
+                    })),
+                _ => None,
+            })
+            .collect()
+    }).collect()
+}
+
+/// Whether the members of this collection are instances of a template
+pub fn instances_a_template(component: &crate::ComponentNode) -> bool {
+    match &component.definition.replacement_components {
+        Some(ReplacementComponents::Collection(def)) => matches!(
+            (def.member_definition)(&component.static_attributes).replacement_components,
+            Some(ReplacementComponents::Children)
+        ),
+        _ => false,
+    }
+}
+
+/// The type of the components that this component is replaced with
+fn member_component_type(component: &crate::ComponentNode) -> crate::ComponentType {
+    match &component.definition.replacement_components {
+        Some(ReplacementComponents::Collection(def)) =>
+            (def.member_definition)(&component.static_attributes).component_type,
+        Some(ReplacementComponents::Batch(def)) =>
+            def.member_definition.component_type,
+        _ => component.definition.component_type,
+    }
 }
 
 pub fn get_children_of_type<'a>(
@@ -762,12 +1169,9 @@ pub fn get_children_of_type<'a>(
             crate::component::ObjectName::String(_) => None,
             crate::component::ObjectName::Component(c) => {
                 let comp = component_nodes.get(c).unwrap();
-                let child_type = match (include_groups, &comp.definition.replacement_components) {
-                    (true, Some(ReplacementComponents::Collection(def))) =>
-                        (def.member_definition)(&node.static_attributes).component_type,
-                    (true, Some(ReplacementComponents::Batch(def))) =>
-                        def.member_definition.component_type,
-                    _ => comp.definition.component_type,
+                let child_type = match include_groups {
+                    true => member_component_type(comp),
+                    false => comp.definition.component_type,
                 };
                 (child_type.to_lowercase() == component_type.to_lowercase())
                     .then(|| component_nodes.get(c).unwrap())
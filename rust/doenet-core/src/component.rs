@@ -1,4 +1,4 @@
-use crate::{CollectionMembers, ComponentRefRelative, ComponentRefStateRelative, ComponentNode, ComponentName, ComponentRefStateArrayRelative, ComponentRelative};
+use crate::{CollectionMembers, SourcesBehavior, ComponentRefRelative, ComponentRefStateRelative, ComponentNode, ComponentName, ComponentRefStateArrayRelative, ComponentRelative};
 use crate::math_expression::MathExpression;
 use enum_as_inner::EnumAsInner;
 use serde::Serialize;
@@ -26,6 +26,30 @@ pub mod template;
 pub mod sources;
 pub mod conditional_content;
 pub mod case;
+pub mod choice_input;
+pub mod choice;
+pub mod feedback;
+pub mod award;
+pub mod hint;
+pub mod solution;
+pub mod circle;
+pub mod polyline;
+pub mod line_segment;
+pub mod polygon;
+pub mod constraints;
+pub mod constrain_to_grid;
+pub mod attract_to;
+pub mod constrain_to;
+pub mod constrain_to_interior;
+pub mod style_definitions;
+pub mod style_definition;
+pub mod intersection;
+pub mod function;
+pub mod curve;
+pub mod number_list;
+pub mod text_list;
+pub mod boolean_list;
+pub mod math_list;
 
 lazy_static! {
     pub static ref COMPONENT_DEFINITIONS: HashMap<ComponentType, &'static ComponentDefinition> = {
@@ -50,6 +74,30 @@ lazy_static! {
             &crate::sources            ::MY_COMPONENT_DEFINITION,
             &crate::conditional_content::MY_COMPONENT_DEFINITION,
             &crate::case               ::MY_COMPONENT_DEFINITION,
+            &crate::choice_input       ::MY_COMPONENT_DEFINITION,
+            &crate::choice             ::MY_COMPONENT_DEFINITION,
+            &crate::feedback           ::MY_COMPONENT_DEFINITION,
+            &crate::award              ::MY_COMPONENT_DEFINITION,
+            &crate::hint               ::MY_COMPONENT_DEFINITION,
+            &crate::solution           ::MY_COMPONENT_DEFINITION,
+            &crate::circle             ::MY_COMPONENT_DEFINITION,
+            &crate::polyline           ::MY_COMPONENT_DEFINITION,
+            &crate::line_segment       ::MY_COMPONENT_DEFINITION,
+            &crate::polygon            ::MY_COMPONENT_DEFINITION,
+            &crate::constraints        ::MY_COMPONENT_DEFINITION,
+            &crate::constrain_to_grid  ::MY_COMPONENT_DEFINITION,
+            &crate::attract_to         ::MY_COMPONENT_DEFINITION,
+            &crate::constrain_to       ::MY_COMPONENT_DEFINITION,
+            &crate::constrain_to_interior::MY_COMPONENT_DEFINITION,
+            &crate::style_definitions  ::MY_COMPONENT_DEFINITION,
+            &crate::style_definition   ::MY_COMPONENT_DEFINITION,
+            &crate::intersection       ::MY_COMPONENT_DEFINITION,
+            &crate::function           ::MY_COMPONENT_DEFINITION,
+            &crate::curve              ::MY_COMPONENT_DEFINITION,
+            &crate::number_list        ::MY_COMPONENT_DEFINITION,
+            &crate::text_list          ::MY_COMPONENT_DEFINITION,
+            &crate::boolean_list       ::MY_COMPONENT_DEFINITION,
+            &crate::math_list          ::MY_COMPONENT_DEFINITION,
         ];
 
         defs.into_iter().map(|def| (def.component_type, def)).collect()
@@ -97,6 +145,11 @@ pub enum CopySource {
     Component(ComponentRefRelative),
     StateVar(ComponentRefStateRelative),
     MapSources(ComponentRelative),
+    MapSourcesIndex(ComponentRelative),
+    /// The same state var of every member of a collection, copied into a list
+    CollectionStateVar(ComponentRelative, StateRef),
+    /// Every element of an array state var, copied into a list
+    StateVarArray(ComponentRefStateArrayRelative),
     DynamicElement(ComponentRefStateArrayRelative, MathExpression, Vec<ComponentName>),
 }
 
@@ -107,7 +160,10 @@ pub enum ComponentProfile {
     Number,
     Boolean,
     Math,
-    // Graphical,
+    Graphical,
+    Constraint,
+    StyleDefinitions,
+    Function,
 }
 
 /// The definition of a component type.
@@ -127,16 +183,23 @@ pub struct ComponentDefinition {
 
     pub array_aliases: HashMap<&'static str, StateRef>,
 
+    /// Other names an attribute can be given by
+    pub attribute_aliases: HashMap<&'static str, AttributeName>,
+
     /// Process an action and return the state variables to change.
     /// The update requests will be processed in the order returned.
     pub on_action: for<'a> fn(
         action_name: &str,
         args: HashMap<String, Vec<StateVarValue>>,
-        resolve_and_retrieve_state_var: &'a dyn Fn(&'a StateRef) -> Option<StateVarValue>
+        resolve_and_retrieve_state_var: &'a dyn Fn(&StateRef) -> Option<StateVarValue>
     ) -> Vec<(StateRef, StateVarValue)>,
 
     pub should_render_children: bool,
 
+    /// When false, string children (including whitespace between tags) are left out
+    /// of the rendered children, so the renderer only receives component children.
+    pub should_render_string_children: bool,
+
     /// These have to match `on_action` and with what the renderers have
     pub action_names: fn() -> Vec<&'static str>,
 
@@ -147,6 +210,10 @@ pub struct ComponentDefinition {
 
     pub renderer_type: RendererType,
 
+    /// State variables sent to the renderer together as one object, under the given name.
+    /// They do not need to be `for_renderer`.
+    pub renderer_objects: Vec<(&'static str, Vec<StateVarName>)>,
+
     /// If specified, the component's parent will treat this as multiple components.
     pub replacement_components: Option<ReplacementComponents>,
 
@@ -285,8 +352,11 @@ impl Default for ComponentDefinition {
             attribute_names: Vec::new(),
             static_attribute_names: Vec::new(),
             array_aliases: HashMap::new(),
+            attribute_aliases: HashMap::new(),
             should_render_children: false,
+            should_render_string_children: true,
             renderer_type: RendererType::Myself,
+            renderer_objects: Vec::new(),
             primary_input_state_var: None,
             component_profiles: vec![],
             valid_children_profiles: ValidChildTypes::ValidProfiles(vec![]),
@@ -334,7 +404,8 @@ pub enum RendererType {
         component_type: &'static str,
         state_var_aliases: HashMap<StateVarName, StateVarName>,
     },
-    // DoNotRender,
+    /// Left out of the render tree, along with its children
+    DoNotRender,
 }
 
 
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+
+use super::*;
+use super::constraints::*;
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("threshold", number_definition_from_attribute!("threshold", 0.5));
+
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("objects", graphical_children_instruction()),
+                    ("threshold", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("threshold")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let threshold = dependency_values.dep_value("threshold")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+
+                let constraint = Constraint::AttractTo {
+                    objects: objects_from_dependency_values(&dependency_values)?,
+                    threshold,
+                };
+
+                Ok( SetValue( constraints_to_json(&[constraint]) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "attractTo",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "threshold",
+
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Constraint, "constraints"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Graphical]),
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::base_definitions::*;
+
+
+use crate::ComponentProfile;
+
+
+use lazy_static::lazy_static;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("condition", boolean_definition_from_attribute!("condition", false));
+        state_var_definitions.insert("credit", number_definition_from_attribute!("credit", 1.0));
+        state_var_definitions.insert("feedbackCodes", string_definition_from_attribute!("feedbackCodes", ""));
+
+        // Whether this award's condition currently matches, so a <feedback> can depend on it
+        state_var_definitions.insert("awarded", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("condition", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("condition")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let condition = dependency_values.dep_value("condition")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                Ok(SetValue(condition))
+            },
+
+            ..Default::default()
+        }));
+
+        // The credit this award contributes, which is zero until it is awarded
+        state_var_definitions.insert("creditAchieved", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("awarded", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("awarded")),
+                    }),
+                    ("credit", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("credit")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let awarded = dependency_values.dep_value("awarded")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+                let credit = dependency_values.dep_value("credit")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+
+                Ok(SetValue(if awarded { credit } else { 0.0 }))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "award",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "condition",
+            "credit",
+            "feedbackCodes",
+
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Boolean, "awarded"),
+            (ComponentProfile::Number, "creditAchieved"),
+        ],
+
+        renderer_type: RendererType::Special { component_type: "containerInline", state_var_aliases: HashMap::new() },
+
+        valid_children_profiles: ValidChildTypes::AllComponents,
+
+        ..Default::default()
+    };
+}
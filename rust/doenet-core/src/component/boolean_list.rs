@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+
+use super::*;
+
+use crate::ComponentProfile;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("value", StateVarVariant::BooleanArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                list_dependency_instructions(vec![ComponentProfile::Boolean, ComponentProfile::Text])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let items = list_items(&dependency_values)?;
+                Ok(SetValue(items.get(index).is_some_and(list_item_into_bool)))
+            },
+
+            request_element_dependencies_to_update_value: |index, desired_value, sources| {
+                HashMap::from([
+                    ("children", list_item_dependencies(index, StateVarValue::Boolean(desired_value), &sources)),
+                ])
+            },
+
+            return_size_dependency_instructions: |_| {
+                list_dependency_instructions(vec![ComponentProfile::Boolean, ComponentProfile::Text])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                Ok(SetValue(list_size(&dependency_values)?))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("text", StateVarVariant::StringArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([(
+                    "corresponding_value", DependencyInstruction::CorrespondingElements {
+                        component_ref: None,
+                        array_state_var_name: "value",
+                    }
+                )])
+            },
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                let my_value: bool = dependency_values.dep_value("corresponding_value")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                Ok(SetValue(my_value.to_string()))
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("sv_value_size", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("value")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("sv_value_size")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+
+                Ok(SetValue(size as usize))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("maxNumber", number_definition_from_attribute!("maxNumber", f64::INFINITY));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+fn member_state_var(
+    index: usize,
+    state_var_slice: &StateVarSlice,
+) -> Option<StateVarSlice> {
+
+    let slice = match state_var_slice {
+        StateVarSlice::Single(StateRef::Basic("value")) => {
+            StateVarSlice::Single(StateRef::ArrayElement("value", vec![index]))
+        }
+        StateVarSlice::Single(StateRef::Basic("text")) => {
+            StateVarSlice::Single(StateRef::ArrayElement("text", vec![index]))
+        },
+        _ => state_var_slice.clone(),
+    };
+
+    Some(slice)
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "booleanList",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        replacement_components: Some(ReplacementComponents::Batch(BatchDefinition {
+            member_definition: &super::boolean::MY_COMPONENT_DEFINITION,
+            size: StateRef::SizeOf("value"),
+            member_state_var,
+        })),
+
+        attribute_names: vec![
+            "maxNumber",
+            "hide",
+            "disabled",
+        ],
+
+        static_attribute_names: vec![
+            "assignNames",
+        ],
+
+        primary_input_state_var: Some("value"),
+
+        component_profiles: vec![
+            (ComponentProfile::Boolean, "value"),
+            (ComponentProfile::Text, "text"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![
+            ComponentProfile::Boolean,
+            ComponentProfile::Text,
+        ]),
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::base_definitions::*;
+
+
+use crate::ComponentProfile;
+
+use lazy_static::lazy_static;
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("text", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                let instruction = DependencyInstruction::Child {
+                    desired_profiles: vec![ComponentProfile::Text],
+                    parse_into_expression: false,
+                };
+
+                HashMap::from([("children_value_svs", instruction)])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let textlike_children = dependency_values.get("children_value_svs").unwrap();
+                DETERMINE_STRING(textlike_children.clone()).map(|x| SetValue(x.trim().to_string()))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("credit", number_definition_from_attribute!("credit", 0.0));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "choice",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "credit",
+
+            "hide",
+            "disabled",
+        ],
+
+        // A <choiceInput> reads the text and the credit of its choices through these
+        component_profiles: vec![
+            (ComponentProfile::Text, "text"),
+            (ComponentProfile::Number, "credit"),
+        ],
+
+        renderer_type: RendererType::Special { component_type: "containerInline", state_var_aliases: HashMap::new() },
+
+        should_render_children: true,
+
+        valid_children_profiles: ValidChildTypes::AllComponents,
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use super::*;
+
+use crate::ComponentProfile;
+use crate::utils::log;
+
+
+
+lazy_static! {
+
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        // Indices into choiceOrder, so they refer to the choices in the order they are displayed
+        state_var_definitions.insert("selectedIndices", StateVarVariant::NumberArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                DETERMINE_FROM_ESSENTIAL(dependency_values)
+            },
+
+            request_element_dependencies_to_update_value: |_, desired_value, sources| {
+                REQUEST_ESSENTIAL_TO_UPDATE(desired_value, sources)
+            },
+
+            return_size_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("essential")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok(SetValue(size as usize))
+            },
+
+            request_size_dependencies_to_update_value: |desired_size, sources| {
+                REQUEST_ESSENTIAL_TO_UPDATE(StateVarValue::Integer(desired_size as i64), sources)
+            },
+
+            initial_essential_element_value: 0.0,
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("selectedValues", StateVarVariant::StringArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("selected_indices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("selectedIndices"),
+                    }),
+                    ("choice_texts", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("choiceTexts"),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let selected_indices = dependency_values.dep_value("selected_indices")?
+                    .into_number_list()?;
+                let choice_texts = dependency_values.dep_value("choice_texts")?
+                    .into_string_list()?;
+
+                let choice_text = selected_indices.get(index)
+                    .and_then(|&i| choice_texts.get((i as usize).wrapping_sub(1)))
+                    .cloned()
+                    .unwrap_or_default();
+
+                Ok(SetValue(choice_text))
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("num_selected", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("selectedIndices")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("num_selected")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok(SetValue(size as usize))
+            },
+
+            ..Default::default()
+        }));
+
+        // For each displayed position, the (1-indexed) choice child shown there
+        state_var_definitions.insert("choiceOrder", StateVarVariant::NumberArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("shuffle_order", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("shuffleOrder")),
+                    }),
+                    ("seed", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("seed")),
+                    }),
+                    ("variant_index", DependencyInstruction::Document {
+                        state_var: "variantIndex",
+                    }),
+                    ("num_choices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("choiceOrder")),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let shuffle_order = dependency_values.dep_value("shuffle_order")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+                let seed = dependency_values.dep_value("seed")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                let variant_index = dependency_values.dep_value("variant_index")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                let num_choices = dependency_values.dep_value("num_choices")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+
+                let choice_number = if shuffle_order {
+                    shuffled_order(num_choices as usize, seed, variant_index)[index]
+                } else {
+                    index + 1
+                };
+
+                Ok(SetValue(choice_number as f64))
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("choices", DependencyInstruction::Child {
+                        desired_profiles: vec![ComponentProfile::Text],
+                        parse_into_expression: false,
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let choices = dependency_values.dep_value("choices")?;
+                let (choices, _) = choices.filter_include_component_type(&"choice");
+                Ok(SetValue(choices.len()))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("choiceTexts", StateVarVariant::StringArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("choices", DependencyInstruction::Child {
+                        desired_profiles: vec![ComponentProfile::Text],
+                        parse_into_expression: false,
+                    }),
+                    ("choice_order", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("choiceOrder"),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let choice_texts = dependency_values.dep_value("choices")?
+                    .filter_include_component_type(&"choice")
+                    .into_string_list()?;
+                let choice_order = dependency_values.dep_value("choice_order")?
+                    .into_number_list()?;
+
+                let choice_number = *choice_order.get(index)
+                    .ok_or(format!("choice index {} out of range", index))? as usize;
+
+                // A choice number that does not match a choice has no text
+                let choice_text = choice_number.checked_sub(1)
+                    .and_then(|choice_index| choice_texts.get(choice_index))
+                    .cloned()
+                    .unwrap_or_default();
+
+                Ok(SetValue(choice_text))
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("num_choices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("choiceOrder")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("num_choices")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok(SetValue(size as usize))
+            },
+
+            ..Default::default()
+        }));
+
+        // With selectMultiple, the credits of all selected choices are added, capped at 1
+        state_var_definitions.insert("creditAchieved", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("choice_credits", DependencyInstruction::Child {
+                        desired_profiles: vec![ComponentProfile::Number],
+                        parse_into_expression: false,
+                    }),
+                    ("choice_order", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("choiceOrder"),
+                    }),
+                    ("selected_indices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("selectedIndices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let choice_credits = dependency_values.dep_value("choice_credits")?
+                    .filter_include_component_type(&"choice")
+                    .into_number_list()?;
+                let choice_order = dependency_values.dep_value("choice_order")?
+                    .into_number_list()?;
+                let selected_indices = dependency_values.dep_value("selected_indices")?
+                    .into_number_list()?;
+
+                let credit: f64 = selected_indices.iter()
+                    .filter_map(|&i| choice_order.get((i as usize).wrapping_sub(1)))
+                    .filter_map(|&choice_number| choice_credits.get(choice_number as usize - 1))
+                    .fold(0.0, |total, credit| total + credit);
+
+                Ok(SetValue(credit.clamp(0.0, 1.0)))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("valueHasBeenValidated", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+            initial_essential_value: false,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("showCorrectness", boolean_definition_from_document!("showCorrectness"));
+
+        state_var_definitions.insert("inline", boolean_definition_from_attribute!("inline", false));
+        state_var_definitions.insert("selectMultiple", boolean_definition_from_attribute!("selectMultiple", false));
+        state_var_definitions.insert("shuffleOrder", boolean_definition_from_attribute!("shuffleOrder", false));
+        state_var_definitions.insert("seed", integer_definition_from_attribute!("seed", 0));
+        state_var_definitions.insert("includeCheckWork", boolean_definition_from_attribute!("includeCheckWork", false));
+
+        state_var_definitions.insert("submitLabel", string_definition_from_attribute!("submitLabel", "Check Work"));
+        state_var_definitions.insert("submitLabelNoCorrectness", string_definition_from_attribute!("submitLabelNoCorrectness", "Submit Response"));
+        state_var_definitions.insert("placeHolder", string_definition_from_attribute!("placeHolder", ""));
+        state_var_definitions.insert("label", string_definition_from_attribute!("label", ""));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+/// A permutation of 1..=size that only depends on the seed and the variant
+/// of the document, so the order stays the same every time the document is loaded.
+fn shuffled_order(size: usize, seed: i64, variant_index: i64) -> Vec<usize> {
+    let mut order: Vec<usize> = (1..=size).collect();
+
+    // splitmix64, started at a different state for each variant
+    let mut state = (seed as u64).wrapping_add((variant_index as u64).wrapping_sub(1).wrapping_mul(0x9E3779B97F4A7C15));
+    let mut next_random = || {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    };
+
+    // Fisher-Yates
+    for i in (1..size).rev() {
+        let j = (next_random() % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+
+    order
+}
+
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "choiceInput",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "inline",
+            "selectMultiple",
+            "shuffleOrder",
+            "seed",
+            "includeCheckWork",
+            "submitLabel",
+            "submitLabelNoCorrectness",
+            "placeHolder",
+            "label",
+
+            "hide",
+            "disabled",
+        ],
+
+        should_render_children: true,
+
+        // The renderer looks up choices by their position among the children
+        should_render_string_children: false,
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Text]),
+
+        action_names: || vec!["updateSelectedIndices", "submitAnswer"],
+
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
+            match action_name {
+                "updateSelectedIndices" => {
+                    let Some(selected_indices) = args.get("selectedIndices") else {
+                        log!("updateSelectedIndices called on choiceInput without selectedIndices");
+                        return vec![];
+                    };
+                    let Ok(mut selected_indices) = selected_indices.iter()
+                        .map(|index| index.clone().into_number())
+                        .collect::<Result<Vec<StateVarValue>, _>>() else {
+                        log!("updateSelectedIndices called on choiceInput with indices that are not numbers");
+                        return vec![];
+                    };
+
+                    // Only the last choice stays selected
+                    let select_multiple = resolve_and_retrieve_state_var(&StateRef::Basic("selectMultiple"))
+                        == Some(StateVarValue::Boolean(true));
+                    if !select_multiple && selected_indices.len() > 1 {
+                        selected_indices.drain(..selected_indices.len() - 1);
+                    }
+
+                    let mut updates = vec![(
+                        StateRef::SizeOf("selectedIndices"),
+                        StateVarValue::Number(selected_indices.len() as f64),
+                    )];
+                    updates.extend(selected_indices.into_iter().enumerate().map(|(i, index)| (
+                        StateRef::ArrayElement("selectedIndices", vec![i + 1]),
+                        index,
+                    )));
+                    updates.push((StateRef::Basic("valueHasBeenValidated"), StateVarValue::Boolean(false)));
+
+                    updates
+                },
+
+                "submitAnswer" => {
+                    vec![(StateRef::Basic("valueHasBeenValidated"), StateVarValue::Boolean(true))]
+                },
+
+                _ => {
+                    log!("Unknown action '{}' called on choiceInput", action_name);
+                    vec![]
+                },
+            }
+        },
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+use crate::utils::log;
+
+use super::*;
+
+
+
+/// The center, from the point component in `center` when there is one,
+/// otherwise from the numbers in `center`. Moving the circle moves that point.
+fn center_definition() -> StateVarVariant {
+    use StateVarUpdateInstruction::*;
+
+    StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+        initial_essential_element_value: 0.0,
+        initial_essential_size: 2,
+
+        return_element_dependency_instructions: |i, _| {
+            HashMap::from([
+                ("attribute", DependencyInstruction::Attribute {
+                    attribute_name: "center",
+                    index: StateIndex::Element(vec![i]),
+                }),
+                ("center_point", DependencyInstruction::AttributeComponent {
+                    attribute_name: "center",
+                    index: 1,
+                    state_var: StateVarSlice::Single(StateRef::ArrayElement("xs", vec![i])),
+                }),
+            ])
+        },
+
+        determine_element_from_dependencies: |_, dependency_values| {
+            let (center_point, _) = dependency_values.dep_value("center_point")?;
+            if let Some(center_point) = center_point.first() {
+                return Ok( SetValue( center_point.value.clone().try_into()? ) )
+            }
+
+            let (attribute, _) = dependency_values.dep_value("attribute")?;
+            if attribute.is_empty() {
+                return Ok( SetValue( 0.0 ) )
+            }
+            Ok( SetValue( DETERMINE_NUMBER(attribute).unwrap_or(f64::NAN) ) )
+        },
+
+        request_element_dependencies_to_update_value: |_, desired_value, sources| {
+            let center_point_sources = sources.get("center_point").unwrap();
+            if let Some((source, _)) = center_point_sources.first() {
+                return HashMap::from([
+                    ("center_point", Ok(vec![DependencyValue {
+                        source: source.clone(),
+                        value: StateVarValue::Number(desired_value),
+                    }])),
+                ])
+            }
+
+            let attribute_sources = sources.get("attribute").unwrap();
+            HashMap::from([
+                ("attribute", DETERMINE_NUMBER_DEPENDENCIES(desired_value, attribute_sources)),
+            ])
+        },
+
+        return_size_dependency_instructions: |_| {
+            HashMap::from([
+                ("attribute", DependencyInstruction::Attribute {
+                    attribute_name: "center",
+                    index: StateIndex::SizeOf,
+                }),
+                ("center_point", DependencyInstruction::AttributeComponent {
+                    attribute_name: "center",
+                    index: 1,
+                    state_var: StateVarSlice::Single(StateRef::SizeOf("xs")),
+                }),
+            ])
+        },
+
+        determine_size_from_dependencies: |dependency_values| {
+            let (center_point, _) = dependency_values.dep_value("center_point")?;
+            let size: i64 = match center_point.first() {
+                Some(center_point) => center_point.value.clone().try_into()?,
+                None => dependency_values.dep_value("attribute")?
+                    .has_exactly_one_element()?
+                    .into_integer()?,
+            };
+            Ok( SetValue( size as usize ) )
+        },
+
+        ..Default::default()
+    })
+}
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("center", center_definition());
+        state_var_definitions.insert("radius", number_definition_from_attribute!("radius", 1.0));
+
+        // A point on the circle. When it is not given it has no elements.
+        state_var_definitions.insert("through", number_array_definition_from_attribute!("through", 0.0, 0));
+
+        state_var_definitions.insert("numericalCenter", StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("center", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("center"),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let center = dependency_values.dep_value("center")?
+                    .into_number_list()?;
+                let my_coord = *center.get(index).unwrap();
+
+                Ok( SetValue( my_coord ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("dimensions", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("center")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let dim = dependency_values.dep_value("dimensions")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok( SetValue( dim as usize ) )
+            },
+
+            ..Default::default()
+        }));
+
+        // The distance to the through point when there is one, otherwise the radius
+        state_var_definitions.insert("numericalRadius", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("radius", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("radius")),
+                    }),
+                    ("center", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("center"),
+                    }),
+                    ("through", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("through"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let radius = dependency_values.dep_value("radius")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+                let center = dependency_values.dep_value("center")?
+                    .into_number_list()?;
+                let through = dependency_values.dep_value("through")?
+                    .into_number_list()?;
+
+                if through.is_empty() {
+                    return Ok( SetValue( radius ) )
+                }
+
+                let distance = center.iter().zip(through.iter())
+                    .map(|(c, t)| (t - c).powi(2))
+                    .fold(0.0, |total, square| total + square)
+                    .sqrt();
+
+                Ok( SetValue( distance ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("geometry", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("center", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalCenter"),
+                    }),
+                    ("radius", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("numericalRadius")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let center = dependency_values.dep_value("center")?
+                    .into_number_list()?;
+                let radius = dependency_values.dep_value("radius")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+
+                Ok( SetValue( Geometry::Circle { center, radius }.to_json() ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("latex", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("center", DependencyInstruction::StateVar{
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalCenter"),
+                    }),
+                    ("radius", DependencyInstruction::StateVar{
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("numericalRadius")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+
+                let center = dependency_values.dep_value("center")?
+                    .into_number_list()?;
+                let radius = dependency_values.dep_value("radius")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+                // The equation is only written for a circle in the plane
+                let [x, y] = center[..] else {
+                    return Ok( SetNull )
+                };
+                let set_value = format!("(x - {})^2 + (y - {})^2 = {}^2", x, y, radius);
+
+                Ok( SetValue( set_value ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
+        state_var_definitions.insert("filled", boolean_definition_from_attribute!("filled", false));
+        state_var_definitions.insert("draggable", boolean_definition_from_attribute!("draggable", true));
+        state_var_definitions.insert("fixLocation", boolean_definition_from_attribute!("fixLocation", false));
+        state_var_definitions.insert("fixed", FIXED_DEFAULT_DEFINITION());
+        state_var_definitions.insert("hideOffGraphIndicator", boolean_definition_from_attribute!("hideOffGraphIndicator", false));
+
+        state_var_definitions.insert("labelHasLatex", boolean_definition_from_attribute!("labelHasLatex", false));
+
+        // Graphical
+        state_var_definitions.insert("showLabel", boolean_definition_from_attribute!("showLabel", true));
+        state_var_definitions.insert("applyStyleToLabel", boolean_definition_from_attribute!("applyStyleToLabel", true));
+        state_var_definitions.insert("layer", integer_definition_from_attribute!("layer", 0));
+        state_var_definitions.insert("labelForGraph", string_definition_from_attribute!("label", ""));
+
+        // Base
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "circle",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "center",
+            "radius",
+            "through",
+
+            "filled",
+            "draggable",
+            "fixLocation",
+            "hideOffGraphIndicator",
+            "labelHasLatex",
+            "styleNumber",
+
+            "showLabel",
+            "applyStyleToLabel",
+            "layer",
+            "label",
+
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Text, "latex"),
+            (ComponentProfile::Graphical, "geometry"),
+        ],
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
+        action_names: || vec!["moveCircle", "circleClicked", "circleFocused"],
+
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
+            match action_name {
+                "moveCircle" => {
+                    let sizes = resolve_and_retrieve_state_var(&StateRef::SizeOf("center"))
+                        .and_then(|size| i64::try_from(size).ok())
+                        .zip(resolve_and_retrieve_state_var(&StateRef::SizeOf("through"))
+                            .and_then(|size| i64::try_from(size).ok()));
+                    let Some((center_size, through_size)) = sizes else {
+                        log!("Ignoring moveCircle on a circle whose center and through sizes could not be resolved");
+                        return vec![];
+                    };
+
+                    let center: Option<Vec<f64>> = args.get("center")
+                        .and_then(|center| center.iter()
+                            .map(|coord| coord.clone().try_into().ok())
+                            .collect());
+                    let Some(center) = center.filter(|center| center.len() as i64 == center_size) else {
+                        log!("Ignoring moveCircle with center {:?} for a circle of dimension {}", args.get("center"), center_size);
+                        return vec![];
+                    };
+
+                    let mut updates = Vec::new();
+
+                    // A circle defined by a through point keeps its radius by moving that point along
+                    for (i, new_coord) in center.into_iter().enumerate() {
+                        if (i as i64) < through_size {
+                            let coords = resolve_and_retrieve_state_var(&StateRef::ArrayElement("center", vec![i + 1]))
+                                .and_then(|coord| f64::try_from(coord).ok())
+                                .zip(resolve_and_retrieve_state_var(&StateRef::ArrayElement("through", vec![i + 1]))
+                                    .and_then(|coord| f64::try_from(coord).ok()));
+                            let Some((old_coord, through_coord)) = coords else {
+                                log!("Ignoring moveCircle on a circle whose center or through point could not be resolved");
+                                return vec![];
+                            };
+
+                            updates.push((
+                                StateRef::ArrayElement("through", vec![i + 1]),
+                                StateVarValue::Number(through_coord + new_coord - old_coord),
+                            ));
+                        }
+
+                        updates.push((StateRef::ArrayElement("center", vec![i + 1]), StateVarValue::Number(new_coord)));
+                    }
+
+                    if through_size == 0 {
+                        if let Some(radius) = args.get("radius").and_then(|radius| radius.first()) {
+                            updates.push((StateRef::Basic("radius"), radius.clone()));
+                        }
+                    }
+
+                    updates
+                },
+                "circleClicked" => {
+                    vec![]
+                }
+                "circleFocused" => {
+                    vec![]
+                }
+
+                _ => {
+                    log!("Unknown action '{}' called on circle", action_name);
+                    vec![]
+                }
+            }
+        },
+
+        ..Default::default()
+    };
+}
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use lazy_static::lazy_static;
 
 use crate::base_definitions::*;
+use crate::parse_json::DoenetMLError;
 
 use super::*;
 
@@ -16,11 +17,49 @@ lazy_static!{
     };
 }
 
+/// The types given by the comma-separated componentType static attribute,
+/// which check_static_attributes makes sure exist
+pub fn component_types(
+    values: &HashMap<AttributeName, String>,
+) -> Vec<ComponentType> {
+    values.get("componentType").unwrap()
+        .split(',')
+        .map(|component_type| COMPONENT_DEFINITIONS
+            .get_key_value_ignore_case(component_type.trim())
+            .unwrap().0)
+        .copied()
+        .collect()
+}
+
+/// An error for a missing source or componentType, or a type that does not exist
+pub fn check_static_attributes(
+    comp_name: &ComponentName,
+    values: &HashMap<AttributeName, String>,
+) -> Result<(), DoenetMLError> {
+    for attr_name in ["source", "componentType"] {
+        if !values.contains_key(attr_name) {
+            return Err(DoenetMLError::AttributeDoesNotExist {
+                comp_name: comp_name.clone(),
+                attr_name: attr_name.to_string(),
+            });
+        }
+    }
+    for component_type in values.get("componentType").unwrap().split(',') {
+        if COMPONENT_DEFINITIONS.get_key_value_ignore_case(component_type.trim()).is_none() {
+            return Err(DoenetMLError::InvalidComponentType {
+                comp_type: component_type.trim().to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// With several types, this is the definition of the first one.
+/// The definition of a particular member is found from the member itself.
 fn member_definition(
     values: &HashMap<AttributeName, String>,
 ) -> &'static ComponentDefinition {
-    let component_type = values.get("componentType").unwrap();
-    COMPONENT_DEFINITIONS.get_key_value_ignore_case(component_type.as_str()).unwrap().1
+    COMPONENT_DEFINITIONS.get(component_types(values)[0]).unwrap()
 }
 
 fn collection_members(
@@ -28,12 +67,14 @@ fn collection_members(
     component_nodes: &HashMap<ComponentName, ComponentNode>,
 ) -> Vec<CollectionMembersOrCollection> {
 
-    let my_attributes = &node.static_attributes;
-    let source = my_attributes.get("source").unwrap();
-    let desired_type = my_attributes.get("componentType").unwrap();
-    let desired_type = COMPONENT_DEFINITIONS.get_key_value_ignore_case(desired_type).unwrap().0;
+    let source = node.static_attributes.get("source").unwrap();
+    let desired_types = component_types(&node.static_attributes);
     let source_node = component_nodes.get(source).unwrap();
-    members_from_children_of_type(component_nodes, source_node, desired_type)
+    if instances_a_template(source_node) {
+        members_from_instances_of_types(component_nodes, source_node, &desired_types, true)
+    } else {
+        members_from_children_of_types(component_nodes, source_node, &desired_types, true)
+    }
 }
 
 lazy_static! {
@@ -50,6 +91,8 @@ lazy_static! {
         static_attribute_names: vec![
             "source",
             "componentType",
+            "maxNumber",
+            "assignNames",
         ],
 
         should_render_children: true,
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+
+use super::*;
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("dx", number_definition_from_attribute!("dx", 1.0));
+        state_var_definitions.insert("dy", number_definition_from_attribute!("dy", 1.0));
+        state_var_definitions.insert("xoffset", number_definition_from_attribute!("xoffset", 0.0));
+        state_var_definitions.insert("yoffset", number_definition_from_attribute!("yoffset", 0.0));
+
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                let state_var = |name| DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Single(StateRef::Basic(name)),
+                };
+                HashMap::from([
+                    ("dx", state_var("dx")),
+                    ("dy", state_var("dy")),
+                    ("xoffset", state_var("xoffset")),
+                    ("yoffset", state_var("yoffset")),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let number = |name| dependency_values.dep_value(name)?
+                    .has_exactly_one_element()?
+                    .into_number();
+
+                let constraint = Constraint::ConstrainToGrid {
+                    dx: number("dx")?,
+                    dy: number("dy")?,
+                    xoffset: number("xoffset")?,
+                    yoffset: number("yoffset")?,
+                };
+
+                Ok( SetValue( constraints_to_json(&[constraint]) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "constrainToGrid",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "dx",
+            "dy",
+            "xoffset",
+            "yoffset",
+
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Constraint, "constraints"),
+        ],
+
+        ..Default::default()
+    };
+}
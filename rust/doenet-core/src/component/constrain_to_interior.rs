@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+
+use super::*;
+use super::constraints::*;
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("objects", graphical_children_instruction()),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let constraint = Constraint::ConstrainToInterior {
+                    objects: objects_from_dependency_values(&dependency_values)?,
+                };
+
+                Ok( SetValue( constraints_to_json(&[constraint]) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "constrainToInterior",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Constraint, "constraints"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Graphical]),
+
+        ..Default::default()
+    };
+}
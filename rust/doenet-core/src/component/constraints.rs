@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+
+use super::*;
+
+
+
+/// The shapes given as children of a constraint
+pub(super) fn graphical_children_instruction() -> DependencyInstruction {
+    DependencyInstruction::Child {
+        desired_profiles: vec![ComponentProfile::Graphical],
+        parse_into_expression: false,
+    }
+}
+
+/// The constraints given as children, in the order they apply
+pub(super) fn constraint_children_instruction() -> DependencyInstruction {
+    DependencyInstruction::Child {
+        desired_profiles: vec![ComponentProfile::Constraint],
+        parse_into_expression: false,
+    }
+}
+
+pub(super) fn constraints_from_dependency_values(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<Vec<Constraint>, String> {
+    let constraints = dependency_values.dep_value("constraint_children")?
+        .into_string_list()?
+        .iter()
+        .map(|json| constraints_from_json(json))
+        .collect::<Result<Vec<Vec<Constraint>>, String>>()?;
+    Ok(constraints.concat())
+}
+
+pub(super) fn objects_from_dependency_values(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<Vec<Geometry>, String> {
+    Ok(dependency_values.dep_value("objects")?
+        .into_string_list()?
+        .iter()
+        .filter_map(|json| Geometry::from_json(json))
+        .collect())
+}
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([("constraint_children", constraint_children_instruction())])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let constraints = constraints_from_dependency_values(&dependency_values)?;
+
+                Ok( SetValue( constraints_to_json(&constraints) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "constraints",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Constraint, "constraints"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Constraint]),
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,551 @@
+use std::collections::{BTreeMap, HashMap};
+
+use lazy_static::lazy_static;
+use serde_json::json;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::sampling::*;
+use crate::utils::log;
+
+use super::*;
+use super::function::*;
+
+
+
+/// The control vectors that were dragged, by the index of their through point
+type MovedControlVectors = BTreeMap<usize, [[f64; 2]; 2]>;
+
+fn through_points(coords: &[f64]) -> Vec<[f64; 2]> {
+    coords.chunks_exact(2).map(|c| [c[0], c[1]]).collect()
+}
+
+/// Pairs of vectors from a flat list of coordinates
+fn control_vector_list(coords: &[f64]) -> Vec<[[f64; 2]; 2]> {
+    coords.chunks_exact(4).map(|c| [[c[0], c[1]], [c[2], c[3]]]).collect()
+}
+
+fn moved_control_vectors_from_json(json: &str) -> MovedControlVectors {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+fn state_var_instruction(name: StateVarName) -> DependencyInstruction {
+    DependencyInstruction::StateVar {
+        component_ref: None,
+        state_var: StateVarSlice::Single(StateRef::Basic(name)),
+    }
+}
+
+fn through_instruction() -> DependencyInstruction {
+    DependencyInstruction::StateVar {
+        component_ref: None,
+        state_var: StateVarSlice::Array("through"),
+    }
+}
+
+fn control_vectors_instruction() -> DependencyInstruction {
+    DependencyInstruction::StateVar {
+        component_ref: None,
+        state_var: StateVarSlice::Array("controlVectors"),
+    }
+}
+
+/// One row of two control points or vectors for each through point
+fn num_through_points_size(
+    dependency_values: HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<StateVarUpdateInstruction<usize>, String> {
+    let num_through_points = dependency_values.dep_value("numThroughPoints")?
+        .has_exactly_one_element()?
+        .into_integer()?;
+    Ok( StateVarUpdateInstruction::SetValue( num_through_points as usize ) )
+}
+
+/// A parameter limit: the one given for a parameterization, through the ends of a Bézier curve,
+/// or like a function graph's
+fn curve_par_limit(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+    function_limit_of: fn(f64, f64) -> f64,
+    bezier_limit_of: fn(usize) -> f64,
+) -> Result<f64, String> {
+    let curve_type = dependency_values.dep_value("curveType")?
+        .has_exactly_one_element()?
+        .into_string()?;
+
+    match curve_type.as_str() {
+        "bezier" => {
+            let num_through_points = dependency_values.dep_value("numThroughPoints")?
+                .has_exactly_one_element()?
+                .into_integer()?;
+            Ok( bezier_limit_of(num_through_points as usize) )
+        },
+        "parameterization" => dependency_values.dep_value("specifiedParLimit")?
+            .has_exactly_one_element()?
+            .into_number(),
+        _ => function_par_limit(dependency_values, function_limit_of),
+    }
+}
+
+fn curve_par_limit_instructions(specified: StateVarName) -> HashMap<InstructionName, DependencyInstruction> {
+    let mut instructions = function_par_limit_instructions();
+    instructions.extend([
+        ("curveType", state_var_instruction("curveType")),
+        ("numThroughPoints", state_var_instruction("numThroughPoints")),
+        ("specifiedParLimit", state_var_instruction(specified)),
+    ]);
+    instructions
+}
+
+/// The numbers of an action argument, or None when one is not a number
+fn number_args(args: &HashMap<String, Vec<StateVarValue>>, name: &str) -> Option<Vec<f64>> {
+    args.get(name)?.iter()
+        .map(|value| value.clone().try_into().ok())
+        .collect()
+}
+
+fn integer_arg(args: &HashMap<String, Vec<StateVarValue>>, name: &str) -> Option<i64> {
+    args.get(name)?.first()?.clone().try_into().ok()
+}
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        // The points of a Bézier curve, as a flat list of coordinates.
+        // When it is not given it has no elements.
+        state_var_definitions.insert("through", number_array_definition_from_attribute!("through", 0.0, 0));
+
+        state_var_definitions.insert("numThroughPoints", StateVarVariant::Integer(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("through_size", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("through")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("through_size")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok( SetValue( size / 2 ) )
+            },
+            ..Default::default()
+        }));
+
+        // Bézier through the points, otherwise parameterized by two functions,
+        // otherwise the graph of one function
+        state_var_definitions.insert("curveType", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("numThroughPoints", state_var_instruction("numThroughPoints")),
+                    ("function_children", function_children_instruction()),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let num_through_points = dependency_values.dep_value("numThroughPoints")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                let functions = dependency_values.dep_value("function_children")?
+                    .into_string_list()?;
+
+                let curve_type = if num_through_points > 0 {
+                    "bezier"
+                } else if functions.len() >= 2 {
+                    "parameterization"
+                } else {
+                    "function"
+                };
+                Ok( SetValue( curve_type.to_string() ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("specifiedParMin", number_definition_from_attribute!("parMin", -10.0));
+        state_var_definitions.insert("specifiedParMax", number_definition_from_attribute!("parMax", 10.0));
+        state_var_definitions.insert("flipFunction", boolean_definition_from_attribute!("flipFunction", false));
+
+        state_var_definitions.extend(graph_limit_state_var_definitions());
+
+        state_var_definitions.insert("parMin", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: |_| curve_par_limit_instructions("specifiedParMin"),
+            determine_state_var_from_dependencies: |dependency_values| {
+                curve_par_limit(
+                    &dependency_values,
+                    |min, max| min - (max - min) * 0.1,
+                    |_| 0.0,
+                ).map(SetValue)
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("parMax", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: |_| curve_par_limit_instructions("specifiedParMax"),
+            determine_state_var_from_dependencies: |dependency_values| {
+                curve_par_limit(
+                    &dependency_values,
+                    |min, max| max + (max - min) * 0.1,
+                    |num_through_points| num_through_points.saturating_sub(1) as f64,
+                ).map(SetValue)
+            },
+            ..Default::default()
+        }));
+
+        // One row for each through point
+        state_var_definitions.insert("numericalThroughPoints", StateVarVariant::NumberArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([("through", through_instruction())])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let through = dependency_values.dep_value("through")?
+                    .into_number_list()?;
+                Ok( SetValue( *through.get(index).unwrap() ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("through_size", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("through")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("through_size")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok( SetValue( size as usize / 2 ) )
+            },
+
+            inner_dimensions: &[2],
+
+            ..Default::default()
+        }));
+
+        // JSON of the control vectors that were dragged, which replace the default ones
+        state_var_definitions.insert("movedControlVectors", StateVarVariant::String(StateVarDefinition {
+            initial_essential_value: "{}".to_string(),
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+            ..Default::default()
+        }));
+
+        // The vectors from each through point to the control points before and after it
+        state_var_definitions.insert("controlVectors", StateVarVariant::NumberArray(StateVarArrayDefinition {
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("through", through_instruction()),
+                    ("movedControlVectors", state_var_instruction("movedControlVectors")),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let through = dependency_values.dep_value("through")?
+                    .into_number_list()?;
+                let moved = dependency_values.dep_value("movedControlVectors")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                let moved = moved_control_vectors_from_json(&moved);
+
+                let mut control_vectors = default_control_vectors(&through_points(&through));
+                for (point, vectors) in moved {
+                    if let Some(control_vector) = control_vectors.get_mut(point) {
+                        *control_vector = vectors;
+                    }
+                }
+
+                let vectors = control_vectors.get(index / 4).ok_or("No through point for control vector")?;
+                Ok( SetValue( vectors[index / 2 % 2][index % 2] ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([("numThroughPoints", state_var_instruction("numThroughPoints"))])
+            },
+            determine_size_from_dependencies: num_through_points_size,
+
+            inner_dimensions: &[2, 2],
+
+            ..Default::default()
+        }));
+
+        // The control points before and after each through point
+        state_var_definitions.insert("numericalControlPoints", StateVarVariant::NumberArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("through", through_instruction()),
+                    ("controlVectors", control_vectors_instruction()),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let through = dependency_values.dep_value("through")?
+                    .into_number_list()?;
+                let control_vectors = dependency_values.dep_value("controlVectors")?
+                    .into_number_list()?;
+
+                let point_coord = through.get(index / 4 * 2 + index % 2).ok_or("No through point for control point")?;
+                let vector_coord = control_vectors.get(index).ok_or("No control vector for control point")?;
+                Ok( SetValue( point_coord + vector_coord ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([("numThroughPoints", state_var_instruction("numThroughPoints"))])
+            },
+            determine_size_from_dependencies: num_through_points_size,
+
+            inner_dimensions: &[2, 2],
+
+            ..Default::default()
+        }));
+
+        // Dragging one control point of a through point moves the other opposite it
+        state_var_definitions.insert("vectorControlDirections", StateVarVariant::StringArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            determine_element_from_dependencies: |_, _| {
+                Ok( SetValue( "symmetric".to_string() ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([("numThroughPoints", state_var_instruction("numThroughPoints"))])
+            },
+
+            determine_size_from_dependencies: num_through_points_size,
+
+            ..Default::default()
+        }));
+
+        // Whether the controls of each through point are hidden
+        state_var_definitions.insert("hiddenControls", StateVarVariant::BooleanArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            determine_element_from_dependencies: |_, _| {
+                Ok( SetValue( false ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([("numThroughPoints", state_var_instruction("numThroughPoints"))])
+            },
+            determine_size_from_dependencies: num_through_points_size,
+
+            ..Default::default()
+        }));
+
+        // JSON of the renderer's definitions of the functions that draw the curve
+        state_var_definitions.insert("fDefinitions", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+            reshape_for_renderer: PARSE_JSON_FOR_RENDERER,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("curveType", state_var_instruction("curveType")),
+                    ("function_children", function_children_instruction()),
+                    ("parMin", state_var_instruction("parMin")),
+                    ("parMax", state_var_instruction("parMax")),
+                    ("through", through_instruction()),
+                    ("controlVectors", control_vectors_instruction()),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let curve_type = dependency_values.dep_value("curveType")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                let functions: Vec<MathFunction> = dependency_values.dep_value("function_children")?
+                    .into_string_list()?
+                    .iter()
+                    .filter_map(|json| MathFunction::from_json(json))
+                    .collect();
+                let par_min = dependency_values.dep_value("parMin")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+                let par_max = dependency_values.dep_value("parMax")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+
+                let definitions = match (curve_type.as_str(), functions.as_slice()) {
+                    ("bezier", _) => {
+                        let through = dependency_values.dep_value("through")?
+                            .into_number_list()?;
+                        let control_vectors = dependency_values.dep_value("controlVectors")?
+                            .into_number_list()?;
+
+                        bezier_function_definitions(
+                            &through_points(&through),
+                            &control_vector_list(&control_vectors),
+                        )
+                    },
+                    ("parameterization", [x_function, y_function, ..]) => {
+                        match (x_function.compile(), y_function.compile()) {
+                            (Some(x), Some(y)) => {
+                                // Sampled together so that both use the same parameter values
+                                let (ts, points) = sample_adaptively(|t| vec![x(t), y(t)], par_min, par_max);
+                                (0..2).map(|c| {
+                                    let values: Vec<f64> = points.iter().map(|p| p[c]).collect();
+                                    interpolated_function_definition(&ts, &values)
+                                }).collect()
+                            },
+                            _ => vec![interpolated_function_definition(&[], &[]); 2],
+                        }
+                    },
+                    (_, [function, ..]) => vec![sampled_function_definition(function, par_min, par_max)],
+                    (_, []) => vec![interpolated_function_definition(&[], &[])],
+                };
+
+                Ok( SetValue( json!(definitions).to_string() ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
+        state_var_definitions.insert("draggable", boolean_definition_from_attribute!("draggable", true));
+        state_var_definitions.insert("fixed", FIXED_DEFAULT_DEFINITION());
+        state_var_definitions.insert("bezierControlsAlwaysVisible", boolean_definition_from_attribute!("bezierControlsAlwaysVisible", false));
+        state_var_definitions.insert("extrapolateBackward", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( false ) )
+            },
+            ..Default::default()
+        }));
+        state_var_definitions.insert("extrapolateForward", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( false ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("labelPosition", string_definition_from_attribute!("labelPosition", "upperright"));
+        state_var_definitions.insert("labelHasLatex", boolean_definition_from_attribute!("labelHasLatex", false));
+
+        // Graphical
+        state_var_definitions.insert("showLabel", boolean_definition_from_attribute!("showLabel", true));
+        state_var_definitions.insert("applyStyleToLabel", boolean_definition_from_attribute!("applyStyleToLabel", true));
+        state_var_definitions.insert("layer", integer_definition_from_attribute!("layer", 0));
+        state_var_definitions.insert("label", string_definition_from_attribute!("label", ""));
+
+        // Base
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "curve",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "through",
+            "parMin",
+            "parMax",
+            "flipFunction",
+            "draggable",
+            "bezierControlsAlwaysVisible",
+            "labelPosition",
+            "labelHasLatex",
+            "styleNumber",
+
+            "showLabel",
+            "applyStyleToLabel",
+            "layer",
+            "label",
+
+            "hide",
+            "disabled",
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Function]),
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
+        action_names: || vec!["moveThroughPoint", "moveControlVector", "switchCurve"],
+
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
+            match action_name {
+                "moveThroughPoint" => {
+                    let num_through_points: i64 = resolve_and_retrieve_state_var(&StateRef::Basic("numThroughPoints"))
+                        .unwrap().try_into().unwrap();
+
+                    // Note: the key here is whatever the renderers call the new value
+                    let index = integer_arg(&args, "throughPointInd")
+                        .filter(|index| (0..num_through_points).contains(index));
+                    let point = number_args(&args, "throughPoint")
+                        .filter(|point| point.len() == 2);
+                    let (Some(index), Some(point)) = (index, point) else {
+                        log!("Ignoring moveThroughPoint with {:?} for a curve with {} through points", args, num_through_points);
+                        return vec![];
+                    };
+
+                    point.into_iter().enumerate().map(|(i, coord)| {
+                        (StateRef::ArrayElement("through", vec![2 * index as usize + i + 1]), StateVarValue::Number(coord))
+                    }).collect()
+                },
+
+                "moveControlVector" => {
+                    let num_through_points: i64 = resolve_and_retrieve_state_var(&StateRef::Basic("numThroughPoints"))
+                        .unwrap().try_into().unwrap();
+
+                    let vector = number_args(&args, "controlVector")
+                        .filter(|vector| vector.len() == 2);
+                    let indices = args.get("controlVectorInds")
+                        .and_then(|indices| indices.iter()
+                            .map(|index| i64::try_from(index.clone()).ok())
+                            .collect::<Option<Vec<i64>>>())
+                        .filter(|indices| matches!(indices[..], [point, 0 | 1] if (0..num_through_points).contains(&point)));
+                    let (Some(vector), Some(indices)) = (vector, indices) else {
+                        log!("Ignoring moveControlVector with {:?} for a curve with {} through points", args, num_through_points);
+                        return vec![];
+                    };
+                    let (point, side) = (indices[0] as usize, indices[1]);
+
+                    let moved: String = resolve_and_retrieve_state_var(&StateRef::Basic("movedControlVectors"))
+                        .unwrap().try_into().unwrap();
+                    let mut moved = moved_control_vectors_from_json(&moved);
+
+                    // The vector on the other side is symmetric
+                    let vector = [vector[0], vector[1]];
+                    let opposite = [-vector[0], -vector[1]];
+                    let vectors = if side == 0 { [vector, opposite] } else { [opposite, vector] };
+                    moved.insert(point, vectors);
+
+                    vec![(
+                        StateRef::Basic("movedControlVectors"),
+                        StateVarValue::String(json!(moved).to_string()),
+                    )]
+                },
+
+                "switchCurve" => {
+                    vec![]
+                },
+
+                _ => {
+                    log!("Unknown action '{}' called on curve", action_name);
+                    vec![]
+                }
+            }
+        },
+
+        ..Default::default()
+    };
+}
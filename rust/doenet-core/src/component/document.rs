@@ -4,6 +4,7 @@ use lazy_static::lazy_static;
 
 use crate::state_variables::*;
 use crate::base_definitions::*;
+use crate::style::*;
 
 use super::*;
 
@@ -16,30 +17,39 @@ lazy_static! {
 
         let mut state_var_definitions = HashMap::new();
         
-        state_var_definitions.insert("submitLabel", StateVarVariant::String(StateVarDefinition {
-            determine_state_var_from_dependencies: |_| Ok(SetValue("Check Work".to_string())),
+        state_var_definitions.insert("hidden", StateVarVariant::Boolean(Default::default()));
+
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        state_var_definitions.insert("fixed", StateVarVariant::Boolean(StateVarDefinition {
             for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("read_only", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("readOnly")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let read_only = dependency_values.dep_value("read_only")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                Ok(SetValue(read_only))
+            },
+
             ..Default::default()
         }));
 
-        state_var_definitions.insert("submitLabelNoCorrectness", StateVarVariant::String(StateVarDefinition {
-            determine_state_var_from_dependencies: |_| Ok(SetValue("Submit Response".to_string())),
+        state_var_definitions.insert("titleChildName", StateVarVariant::String(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| Ok(SetNull),
             for_renderer: true,
             ..Default::default()
         }));
 
-        state_var_definitions.insert("hidden", StateVarVariant::Boolean(Default::default()));
-
-        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
-
-        state_var_definitions.insert("fixed", FIXED_DEFAULT_DEFINITION());
-
-        // state_var_definitions.insert("titleChildName", StateVarVariant::String(StateVarDefinition {
-        //     determine_state_var_from_dependencies: |_| Ok(SetValue("Submit Response")),
-        //     for_renderer: true,
-        //     ..Default::default()
-        // })); 
-
         
         state_var_definitions.insert("title", StateVarVariant::String(StateVarDefinition {
             determine_state_var_from_dependencies: |_| Ok(SetValue("".to_string())),
@@ -53,15 +63,79 @@ lazy_static! {
             ..Default::default()
         }));
 
+        // Whether every answer in the document has been submitted since it last changed
         state_var_definitions.insert("justSubmitted", StateVarVariant::Boolean(StateVarDefinition {
-            determine_state_var_from_dependencies: |_| Ok(SetValue(true)),
             for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("inputs_validated", DependencyInstruction::ComponentsOfType {
+                        component_type: "choiceInput",
+                        state_var: "valueHasBeenValidated",
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let inputs_validated = dependency_values.dep_value("inputs_validated")?
+                    .into_bool_list()?;
+
+                Ok(SetValue(!inputs_validated.contains(&false)))
+            },
+
             ..Default::default()
         }));
 
-        state_var_definitions.insert("showCorrectness", StateVarVariant::Boolean(StateVarDefinition {
-            determine_state_var_from_dependencies: |_| Ok(SetValue(true)),
+        // These hold the CoreFlags, which are set when the core is created
+        state_var_definitions.insert("showCorrectness", core_flag_definition(true, true));
+        state_var_definitions.insert("readOnly", core_flag_definition(false, false));
+        state_var_definitions.insert("showFeedback", core_flag_definition(true, false));
+        state_var_definitions.insert("showHints", core_flag_definition(true, false));
+
+        state_var_definitions.insert("submitLabel", StateVarVariant::String(StateVarDefinition {
             for_renderer: true,
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            initial_essential_value: "Check Work".to_string(),
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("submitLabelNoCorrectness", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            initial_essential_value: "Submit Response".to_string(),
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("variantIndex", StateVarVariant::Integer(StateVarDefinition {
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            initial_essential_value: 1,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("solutionDisplayMode", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            initial_essential_value: "button".to_string(),
+            ..Default::default()
+        }));
+
+        // The default style definitions changed by <styleDefinitions> children
+        state_var_definitions.insert("styleDefinitions", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([("style_definition_children", style_definitions::style_definition_children_instruction())])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let changes = style_definitions::style_changes_from_dependency_values(&dependency_values)?;
+
+                let mut definitions = default_style_definitions();
+                apply_style_changes(&mut definitions, &changes);
+
+                Ok( SetValue( style_definitions_to_json(&definitions) ) )
+            },
             ..Default::default()
         }));
 
@@ -71,6 +145,25 @@ lazy_static! {
             ..Default::default()
         }));
 
+        // Core-level permission checked by <solution> before it can be revealed
+        state_var_definitions.insert("canRevealSolutions", StateVarVariant::Boolean(StateVarDefinition {
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+            initial_essential_value: true,
+            ..Default::default()
+        }));
+
+        // Whether set_solution_display_permission allowed solutions, which keeps
+        // canRevealSolutions when the core is created again from saved state
+        state_var_definitions.insert("solutionPermissionGranted", StateVarVariant::Boolean(StateVarDefinition {
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+            initial_essential_value: false,
+            ..Default::default()
+        }));
+
         state_var_definitions.insert("createSubmitAllButton", StateVarVariant::Boolean(StateVarDefinition {
             determine_state_var_from_dependencies: |_| Ok(SetValue(false)),
             for_renderer: true,
@@ -90,6 +183,18 @@ lazy_static! {
 
 
 
+/// A flag cannot be changed by update requests, only by creating the core again
+fn core_flag_definition(default: bool, for_renderer: bool) -> StateVarVariant {
+    StateVarVariant::Boolean(StateVarDefinition {
+        for_renderer,
+        return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+        determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+        initial_essential_value: default,
+        ..Default::default()
+    })
+}
+
+
 lazy_static! {
     pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
         component_type: "document",
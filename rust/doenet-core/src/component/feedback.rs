@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::base_definitions::*;
+
+
+use lazy_static::lazy_static;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        // Defaults to false so that feedback stays hidden until its condition
+        // (typically on an <award>) or one of its feedbackCodes is met
+        state_var_definitions.insert("condition", boolean_definition_from_attribute!("condition", false));
+        state_var_definitions.insert("feedbackCodes", string_definition_from_attribute!("feedbackCodes", ""));
+
+        // Whether an <award> sharing one of the feedbackCodes is awarded
+        state_var_definitions.insert("codeAwarded", StateVarVariant::Boolean(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("feedback_codes", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("feedbackCodes")),
+                    }),
+                    ("award_codes", DependencyInstruction::ComponentsOfType {
+                        component_type: "award",
+                        state_var: "feedbackCodes",
+                    }),
+                    ("awards_awarded", DependencyInstruction::ComponentsOfType {
+                        component_type: "award",
+                        state_var: "awarded",
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let feedback_codes: String = dependency_values.dep_value("feedback_codes")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                let award_codes = dependency_values.dep_value("award_codes")?
+                    .into_string_list()?;
+                let awards_awarded = dependency_values.dep_value("awards_awarded")?
+                    .into_bool_list()?;
+
+                let code_awarded = award_codes.iter().zip(awards_awarded)
+                    .filter(|(_, awarded)| *awarded)
+                    .any(|(codes, _)| codes.split_whitespace()
+                        .any(|code| feedback_codes.split_whitespace().any(|c| c.eq_ignore_ascii_case(code)))
+                    );
+
+                Ok(SetValue(code_awarded))
+            },
+
+            ..Default::default()
+        }));
+
+        // Whether an answer in the document has been submitted
+        state_var_definitions.insert("submitted", StateVarVariant::Boolean(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("inputs_validated", DependencyInstruction::ComponentsOfType {
+                        component_type: "choiceInput",
+                        state_var: "valueHasBeenValidated",
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let inputs_validated = dependency_values.dep_value("inputs_validated")?
+                    .into_bool_list()?;
+
+                Ok(SetValue(inputs_validated.contains(&true)))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("parent_hidden", DependencyInstruction::Parent {
+                        state_var: "hidden",
+                    }),
+                    ("my_hide", DependencyInstruction::Attribute {
+                        attribute_name: "hide",
+                        index: crate::state_variables::StateIndex::Basic,
+                    }),
+                    ("condition", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("condition")),
+                    }),
+                    ("code_awarded", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("codeAwarded")),
+                    }),
+                    ("submitted", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("submitted")),
+                    }),
+                    ("show_feedback", DependencyInstruction::Document {
+                        state_var: "showFeedback",
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let parent_hidden = dependency_values.dep_value("parent_hidden")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                let (attribute, _) = dependency_values.dep_value("my_hide")?;
+                let my_hide = (!attribute.is_empty())
+                    .then(|| DETERMINE_BOOLEAN(attribute).ok())
+                    .flatten()
+                    .unwrap_or(false);
+
+                let condition = dependency_values.dep_value("condition")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                let code_awarded = dependency_values.dep_value("code_awarded")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                let submitted = dependency_values.dep_value("submitted")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                let show_feedback = dependency_values.dep_value("show_feedback")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                let matched = condition || code_awarded;
+                Ok(SetValue(parent_hidden || my_hide || !matched || !submitted || !show_feedback))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "feedback",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "condition",
+            "feedbackCodes",
+
+            "hide",
+            "disabled",
+        ],
+
+        should_render_children: true,
+
+        valid_children_profiles: ValidChildTypes::AllComponents,
+
+        ..Default::default()
+    };
+}
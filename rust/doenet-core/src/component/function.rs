@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::sampling::*;
+
+use super::*;
+
+
+
+/// The graph's limit when the parent is a graph, otherwise the default limit
+macro_rules! graph_limit_definition {
+    ( $limit:expr, $default:expr ) => {
+        StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([("limit", DependencyInstruction::ParentOfType {
+                    component_type: "graph",
+                    state_var: $limit,
+                })])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let limit: Option<f64> = dependency_values.dep_value("limit")?
+                    .has_zero_or_one_elements()?
+                    .into_if_exists()?;
+                Ok( StateVarUpdateInstruction::SetValue( limit.unwrap_or($default) ) )
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// The limits of the graph a curve is drawn in, so it is only sampled where it can be seen
+pub(super) fn graph_limit_state_var_definitions() -> HashMap<StateVarName, StateVarVariant> {
+    HashMap::from([
+        ("graphXmin", graph_limit_definition!("xmin", -10.0)),
+        ("graphXmax", graph_limit_definition!("xmax", 10.0)),
+        ("graphYmin", graph_limit_definition!("ymin", -10.0)),
+        ("graphYmax", graph_limit_definition!("ymax", 10.0)),
+    ])
+}
+
+/// The functions of `<function>` children, in order
+pub(super) fn function_children_instruction() -> DependencyInstruction {
+    DependencyInstruction::Child {
+        desired_profiles: vec![ComponentProfile::Function],
+        parse_into_expression: false,
+    }
+}
+
+/// The limits of a function graph: a little beyond the graph, like the renderer draws it
+pub(super) fn function_par_limit(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+    limit_of: fn(f64, f64) -> f64,
+) -> Result<f64, String> {
+    let flip_function = dependency_values.dep_value("flipFunction")?
+        .has_exactly_one_element()?
+        .into_bool()?;
+    let (min, max) = if flip_function { ("graphYmin", "graphYmax") } else { ("graphXmin", "graphXmax") };
+    let min = dependency_values.dep_value(min)?.has_exactly_one_element()?.into_number()?;
+    let max = dependency_values.dep_value(max)?.has_exactly_one_element()?.into_number()?;
+
+    Ok( limit_of(min, max) )
+}
+
+pub(super) fn function_par_limit_instructions() -> HashMap<InstructionName, DependencyInstruction> {
+    ["flipFunction", "graphXmin", "graphXmax", "graphYmin", "graphYmax"]
+        .map(|name| (name, DependencyInstruction::StateVar {
+            component_ref: None,
+            state_var: StateVarSlice::Single(StateRef::Basic(name)),
+        }))
+        .into()
+}
+
+/// The renderer's definition of the function sampled between `parMin` and `parMax`
+pub(super) fn sampled_function_definition(function: &MathFunction, par_min: f64, par_max: f64) -> serde_json::Value {
+    let Some(f) = function.compile() else {
+        return interpolated_function_definition(&[], &[])
+    };
+
+    let (xs, ys) = sample_adaptively(|x| vec![f(x)], par_min, par_max);
+    let ys: Vec<f64> = ys.into_iter().map(|y| y[0]).collect();
+    interpolated_function_definition(&xs, &ys)
+}
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("variable", string_definition_from_attribute!("variable", "x"));
+
+        state_var_definitions.insert("formula", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("children", DependencyInstruction::Child {
+                        desired_profiles: vec![ComponentProfile::Text],
+                        parse_into_expression: false,
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let children = dependency_values.get("children").unwrap();
+                DETERMINE_STRING(children.clone()).map(|formula| SetValue(formula.trim().to_string()))
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("function", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("variable", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("variable")),
+                    }),
+                    ("formula", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("formula")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let variable = dependency_values.dep_value("variable")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                let formula = dependency_values.dep_value("formula")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+
+                Ok( SetValue( MathFunction { variable, formula }.to_json() ) )
+            },
+            ..Default::default()
+        }));
+
+        // The graph of x against y instead of y against x
+        state_var_definitions.insert("flipFunction", boolean_definition_from_attribute!("flipFunction", false));
+
+        state_var_definitions.extend(graph_limit_state_var_definitions());
+
+        state_var_definitions.insert("parMin", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: |_| function_par_limit_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                function_par_limit(&dependency_values, |min, max| min - (max - min) * 0.1).map(SetValue)
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("parMax", StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: |_| function_par_limit_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                function_par_limit(&dependency_values, |min, max| max + (max - min) * 0.1).map(SetValue)
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("curveType", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( "function".to_string() ) )
+            },
+            ..Default::default()
+        }));
+
+        // A list of one definition, holding the samples of the function
+        state_var_definitions.insert("fDefinitions", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+            reshape_for_renderer: PARSE_JSON_FOR_RENDERER,
+
+            return_dependency_instructions: |_| {
+                ["function", "parMin", "parMax"]
+                    .map(|name| (name, DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic(name)),
+                    }))
+                    .into()
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let function = dependency_values.dep_value("function")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                let par_min = dependency_values.dep_value("parMin")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+                let par_max = dependency_values.dep_value("parMax")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+
+                let definition = match MathFunction::from_json(&function) {
+                    Some(function) => sampled_function_definition(&function, par_min, par_max),
+                    None => interpolated_function_definition(&[], &[]),
+                };
+                Ok( SetValue( serde_json::json!([definition]).to_string() ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
+        // A function graph cannot be dragged
+        state_var_definitions.insert("draggable", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( false ) )
+            },
+            ..Default::default()
+        }));
+        state_var_definitions.insert("fixed", FIXED_DEFAULT_DEFINITION());
+
+        state_var_definitions.insert("labelPosition", string_definition_from_attribute!("labelPosition", "upperright"));
+        state_var_definitions.insert("labelHasLatex", boolean_definition_from_attribute!("labelHasLatex", false));
+
+        // Graphical
+        state_var_definitions.insert("showLabel", boolean_definition_from_attribute!("showLabel", true));
+        state_var_definitions.insert("applyStyleToLabel", boolean_definition_from_attribute!("applyStyleToLabel", true));
+        state_var_definitions.insert("layer", integer_definition_from_attribute!("layer", 0));
+        state_var_definitions.insert("label", string_definition_from_attribute!("label", ""));
+
+        // Base
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "function",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "variable",
+            "flipFunction",
+            "labelPosition",
+            "labelHasLatex",
+            "styleNumber",
+
+            "showLabel",
+            "applyStyleToLabel",
+            "layer",
+            "label",
+
+            "hide",
+            "disabled",
+        ],
+
+        component_profiles: vec![
+            (ComponentProfile::Function, "function"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Text]),
+
+        renderer_type: RendererType::Special {
+            component_type: "curve",
+            state_var_aliases: HashMap::new(),
+        },
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
+        ..Default::default()
+    };
+}
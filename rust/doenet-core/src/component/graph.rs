@@ -4,10 +4,133 @@ use lazy_static::lazy_static;
 
 use crate::state_variables::*;
 use crate::base_definitions::*;
+use crate::utils::log;
 
 use super::*;
 
 
+
+/// One of the bounds of the graph: the panned or zoomed bounds unless the axes are fixed,
+/// adjusted to the same scale on both axes if asked.
+/// Setting a bound pans to it like the renderer does, unless the axes are fixed.
+/// With identicalAxisScales, the y bounds still follow the x bounds.
+macro_rules! axis_limit_definition {
+    ( $index:expr ) => {
+        StateVarVariant::Number(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: |_| {
+                let mut instructions: HashMap<InstructionName, DependencyInstruction> =
+                    ["specifiedXmin", "specifiedXmax", "specifiedYmin", "specifiedYmax",
+                        "fixAxes", "identicalAxisScales", "aspectRatio"]
+                    .map(|name| (name, DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic(name)),
+                    }))
+                    .into();
+                instructions.insert("changedAxisLimits", DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Array("changedAxisLimits"),
+                });
+                instructions.insert("changed_limit", DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Single(StateRef::ArrayElement("changedAxisLimits", vec![$index + 1])),
+                });
+                instructions
+            },
+            determine_state_var_from_dependencies: |dependency_values| {
+                axis_limits(&dependency_values).map(|limits| SetValue(limits[$index]))
+            },
+            request_dependencies_to_update_value: |desired_value, sources| {
+                let fixed_axes = sources.get("fixAxes").unwrap().first()
+                    .is_some_and(|(_, fix_axes)| fix_axes == &Some(StateVarValue::Boolean(true)));
+                if fixed_axes {
+                    return HashMap::from([("changed_limit", Err("the axes are fixed".to_string()))])
+                }
+
+                HashMap::from([
+                    ("changed_limit", Ok(vec![DependencyValue {
+                        source: sources.get("changed_limit").unwrap().first().unwrap().0.clone(),
+                        value: StateVarValue::Number(desired_value),
+                    }])),
+                ])
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// The xmin, xmax, ymin and ymax of the graph
+fn axis_limits(dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>) -> Result<[f64; 4], String> {
+    let number = |name| dependency_values.dep_value(name)?.has_exactly_one_element()?.into_number();
+    let boolean = |name| dependency_values.dep_value(name)?.has_exactly_one_element()?.into_bool();
+
+    let specified = [number("specifiedXmin")?, number("specifiedXmax")?, number("specifiedYmin")?, number("specifiedYmax")?];
+    let changed = dependency_values.dep_value("changedAxisLimits")?
+        .into_number_list()?;
+
+    // A bound that was not panned or zoomed to is NaN
+    let [xmin, xmax, mut ymin, mut ymax] = match <[f64; 4]>::try_from(changed) {
+        Ok(changed) if !boolean("fixAxes")? => {
+            let mut limits = specified;
+            for (limit, changed) in limits.iter_mut().zip(changed) {
+                if !changed.is_nan() {
+                    *limit = changed;
+                }
+            }
+            limits
+        },
+        _ => specified,
+    };
+
+    if boolean("identicalAxisScales")? {
+        let aspect_ratio = number("aspectRatio")?;
+        let y_center = (ymin + ymax) / 2.0;
+        let y_half_range = (xmax - xmin) / aspect_ratio / 2.0;
+        ymin = y_center - y_half_range;
+        ymax = y_center + y_half_range;
+    }
+
+    Ok([xmin, xmax, ymin, ymax])
+}
+
+/// The renderer's size object for a size like `425px` or `80%`.
+/// A plain number is in pixels.
+fn component_size_json(size: &str) -> String {
+    let size = size.trim();
+    let (number, is_absolute) = match size.strip_suffix('%') {
+        Some(percent) => (percent, false),
+        None => (size.strip_suffix("px").unwrap_or(size), true),
+    };
+    let (size, is_absolute) = match number.trim().parse::<f64>() {
+        Ok(size) if size > 0.0 => (size, is_absolute),
+        _ => (425.0, true),
+    };
+
+    serde_json::json!({ "size": size, "isAbsolute": is_absolute }).to_string()
+}
+
+fn normalize_grid(words: &[String]) -> String {
+    let words: Vec<String> = words.iter().map(|word| word.trim().to_lowercase()).collect();
+    let spacing: Vec<f64> = words.iter().filter_map(|word| word.parse().ok()).collect();
+
+    match (words.as_slice(), spacing.as_slice()) {
+        ([_, _], [dx, dy]) => format!("{} {}", dx, dy),
+        ([_], [d]) => format!("{} {}", d, d),
+        ([word], _) if word == "true" || word == "medium" => "medium".to_string(),
+        ([word], _) if word == "dense" => "dense".to_string(),
+        _ => "none".to_string(),
+    }
+}
+
+/// The renderer takes the spacing of the grid lines as a list
+fn grid_json(grid: &str) -> String {
+    let spacing: Vec<f64> = grid.split(' ').filter_map(|word| word.parse().ok()).collect();
+    match spacing.as_slice() {
+        [dx, dy] => serde_json::json!([dx, dy]).to_string(),
+        _ => serde_json::json!(grid).to_string(),
+    }
+}
+
 lazy_static! {
 
     pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
@@ -40,45 +163,163 @@ lazy_static! {
         //     ..Default::default()
         // }));
 
-        state_var_definitions.insert("width", StateVarVariant::Number(StateVarDefinition {
-            return_dependency_instructions: |_| HashMap::from([
-                // ("size", DependencyInstruction::StateVar {
-                //     component_name: None,
-                //     state_var: StateVarGroup::Single(StateVarReference::Basic("size")),
-                // })
-            ]),
-            determine_state_var_from_dependencies: |_| Ok(SetValue(10.0)),
-            for_renderer: true,
+        // A size like `425px` or `80%`. The renderer gets it as `renderedWidth`.
+        state_var_definitions.insert("width", StateVarVariant::String(StateVarDefinition {
+            initial_essential_value: "425px".to_string(),
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("attribute", DependencyInstruction::Attribute {
+                        attribute_name: "width",
+                        index: StateIndex::Basic,
+                    }),
+                ])
+            },
+            determine_state_var_from_dependencies: |dependency_values| {
+                let attribute = dependency_values.get("attribute").unwrap();
+                DETERMINE_STRING(attribute.clone()).map(SetValue)
+            },
             ..Default::default()
         }));
 
-        state_var_definitions.insert("aspectRatio", StateVarVariant::Number(StateVarDefinition {
-            return_dependency_instructions: |_| HashMap::from([
-                // ("xscale", DependencyInstruction::StateVar {
-                //     component_name: None,
-                //     state_var: StateVarGroup::Single(StateVarReference::Basic("xscale")),
-                // }),
-                // ("yscale", DependencyInstruction::StateVar {
-                //     component_name: None,
-                //     state_var: StateVarGroup::Single(StateVarReference::Basic("yscale")),
-                // }),
-            ]),
-            determine_state_var_from_dependencies: |_| Ok(SetValue(1.0)),
+        state_var_definitions.insert("renderedWidth", StateVarVariant::String(StateVarDefinition {
             for_renderer: true,
+            reshape_for_renderer: PARSE_JSON_FOR_RENDERER,
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("width", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("width")),
+                    }),
+                ])
+            },
+            determine_state_var_from_dependencies: |dependency_values| {
+                let width = dependency_values.dep_value("width")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                Ok( SetValue( component_size_json(&width) ) )
+            },
             ..Default::default()
         }));
 
-        state_var_definitions.insert("xmin", number_definition_from_attribute!("xmin", -10.0));
-        state_var_definitions.insert("ymin", number_definition_from_attribute!("ymin", -10.0));
-        state_var_definitions.insert("xmax", number_definition_from_attribute!("xmax", 10.0));
-        state_var_definitions.insert("ymax", number_definition_from_attribute!("ymax", 10.0));
-        
+        // The width divided by the height
+        state_var_definitions.insert("aspectRatio", number_definition_from_attribute!("aspectRatio", 1.0));
+
+        // The bounds given by the attributes, before any pan or zoom
+        state_var_definitions.insert("specifiedXmin", number_definition_from_attribute!("xmin", -10.0));
+        state_var_definitions.insert("specifiedXmax", number_definition_from_attribute!("xmax", 10.0));
+        state_var_definitions.insert("specifiedYmin", number_definition_from_attribute!("ymin", -10.0));
+        state_var_definitions.insert("specifiedYmax", number_definition_from_attribute!("ymax", 10.0));
+
+        // The bounds the user panned or zoomed to: xmin, xmax, ymin and ymax,
+        // each NaN before it was changed. This is kept when the essential data is reloaded.
+        state_var_definitions.insert("changedAxisLimits", StateVarVariant::NumberArray(StateVarArrayDefinition {
+            return_array_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                DETERMINE_FROM_ESSENTIAL(dependency_values)
+            },
+
+            request_element_dependencies_to_update_value: |_, desired_value, sources| {
+                REQUEST_ESSENTIAL_TO_UPDATE(desired_value, sources)
+            },
+
+            return_size_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("essential")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok(SetValue(size as usize))
+            },
+
+            initial_essential_element_value: f64::NAN,
+            initial_essential_size: 4,
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("fixAxes", boolean_definition_from_attribute!("fixAxes", false));
+
+        // The y axis is stretched or shrunk so a unit is as long as on the x axis
+        state_var_definitions.insert("identicalAxisScales", boolean_definition_from_attribute!("identicalAxisScales", false));
+
+        state_var_definitions.insert("xmin", axis_limit_definition!(0));
+        state_var_definitions.insert("xmax", axis_limit_definition!(1));
+        state_var_definitions.insert("ymin", axis_limit_definition!(2));
+        state_var_definitions.insert("ymax", axis_limit_definition!(3));
+
+        // The words of the grid attribute
+        state_var_definitions.insert("gridAttribute", StateVarVariant::StringArray(StateVarArrayDefinition {
+            return_element_dependency_instructions: |i, _| {
+                HashMap::from([
+                    ("attribute", DependencyInstruction::Attribute {
+                        attribute_name: "grid",
+                        index: StateIndex::Element(vec![i]),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                let attribute = dependency_values.get("attribute").unwrap();
+                DETERMINE_STRING(attribute.clone()).map(SetValue)
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("attribute", DependencyInstruction::Attribute {
+                        attribute_name: "grid",
+                        index: StateIndex::SizeOf,
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("attribute")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+                Ok( SetValue( size as usize ) )
+            },
+
+            ..Default::default()
+        }));
+
+        // `none`, `medium`, `dense`, or the spacing of the grid lines as `dx dy`
         state_var_definitions.insert("grid", StateVarVariant::String(StateVarDefinition {
-            return_dependency_instructions: |_| HashMap::from([
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("attribute", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("gridAttribute"),
+                    }),
+                ])
+            },
+            determine_state_var_from_dependencies: |dependency_values| {
+                let words: Vec<String> = dependency_values.dep_value("attribute")?
+                    .0.iter()
+                    .map(|word| word.value.clone().try_into())
+                    .collect::<Result<_, _>>()?;
+                Ok( SetValue( normalize_grid(&words) ) )
+            },
+            ..Default::default()
+        }));
 
-            ]),
-            determine_state_var_from_dependencies: |_| Ok(SetValue("none".to_string())),
+        state_var_definitions.insert("renderedGrid", StateVarVariant::String(StateVarDefinition {
             for_renderer: true,
+            reshape_for_renderer: PARSE_JSON_FOR_RENDERER,
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("grid", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("grid")),
+                    }),
+                ])
+            },
+            determine_state_var_from_dependencies: |dependency_values| {
+                let grid = dependency_values.dep_value("grid")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                Ok( SetValue( grid_json(&grid) ) )
+            },
             ..Default::default()
         }));
 
@@ -110,8 +351,6 @@ lazy_static! {
 
         state_var_definitions.insert("showNavigation", boolean_definition_from_attribute!("showNavigation", true));
 
-        state_var_definitions.insert("fixAxes", boolean_definition_from_attribute!("fixAxes", false));
-
 
         state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
 
@@ -145,6 +384,10 @@ lazy_static! {
             "yTickScaleFactor",
             "showNavigation",
             "fixAxes",
+            "identicalAxisScales",
+            "aspectRatio",
+            "width",
+            "grid",
             "xmin",
             "ymin",
             "xmax",
@@ -158,28 +401,44 @@ lazy_static! {
 
         action_names: || vec!["changeAxisLimits"],
 
-        on_action: |action_name, args, _| {
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
 
             match action_name {
                 "changeAxisLimits" => {
+                    let fix_axes = resolve_and_retrieve_state_var(&StateRef::Basic("fixAxes"))
+                        == Some(StateVarValue::Boolean(true));
+                    if fix_axes {
+                        return vec![]
+                    }
+
                     // Note: the key here is whatever the renderers call the new value
-                    let xmin = args.get("xmin").expect("missing bound argument").first().unwrap().clone().into_number().unwrap();
-                    let xmax = args.get("xmax").expect("missing bound argument").first().unwrap().clone().into_number().unwrap();
-                    let ymin = args.get("ymin").expect("missing bound argument").first().unwrap().clone().into_number().unwrap();
-                    let ymax = args.get("ymax").expect("missing bound argument").first().unwrap().clone().into_number().unwrap();
-
-                    vec![
-                        (StateRef::Basic("xmin"), xmin),
-                        (StateRef::Basic("xmax"), xmax),
-                        (StateRef::Basic("ymin"), ymin),
-                        (StateRef::Basic("ymax"), ymax),
-                    ]
+                    let limits: Option<Vec<f64>> = ["xmin", "xmax", "ymin", "ymax"].iter()
+                        .map(|bound| args.get(*bound)
+                            .and_then(|bound| bound.first())
+                            .and_then(|bound| f64::try_from(bound.clone()).ok()))
+                        .collect();
+                    let Some(limits) = limits else {
+                        log!("Ignoring changeAxisLimits with {:?}", args);
+                        return vec![];
+                    };
+
+                    limits.into_iter().enumerate()
+                        .map(|(i, limit)| (StateRef::ArrayElement("changedAxisLimits", vec![i + 1]), StateVarValue::Number(limit)))
+                        .collect()
                 },
 
                 _ => panic!("Unknown action '{}' called on graph", action_name)
             }
         },
 
+        renderer_type: RendererType::Special {
+            component_type: "graph",
+            state_var_aliases: HashMap::from([
+                ("renderedWidth", "width"),
+                ("renderedGrid", "grid"),
+            ]),
+        },
+
         valid_children_profiles: ValidChildTypes::AllComponents,
 
         ..Default::default()
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::base_definitions::*;
+use crate::utils::log;
+
+
+use lazy_static::lazy_static;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("title", string_definition_from_attribute!("title", "Hint"));
+
+        state_var_definitions.insert("titleChildName", StateVarVariant::String(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| Ok(StateVarUpdateInstruction::SetNull),
+            for_renderer: true,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("showHints", boolean_definition_from_document!("showHints"));
+
+        state_var_definitions.insert("open", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+            initial_essential_value: false,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "hint",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "title",
+
+            "hide",
+            "disabled",
+        ],
+
+        should_render_children: true,
+
+        valid_children_profiles: ValidChildTypes::AllComponents,
+
+        action_names: || vec!["revealHint", "closeHint", "recordVisibilityChange"],
+
+        on_action: |action_name, _, _| {
+            match action_name {
+                "revealHint" => vec![(StateRef::Basic("open"), StateVarValue::Boolean(true))],
+                "closeHint" => vec![(StateRef::Basic("open"), StateVarValue::Boolean(false))],
+                "recordVisibilityChange" => vec![],
+
+                _ => {
+                    log!("Unknown action '{}' called on hint", action_name);
+                    vec![]
+                }
+            }
+        },
+
+        ..Default::default()
+    };
+}
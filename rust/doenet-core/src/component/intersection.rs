@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+
+use super::*;
+
+
+
+/// The intersection points of the first two graphical children, as a flat list of coordinates
+fn intersection_coords(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<Vec<f64>, String> {
+    let objects = constraints::objects_from_dependency_values(dependency_values)?;
+
+    Ok(match objects.as_slice() {
+        [a, b, ..] => intersections(a, b).concat(),
+        _ => vec![],
+    })
+}
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        // The number of intersection points changes as the objects move
+        state_var_definitions.insert("numIntersections", StateVarVariant::Integer(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([("objects", constraints::graphical_children_instruction())])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = intersection_coords(&dependency_values)?;
+                Ok( SetValue( coords.len() as i64 / 2 ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("intersections", StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([("objects", constraints::graphical_children_instruction())])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let coords = intersection_coords(&dependency_values)?;
+                Ok( SetValue( coords.get(index).copied().unwrap_or(f64::NAN) ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("numIntersections", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("numIntersections")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let num_intersections = dependency_values.dep_value("numIntersections")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok( SetValue( 2 * num_intersections as usize ) )
+            },
+
+            ..Default::default()
+        }));
+
+        // The latex of each intersection point
+        state_var_definitions.insert("latex", StateVarVariant::StringArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("intersections", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("intersections"),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let coords = dependency_values.dep_value("intersections")?
+                    .into_number_list()?;
+                let x = coords.get(2 * index).unwrap();
+                let y = coords.get(2 * index + 1).unwrap();
+
+                Ok( SetValue( format!("({}, {})", x, y) ) )
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("numIntersections", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("numIntersections")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let num_intersections = dependency_values.dep_value("numIntersections")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok( SetValue( num_intersections as usize ) )
+            },
+
+            ..Default::default()
+        }));
+
+        // For the intersection points
+        state_var_definitions.insert("numDimensions", StateVarVariant::Integer(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( 2 ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
+        // The points follow the objects, so they cannot be dragged
+        state_var_definitions.insert("draggable", StateVarVariant::Boolean(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( false ) )
+            },
+            ..Default::default()
+        }));
+        state_var_definitions.insert("labelPosition", string_definition_from_attribute!("labelPosition", "upperright"));
+        state_var_definitions.insert("showCoordsWhenDragging", boolean_definition_from_attribute!("showCoordsWhenDragging", true));
+        state_var_definitions.insert("labelHasLatex", boolean_definition_from_attribute!("labelHasLatex", false));
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( constraints_to_json(&[]) ) )
+            },
+            ..Default::default()
+        }));
+
+        // Graphical
+        state_var_definitions.insert("showLabel", boolean_definition_from_attribute!("showLabel", true));
+        state_var_definitions.insert("applyStyleToLabel", boolean_definition_from_attribute!("applyStyleToLabel", true));
+        state_var_definitions.insert("layer", integer_definition_from_attribute!("layer", 0));
+        state_var_definitions.insert("label", string_definition_from_attribute!("label", ""));
+
+        // Base
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+fn member_state_var(
+    index: usize,
+    state_var_slice: &StateVarSlice,
+) -> Option<StateVarSlice> {
+
+    // Point `index` owns the coordinates 2*index - 1 and 2*index of the intersections
+    let coord_of_point = |i: &[usize]| match i {
+        [i @ 1..=2] => Some(StateVarSlice::Single(StateRef::ArrayElement("intersections", vec![2 * (index - 1) + i]))),
+        _ => None,
+    };
+
+    match state_var_slice {
+        StateVarSlice::Single(StateRef::ArrayElement("xs", i)) |
+        StateVarSlice::Single(StateRef::ArrayElement("unconstrainedXs", i)) |
+        StateVarSlice::Single(StateRef::ArrayElement("numericalXs", i)) => coord_of_point(i),
+        StateVarSlice::Single(StateRef::SizeOf("xs")) |
+        StateVarSlice::Single(StateRef::SizeOf("unconstrainedXs")) |
+        StateVarSlice::Single(StateRef::SizeOf("numericalXs")) =>
+            Some(StateVarSlice::Single(StateRef::Basic("numDimensions"))),
+
+        // A point's coordinates are only part of an array, so they are reached element by element
+        StateVarSlice::Array("xs") |
+        StateVarSlice::Array("unconstrainedXs") |
+        StateVarSlice::Array("numericalXs") |
+        StateVarSlice::Single(StateRef::Basic("geometry")) => None,
+
+        StateVarSlice::Single(StateRef::Basic("latex")) |
+        StateVarSlice::Single(StateRef::Basic("text")) =>
+            Some(StateVarSlice::Single(StateRef::ArrayElement("latex", vec![index]))),
+
+        _ => Some(state_var_slice.clone()),
+    }
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "intersection",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        replacement_components: Some(ReplacementComponents::Batch(BatchDefinition {
+            member_definition: &super::point::MY_COMPONENT_DEFINITION,
+            size: StateRef::Basic("numIntersections"),
+            member_state_var,
+        })),
+
+        attribute_names: vec![
+            "styleNumber",
+            "labelPosition",
+            "showCoordsWhenDragging",
+            "labelHasLatex",
+
+            "showLabel",
+            "applyStyleToLabel",
+            "layer",
+            "label",
+
+            "hide",
+            "disabled",
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Graphical]),
+
+        ..Default::default()
+    };
+}
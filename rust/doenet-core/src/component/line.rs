@@ -4,11 +4,145 @@ use lazy_static::lazy_static;
 
 use crate::state_variables::*;
 use crate::base_definitions::*;
+use crate::geometry::*;
 
 use super::*;
 
 
 
+/// A point of the line, from the point component in an element of `through`
+/// when there is one, otherwise from the point's own attribute.
+/// Moving the line moves the referenced point.
+macro_rules! line_point_definition {
+    ( $attribute:expr, $through_index:expr, $default:expr ) => {
+        StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+            initial_essential_element_value: $default,
+            initial_essential_size: 2,
+
+            return_element_dependency_instructions: |i, _| {
+                HashMap::from([
+                    ("attribute", DependencyInstruction::Attribute {
+                        attribute_name: $attribute,
+                        index: StateIndex::Element(vec![i]),
+                    }),
+                    ("through", DependencyInstruction::AttributeComponent {
+                        attribute_name: "through",
+                        index: $through_index,
+                        state_var: StateVarSlice::Single(StateRef::ArrayElement("xs", vec![i])),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                let (through, _) = dependency_values.dep_value("through")?;
+                if let Some(through) = through.first() {
+                    return Ok( SetValue( through.value.clone().try_into()? ) )
+                }
+
+                let (attribute, _) = dependency_values.dep_value("attribute")?;
+                if attribute.is_empty() {
+                    return Ok( SetValue( $default ) )
+                }
+                Ok( SetValue( DETERMINE_NUMBER(attribute).unwrap_or(f64::NAN) ) )
+            },
+
+            request_element_dependencies_to_update_value: |_, desired_value, sources| {
+                let through_sources = sources.get("through").unwrap();
+                if let Some((source, _)) = through_sources.first() {
+                    return HashMap::from([
+                        ("through", Ok(vec![DependencyValue {
+                            source: source.clone(),
+                            value: StateVarValue::Number(desired_value),
+                        }])),
+                    ])
+                }
+
+                let attribute_sources = sources.get("attribute").unwrap();
+                HashMap::from([
+                    ("attribute", DETERMINE_NUMBER_DEPENDENCIES(desired_value, attribute_sources)),
+                ])
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("attribute", DependencyInstruction::Attribute {
+                        attribute_name: $attribute,
+                        index: StateIndex::SizeOf,
+                    }),
+                    ("through", DependencyInstruction::AttributeComponent {
+                        attribute_name: "through",
+                        index: $through_index,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("xs")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let (through, _) = dependency_values.dep_value("through")?;
+                let size: i64 = match through.first() {
+                    Some(through) => through.value.clone().try_into()?,
+                    None => dependency_values.dep_value("attribute")?
+                        .has_exactly_one_element()?
+                        .into_integer()?,
+                };
+                Ok( SetValue( size as usize ) )
+            },
+
+            ..Default::default()
+        })
+    }
+}
+
+/// The slope and y-intercept of the line through two points, infinite and NaN when it is vertical
+fn slope_and_intercept(p1: &[f64], p2: &[f64]) -> (f64, f64) {
+    let slope = (p2[1] - p1[1]) / (p2[0] - p1[0]);
+    let y_intercept = if slope.is_finite() { p1[1] - slope * p1[0] } else { f64::NAN };
+    (slope, y_intercept)
+}
+
+fn points_from_dependency_values(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let p1 = dependency_values.dep_value("p1")?.into_number_list()?;
+    let p2 = dependency_values.dep_value("p2")?.into_number_list()?;
+    Ok((p1, p2))
+}
+
+fn points_instructions() -> HashMap<InstructionName, DependencyInstruction> {
+    HashMap::from([
+        ("p1", DependencyInstruction::StateVar {
+            component_ref: None,
+            state_var: StateVarSlice::Array("p1"),
+        }),
+        ("p2", DependencyInstruction::StateVar {
+            component_ref: None,
+            state_var: StateVarSlice::Array("p2"),
+        }),
+    ])
+}
+
+/// An equation like `y = 2x - 1`, or `x = 3` for a vertical line
+fn format_equation(p1: &[f64], p2: &[f64]) -> String {
+    let (slope, y_intercept) = slope_and_intercept(p1, p2);
+    if !slope.is_finite() {
+        return format!("x = {}", p1[0])
+    }
+
+    let x_term = match slope {
+        0.0 => String::new(),
+        1.0 => "x".to_string(),
+        -1.0 => "-x".to_string(),
+        s => format!("{}x", s),
+    };
+    match (x_term.is_empty(), y_intercept) {
+        (true, b) => format!("y = {}", b),
+        (false, 0.0) => format!("y = {}", x_term),
+        (false, b) if b < 0.0 => format!("y = {} - {}", x_term, -b),
+        (false, b) => format!("y = {} + {}", x_term, b),
+    }
+}
+
 lazy_static! {
     pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
 
@@ -16,10 +150,10 @@ lazy_static! {
 
         let mut state_var_definitions = HashMap::new();
 
-        state_var_definitions.insert("p1", number_array_definition_from_attribute!("p1", 0.0, 2));
-        state_var_definitions.insert("p2", number_array_definition_from_attribute!("p2", 1.0, 2));
+        state_var_definitions.insert("p1", line_point_definition!("p1", 1, 0.0));
+        state_var_definitions.insert("p2", line_point_definition!("p2", 2, 1.0));
 
-        // TODO: combine into multi-dim array?
+        // One row for each of the two points
         state_var_definitions.insert("numericalPoints", StateVarVariant::NumberArray(StateVarArrayDefinition {
 
             for_renderer: true,
@@ -54,9 +188,11 @@ lazy_static! {
             },
 
             determine_size_from_dependencies: |_| {
-                Ok( SetValue( 4 ) )
+                Ok( SetValue( 2 ) )
             },
 
+            inner_dimensions: &[2],
+
             ..Default::default()
         }));
 
@@ -93,34 +229,57 @@ lazy_static! {
             ..Default::default()
         }));
 
-        state_var_definitions.insert("selectedStyle", StateVarVariant::String(StateVarDefinition {
-            for_renderer: true,
+        state_var_definitions.insert("equation", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| points_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                let (p1, p2) = points_from_dependency_values(&dependency_values)?;
+                Ok( SetValue( format_equation(&p1, &p2) ) )
+            },
+            ..Default::default()
+        }));
 
-            determine_state_var_from_dependencies: |_| {
+        state_var_definitions.insert("slope", StateVarVariant::Number(StateVarDefinition {
+            return_dependency_instructions: |_| points_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                let (p1, p2) = points_from_dependency_values(&dependency_values)?;
+                Ok( SetValue( slope_and_intercept(&p1, &p2).0 ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("yIntercept", StateVarVariant::Number(StateVarDefinition {
+            return_dependency_instructions: |_| points_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                let (p1, p2) = points_from_dependency_values(&dependency_values)?;
+                Ok( SetValue( slope_and_intercept(&p1, &p2).1 ) )
+            },
+            ..Default::default()
+        }));
 
-                let default_style = r##"{
-                        "lineColor": "#648FFF",
-                        "lineColorWord": "blue",
-                        "lineOpacity": 0.7,
-                        "lineWidth": 4,
-                        "lineWidthWord": "thick",
-                        "lineStyle": "solid",
-                        "lineStyleWord": "",
-                        "markerColor": "#648FFF",
-                        "markerColorWord": "blue",
-                        "markerStyle": "circle",
-                        "markerStyleWord": "point",
-                        "markerSize": 3,
-                        "fillColor": "none",
-                        "fillColorWord": "none",
-                        "fillOpacity": 0.3
-                    }"##;
-
-                Ok( SetValue( default_style.to_string() ) )
+        state_var_definitions.insert("geometry", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("points", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalPoints"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let points = dependency_values.dep_value("points")?
+                    .into_number_list()?;
+
+                Ok( SetValue( Geometry::Line {
+                    p1: points[0..2].to_vec(),
+                    p2: points[2..4].to_vec(),
+                }.to_json() ) )
             },
             ..Default::default()
         }));
 
+        state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
         state_var_definitions.insert("draggable", boolean_definition_from_attribute!("draggable", true));
 
         // For the "points" collection
@@ -145,6 +304,12 @@ lazy_static! {
             initial_essential_value: false,
             ..Default::default()
         }));
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| {
+                Ok( SetValue( constraints_to_json(&[]) ) )
+            },
+            ..Default::default()
+        }));
 
         // Graphical
         state_var_definitions.insert("showLabel", boolean_definition_from_attribute!("showLabel", true));
@@ -166,7 +331,7 @@ fn member_state_var<'a>(
 ) -> Option<StateVarSlice> {
 
     match state_var_slice.name() {
-        "xs" => match index {
+        "xs" | "unconstrainedXs" => match index {
                 1 => Some(state_var_slice.from_slice_new_name("p1")),
                 2 => Some(state_var_slice.from_slice_new_name("p2")),
                 _ => None,
@@ -189,8 +354,10 @@ lazy_static! {
         attribute_names: vec![
             "p1",
             "p2",
+            "through",
 
             "draggable",
+            "styleNumber",
 
             "showLabel",
             "applyStyleToLabel",
@@ -201,9 +368,12 @@ lazy_static! {
         ],
 
         component_profiles: vec![
-            (ComponentProfile::Text, "latex")
+            (ComponentProfile::Text, "latex"),
+            (ComponentProfile::Graphical, "geometry"),
         ],
 
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
         batches: HashMap::from([
             ("points", BatchDefinition {
                 member_definition: &super::point::MY_COMPONENT_DEFINITION,
@@ -219,15 +389,14 @@ lazy_static! {
                 "moveLine" => {
                     let p1 = args.get("point1coords").expect("No p1 argument");
                     let p2 = args.get("point2coords").expect("No p2 argument");
-                    // let _transient = args.get("transient").expect("No transient argument").first().unwrap();
 
                     crate::utils::log!("points: {:?} {:?}", p1, p2);
 
                     vec![
-                        (StateRef::ArrayElement("p1", 2), p1[1].clone()),
-                        (StateRef::ArrayElement("p1", 1), p1[0].clone()),
-                        (StateRef::ArrayElement("p2", 2), p2[1].clone()),
-                        (StateRef::ArrayElement("p2", 1), p2[0].clone()),
+                        (StateRef::ArrayElement("p1", vec![2]), p1[1].clone()),
+                        (StateRef::ArrayElement("p1", vec![1]), p1[0].clone()),
+                        (StateRef::ArrayElement("p2", vec![2]), p2[1].clone()),
+                        (StateRef::ArrayElement("p2", vec![1]), p2[0].clone()),
                     ]
                 },
                 "switchLine" => {
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+
+use super::*;
+use super::polyline::*;
+use crate::geometry::*;
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = vertex_state_var_definitions(2);
+
+        state_var_definitions.insert("length", StateVarVariant::Number(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+
+                Ok( SetValue( path_length(&coords, false) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("geometry", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+
+                Ok( SetValue( Geometry::Polyline { vertices: vertex_list(&coords) }.to_json() ) )
+            },
+            ..Default::default()
+        }));
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "lineSegment",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: VERTEX_ATTRIBUTE_NAMES.to_vec(),
+
+        component_profiles: vec![
+            (ComponentProfile::Graphical, "geometry"),
+        ],
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
+        batches: HashMap::from([
+            ("vertices", vertex_batch()),
+        ]),
+
+        action_names: || vec!["moveVertex", "moveLineSegment"],
+
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
+            on_vertex_action(action_name, args, resolve_and_retrieve_state_var, "moveLineSegment", "lineSegment")
+        },
+
+        ..Default::default()
+    };
+}
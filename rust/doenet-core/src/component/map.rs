@@ -25,10 +25,13 @@ fn collection_members(
     component_nodes: &HashMap<ComponentName, ComponentNode>,
 ) -> Vec<CollectionMembersOrCollection> {
     let templates = get_children_of_type(component_nodes, node, "template", false).next().unwrap();
-    let sources = get_children_of_type(component_nodes, node, "sources", false).next().unwrap();
+    let sources = get_children_of_type(component_nodes, node, "sources", false)
+        .map(|sources| ComponentRelative::same_instance(sources.name.clone()))
+        .collect();
     vec![CollectionMembersOrCollection::Members(CollectionMembers::InstanceBySources {
         template: ComponentRelative::same_instance(templates.name.clone()),
-        sources: ComponentRelative::same_instance(sources.name.clone()),
+        sources,
+        behavior: SourcesBehavior::from_static_attributes(&node.static_attributes),
     })]
 }
 
@@ -43,6 +46,11 @@ lazy_static! {
             "disabled",
         ],
 
+        static_attribute_names: vec![
+            "assignNames",
+            "behavior",
+        ],
+
         replacement_components: Some(ReplacementComponents::Collection(CollectionDefinition {
             member_definition,
             collection_members,
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::math_expression::MathExpression;
+
+use super::*;
+
+use crate::ComponentProfile;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("value", StateVarVariant::MathArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                math_list_dependency_instructions()
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let items = list_items(&dependency_values)?;
+                Ok(SetValue(items.get(index).map_or(MathExpression::from(f64::NAN), list_item_into_math_expression)))
+            },
+
+            request_element_dependencies_to_update_value: |index, desired_value, sources| {
+                HashMap::from([
+                    ("children", list_item_dependencies(index, StateVarValue::MathExpr(desired_value), &sources)),
+                ])
+            },
+
+            return_size_dependency_instructions: |_| {
+                math_list_dependency_instructions()
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                Ok(SetValue(list_size(&dependency_values)?))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("numericalValues", StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([(
+                    "corresponding_value", DependencyInstruction::CorrespondingElements {
+                        component_ref: None,
+                        array_state_var_name: "value",
+                    }
+                )])
+            },
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                let my_value = dependency_values.dep_value("corresponding_value")?
+                    .has_exactly_one_element()?
+                    .into_math_expression()?;
+
+                Ok(SetValue(my_value.tree.eval_number().unwrap_or(f64::NAN)))
+            },
+
+            request_element_dependencies_to_update_value: |_, desired_value, sources| {
+                HashMap::from([
+                    ("corresponding_value", Ok(vec![DependencyValue {
+                        source: sources.get("corresponding_value").unwrap().first().unwrap().0.clone(),
+                        value: MathExpression::from(desired_value).into(),
+                    }])),
+                ])
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("sv_value_size", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("value")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("sv_value_size")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+
+                Ok(SetValue(size as usize))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("text", StateVarVariant::StringArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([(
+                    "corresponding_value", DependencyInstruction::CorrespondingElements {
+                        component_ref: None,
+                        array_state_var_name: "numericalValues",
+                    }
+                )])
+            },
+
+            determine_element_from_dependencies: |_, dependency_values| {
+                let my_value: f64 = dependency_values.dep_value("corresponding_value")?
+                    .has_exactly_one_element()?
+                    .into_number()?;
+
+                Ok(SetValue(my_value.to_string()))
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("sv_value_size", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("value")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let size = dependency_values.dep_value("sv_value_size")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+
+                Ok(SetValue(size as usize))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("maxNumber", number_definition_from_attribute!("maxNumber", f64::INFINITY));
+
+        state_var_definitions.insert("merge", boolean_definition_from_attribute!("merge", false));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+/// With `merge`, the comma-separated entries in the string children are items as well
+fn math_list_dependency_instructions() -> HashMap<InstructionName, DependencyInstruction> {
+    let mut instructions = list_dependency_instructions(vec![ComponentProfile::Number]);
+    instructions.insert("merge", DependencyInstruction::StateVar {
+        component_ref: None,
+        state_var: StateVarSlice::Single(StateRef::Basic("merge")),
+    });
+    instructions
+}
+
+fn member_state_var(
+    index: usize,
+    state_var_slice: &StateVarSlice,
+) -> Option<StateVarSlice> {
+
+    let slice = match state_var_slice {
+        StateVarSlice::Single(StateRef::Basic("value")) => {
+            StateVarSlice::Single(StateRef::ArrayElement("numericalValues", vec![index]))
+        }
+        StateVarSlice::Single(StateRef::Basic("text")) => {
+            StateVarSlice::Single(StateRef::ArrayElement("text", vec![index]))
+        },
+        _ => state_var_slice.clone(),
+    };
+
+    Some(slice)
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "mathList",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        replacement_components: Some(ReplacementComponents::Batch(BatchDefinition {
+            member_definition: &super::number::MY_COMPONENT_DEFINITION,
+            size: StateRef::SizeOf("value"),
+            member_state_var,
+        })),
+
+        attribute_names: vec![
+            "maxNumber",
+            "merge",
+            "hide",
+            "disabled",
+        ],
+
+        static_attribute_names: vec![
+            "assignNames",
+        ],
+
+        primary_input_state_var: Some("value"),
+
+        component_profiles: vec![
+            (ComponentProfile::Number, "numericalValues"),
+            (ComponentProfile::Text, "text"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Number]),
+
+        ..Default::default()
+    };
+}
@@ -4,12 +4,31 @@ use lazy_static::lazy_static;
 
 use crate::state_variables::*;
 use crate::base_definitions::*;
-use crate::utils::log_debug;
+use crate::geometry::*;
+use crate::utils::log;
 
 use super::*;
 
 
 
+fn coords_instructions() -> HashMap<InstructionName, DependencyInstruction> {
+    HashMap::from([
+        ("xs", DependencyInstruction::StateVar {
+            component_ref: None,
+            state_var: StateVarSlice::Array("xs"),
+        }),
+    ])
+}
+
+/// Every coordinate, as in `(1, 2, 3)`
+fn format_coords(dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>) -> Result<String, String> {
+    let xs = dependency_values.dep_value("xs")?
+        .into_number_list()?;
+    let coords: Vec<String> = xs.iter().map(f64::to_string).collect();
+
+    Ok( format!("({})", coords.join(", ")) )
+}
+
 lazy_static! {
     pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
 
@@ -17,7 +36,90 @@ lazy_static! {
 
         let mut state_var_definitions = HashMap::new();
 
-        state_var_definitions.insert("xs", number_array_definition_from_attribute!("xs", 0.0, 2));
+        state_var_definitions.insert("unconstrainedXs", number_array_definition_from_attribute!("xs", 0.0, 2));
+
+        // The constraint children, in the order they apply
+        state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([("constraint_children", constraints::constraint_children_instruction())])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let constraints = constraints::constraints_from_dependency_values(&dependency_values)?;
+
+                Ok( SetValue( constraints_to_json(&constraints) ) )
+            },
+            ..Default::default()
+        }));
+
+        // The unconstrained coordinates moved by the constraints.
+        // This is the only place constraints apply, to actions and copied values alike:
+        // setting an element sets the unconstrained coordinate.
+        state_var_definitions.insert("xs", StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("unconstrained", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("unconstrainedXs"),
+                    }),
+                    ("constraints", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("constraints")),
+                    }),
+                ])
+            },
+
+            return_element_dependency_instructions: |i, _| {
+                HashMap::from([
+                    ("coordinate", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::ArrayElement("unconstrainedXs", vec![i])),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let unconstrained = dependency_values.dep_value("unconstrained")?
+                    .into_number_list()?;
+                let constraints = dependency_values.dep_value("constraints")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+
+                let xs = apply_constraints(&constraints_from_json(&constraints)?, &unconstrained);
+
+                xs.get(index).copied()
+                    .map(SetValue)
+                    .ok_or(format!("coordinate {} outside of the {} dimensions of the point", index + 1, xs.len()))
+            },
+
+            request_element_dependencies_to_update_value: |_, desired_value, sources| {
+                HashMap::from([
+                    ("coordinate", Ok(vec![DependencyValue {
+                        source: sources.get("coordinate").unwrap().first().unwrap().0.clone(),
+                        value: desired_value.into(),
+                    }])),
+                ])
+            },
+
+            return_size_dependency_instructions: |_| {
+                HashMap::from([
+                    ("dimensions", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::SizeOf("unconstrainedXs")),
+                    }),
+                ])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                let dim = dependency_values.dep_value("dimensions")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+                Ok( SetValue( dim as usize ) )
+            },
+
+            ..Default::default()
+        }));
 
         state_var_definitions.insert("numericalXs", StateVarVariant::NumberArray(StateVarArrayDefinition {
 
@@ -59,60 +161,44 @@ lazy_static! {
             ..Default::default()
         }));
 
-        state_var_definitions.insert("latex", StateVarVariant::String(StateVarDefinition {
-            for_renderer: true,
-
+        state_var_definitions.insert("geometry", StateVarVariant::String(StateVarDefinition {
             return_dependency_instructions: |_| {
                 HashMap::from([
-                    ("xs", DependencyInstruction::StateVar{
+                    ("xs", DependencyInstruction::StateVar {
                         component_ref: None,
-                        state_var: StateVarSlice::Array("xs"),
+                        state_var: StateVarSlice::Array("numericalXs"),
                     }),
                 ])
             },
 
             determine_state_var_from_dependencies: |dependency_values| {
-
                 let xs = dependency_values.dep_value("xs")?
                     .into_number_list()?;
-                // log_debug!("xs: {:?}", xs);
-                let x = xs.get(0).unwrap();
-                let y = xs.get(1).unwrap();
-                let set_value = format!("({}, {})", x, y);
 
-                Ok( SetValue( set_value ) )
+                Ok( SetValue( Geometry::Point { xs }.to_json() ) )
             },
             ..Default::default()
         }));
 
-        state_var_definitions.insert("selectedStyle", StateVarVariant::String(StateVarDefinition {
+        state_var_definitions.insert("latex", StateVarVariant::String(StateVarDefinition {
             for_renderer: true,
+            return_dependency_instructions: |_| coords_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                format_coords(&dependency_values).map(SetValue)
+            },
+            ..Default::default()
+        }));
 
-            determine_state_var_from_dependencies: |_| {
-
-                let default_style = r##"{
-                        "lineColor": "#648FFF",
-                        "lineColorWord": "blue",
-                        "lineOpacity": 0.7,
-                        "lineWidth": 4,
-                        "lineWidthWord": "thick",
-                        "lineStyle": "solid",
-                        "lineStyleWord": "",
-                        "markerColor": "#648FFF",
-                        "markerColorWord": "blue",
-                        "markerStyle": "circle",
-                        "markerStyleWord": "point",
-                        "markerSize": 3,
-                        "fillColor": "none",
-                        "fillColorWord": "none",
-                        "fillOpacity": 0.3
-                    }"##;
-
-                Ok( SetValue( default_style.to_string() ) )
+        state_var_definitions.insert("text", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| coords_instructions(),
+            determine_state_var_from_dependencies: |dependency_values| {
+                format_coords(&dependency_values).map(SetValue)
             },
             ..Default::default()
         }));
 
+        state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
 
         state_var_definitions.insert("draggable", boolean_definition_from_attribute!("draggable", true));
 
@@ -151,6 +237,7 @@ lazy_static! {
             "showCoordsWhenDragging",
             "labelHasLatex",
             "xs",
+            "styleNumber",
 
             "showLabel",
             "applyStyleToLabel",
@@ -162,30 +249,58 @@ lazy_static! {
         ],
 
         array_aliases: HashMap::from([
-            ("x", StateRef::ArrayElement("xs", 1)),
-            ("y", StateRef::ArrayElement("xs", 2)),
-            ("z", StateRef::ArrayElement("xs", 3)),
-            ("w", StateRef::ArrayElement("xs", 4)),
+            ("x", StateRef::ArrayElement("xs", vec![1])),
+            ("y", StateRef::ArrayElement("xs", vec![2])),
+            ("z", StateRef::ArrayElement("xs", vec![3])),
+            ("w", StateRef::ArrayElement("xs", vec![4])),
+        ]),
+
+        attribute_aliases: HashMap::from([
+            ("coords", "xs"),
         ]),
 
         component_profiles: vec![
-            (ComponentProfile::Text, "latex")
+            (ComponentProfile::Text, "text"),
+            (ComponentProfile::Graphical, "geometry"),
         ],
 
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Constraint]),
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
         action_names: || vec!["movePoint", "switchPoint", "pointClicked"],
 
-        on_action: |action_name, args, _| {
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
             match action_name {
                 "movePoint" => {
-                    let x = args.get("x").expect("No x argument").first().unwrap();
-                    let y = args.get("y").expect("No y argument").first().unwrap();
-                    // let z = args.get("z").expect("No z argument");
-
-                    // order means that x will processed second
-                    vec![
-                        (StateRef::ArrayElement("xs", 2), y.clone()),
-                        (StateRef::ArrayElement("xs", 1), x.clone()),
-                    ]
+                    let Some(dimensions) = resolve_and_retrieve_state_var(&StateRef::SizeOf("xs"))
+                        .and_then(|size| usize::try_from(size).ok()) else {
+                        log!("Ignoring movePoint on a point whose dimensions could not be resolved");
+                        return vec![];
+                    };
+
+                    // Every coordinate can be given as a list, which has to match the point's dimensions.
+                    // Named coordinates past the point's dimensions are ignored.
+                    let requested: Option<Vec<f64>> = match args.get("coords").or(args.get("xs")) {
+                        Some(coords) => coords.iter()
+                            .map(|coord| f64::try_from(coord.clone()).ok())
+                            .collect::<Option<Vec<f64>>>()
+                            .filter(|coords| coords.len() == dimensions),
+                        None => ["x", "y", "z", "w"].iter()
+                            .take(dimensions)
+                            .map_while(|&name| args.get(name).and_then(|arg| arg.first()))
+                            .map(|coord| f64::try_from(coord.clone()).ok())
+                            .collect(),
+                    };
+                    let Some(requested) = requested else {
+                        log!("Ignoring movePoint with {:?} for a point of dimension {}", args, dimensions);
+                        return vec![];
+                    };
+
+                    // order means that x will processed last
+                    requested.into_iter().enumerate().rev()
+                        .map(|(i, coord)| (StateRef::ArrayElement("xs", vec![i + 1]), StateVarValue::Number(coord)))
+                        .collect()
                 },
                 "switchPoint" => {
 
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+
+use super::*;
+use super::polyline::*;
+use crate::geometry::*;
+
+
+
+/// The signed area from the shoelace formula, positive when the vertices go counterclockwise
+fn signed_area(vertices: &[&[f64]]) -> f64 {
+    let n = vertices.len();
+    (0..n).map(|i| {
+        let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+        a[0] * b[1] - b[0] * a[1]
+    }).sum::<f64>() / 2.0
+}
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = vertex_state_var_definitions(0);
+
+        state_var_definitions.insert("perimeter", StateVarVariant::Number(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+
+                Ok( SetValue( path_length(&coords, true) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("area", StateVarVariant::Number(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+                let vertices: Vec<&[f64]> = coords.chunks_exact(2).collect();
+
+                Ok( SetValue( signed_area(&vertices).abs() ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("centroid", StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+            return_array_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+                let vertices: Vec<&[f64]> = coords.chunks_exact(2).collect();
+                let n = vertices.len();
+
+                if n == 0 {
+                    return Ok( SetValue( f64::NAN ) )
+                }
+
+                let area = signed_area(&vertices);
+
+                // A polygon with no area has the average of its vertices as its centroid
+                let coord = if area == 0.0 {
+                    vertices.iter().map(|v| v[index]).sum::<f64>() / n as f64
+                } else {
+                    (0..n).map(|i| {
+                        let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+                        (a[index] + b[index]) * (a[0] * b[1] - b[0] * a[1])
+                    }).sum::<f64>() / (6.0 * area)
+                };
+
+                Ok( SetValue( coord ) )
+            },
+
+            determine_size_from_dependencies: |_| {
+                Ok( SetValue( 2 ) )
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("geometry", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+
+                Ok( SetValue( Geometry::Polygon { vertices: vertex_list(&coords) }.to_json() ) )
+            },
+            ..Default::default()
+        }));
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "polygon",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: VERTEX_ATTRIBUTE_NAMES.to_vec(),
+
+        component_profiles: vec![
+            (ComponentProfile::Graphical, "geometry"),
+        ],
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
+        batches: HashMap::from([
+            ("vertices", vertex_batch()),
+        ]),
+
+        action_names: || vec!["moveVertex", "movePolygon"],
+
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
+            on_vertex_action(action_name, args, resolve_and_retrieve_state_var, "movePolygon", "polygon")
+        },
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::geometry::*;
+use crate::utils::log;
+
+use super::*;
+
+
+
+/// State vars shared by <lineSegment>, <polyline> and <polygon>.
+/// The `vertices` attribute is a flat list of coordinates: "x1 y1 x2 y2 ...".
+pub(super) fn vertex_state_var_definitions(default_num_vertices: usize) -> HashMap<StateVarName, StateVarVariant> {
+
+    use StateVarUpdateInstruction::*;
+
+    let mut state_var_definitions = HashMap::new();
+
+    state_var_definitions.insert("vertices", number_array_definition_from_attribute!("vertices", 0.0, 2 * default_num_vertices));
+
+    state_var_definitions.insert("numVertices", StateVarVariant::Integer(StateVarDefinition {
+        for_renderer: true,
+
+        return_dependency_instructions: |_| {
+            HashMap::from([
+                ("coords", DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Single(StateRef::SizeOf("vertices")),
+                }),
+            ])
+        },
+
+        determine_state_var_from_dependencies: |dependency_values| {
+            let num_coords = dependency_values.dep_value("coords")?
+                .has_exactly_one_element()?
+                .into_integer()?;
+
+            Ok( SetValue( num_coords / 2 ) )
+        },
+        ..Default::default()
+    }));
+
+    // The dimension of each vertex, which is what a vertex point reports as the size of its xs
+    state_var_definitions.insert("numDimensions", StateVarVariant::Integer(StateVarDefinition {
+        determine_state_var_from_dependencies: |_| {
+            Ok( SetValue( 2 ) )
+        },
+        ..Default::default()
+    }));
+
+    // One row for each vertex
+    state_var_definitions.insert("numericalVertices", StateVarVariant::NumberArray(StateVarArrayDefinition {
+
+        for_renderer: true,
+
+        return_array_dependency_instructions: |_| {
+            HashMap::from([
+                ("vertices", DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Array("vertices"),
+                }),
+            ])
+        },
+
+        determine_element_from_dependencies: |index, dependency_values| {
+            let vertices = dependency_values.dep_value("vertices")?
+                .into_number_list()?;
+            let my_coord = *vertices.get(index).unwrap();
+
+            Ok( SetValue( my_coord ) )
+        },
+
+        return_size_dependency_instructions: |_| {
+            HashMap::from([
+                ("coords", DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Single(StateRef::SizeOf("vertices")),
+                }),
+            ])
+        },
+
+        determine_size_from_dependencies: |dependency_values| {
+            let num_coords = dependency_values.dep_value("coords")?
+                .has_exactly_one_element()?
+                .into_integer()?;
+            Ok( SetValue( num_coords as usize / 2 ) )
+        },
+
+        // The coordinates of each vertex
+        inner_dimensions: &[2],
+
+        ..Default::default()
+    }));
+
+    state_var_definitions.extend(style_definitions::style_state_var_definitions());
+
+    state_var_definitions.insert("draggable", boolean_definition_from_attribute!("draggable", true));
+    state_var_definitions.insert("verticesDraggable", boolean_definition_from_attribute!("verticesDraggable", true));
+
+    // For the "vertices" batch
+    state_var_definitions.insert("labelPosition", StateVarVariant::String(StateVarDefinition {
+        return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+        determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+        request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+        initial_essential_value: "upperright".to_string(),
+        ..Default::default()
+    }));
+    state_var_definitions.insert("showCoordsWhenDragging", StateVarVariant::Boolean(StateVarDefinition {
+        return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+        determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+        request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+        initial_essential_value: true,
+        ..Default::default()
+    }));
+    state_var_definitions.insert("labelHasLatex", StateVarVariant::Boolean(StateVarDefinition {
+        return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+        determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+        request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+        initial_essential_value: false,
+        ..Default::default()
+    }));
+    state_var_definitions.insert("constraints", StateVarVariant::String(StateVarDefinition {
+        determine_state_var_from_dependencies: |_| {
+            Ok( SetValue( constraints_to_json(&[]) ) )
+        },
+        ..Default::default()
+    }));
+
+    // Graphical
+    state_var_definitions.insert("showLabel", boolean_definition_from_attribute!("showLabel", true));
+    state_var_definitions.insert("applyStyleToLabel", boolean_definition_from_attribute!("applyStyleToLabel", true));
+    state_var_definitions.insert("layer", integer_definition_from_attribute!("layer", 0));
+    state_var_definitions.insert("label", string_definition_from_attribute!("label", ""));
+
+    // Base
+    state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+    state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+    state_var_definitions
+}
+
+/// The vertices as points, for the `Graphical` profile
+pub(super) fn vertex_list(coords: &[f64]) -> Vec<Vec<f64>> {
+    coords.chunks_exact(2).map(|vertex| vertex.to_vec()).collect()
+}
+
+pub(super) const VERTEX_ATTRIBUTE_NAMES: [AttributeName; 10] = [
+    "vertices",
+
+    "draggable",
+    "verticesDraggable",
+    "styleNumber",
+
+    "showLabel",
+    "applyStyleToLabel",
+    "layer",
+    "label",
+
+    "hide",
+    "disabled",
+];
+
+/// The length of the path through the vertices, returning to the first one when closed
+pub(super) fn path_length(coords: &[f64], closed: bool) -> f64 {
+    let vertices: Vec<&[f64]> = coords.chunks_exact(2).collect();
+
+    let mut edges: Vec<(&[f64], &[f64])> = vertices.windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect();
+    if closed && vertices.len() > 2 {
+        edges.push((vertices[vertices.len() - 1], vertices[0]));
+    }
+
+    edges.iter()
+        .map(|(a, b)| ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt())
+        .sum()
+}
+
+fn vertex_member_state_var(
+    index: usize,
+    state_var_slice: &StateVarSlice,
+) -> Option<StateVarSlice> {
+
+    // Vertex `index` owns the coordinates 2*index - 1 and 2*index of the vertices,
+    // and row `index` of the numerical vertices
+    let coord_of_vertex = |i: &[usize]| match i {
+        [i @ 1..=2] => Some(StateVarSlice::Single(StateRef::ArrayElement("vertices", vec![2 * (index - 1) + i]))),
+        _ => None,
+    };
+    let numerical_coord_of_vertex = |i: &[usize]| match i {
+        [i @ 1..=2] => Some(StateVarSlice::Single(StateRef::ArrayElement("numericalVertices", vec![index, *i]))),
+        _ => None,
+    };
+
+    match state_var_slice {
+        StateVarSlice::Single(StateRef::ArrayElement("xs", i)) |
+        StateVarSlice::Single(StateRef::ArrayElement("unconstrainedXs", i)) => coord_of_vertex(i),
+        StateVarSlice::Single(StateRef::ArrayElement("numericalXs", i)) => numerical_coord_of_vertex(i),
+        StateVarSlice::Single(StateRef::SizeOf("xs")) |
+        StateVarSlice::Single(StateRef::SizeOf("unconstrainedXs")) |
+        StateVarSlice::Single(StateRef::SizeOf("numericalXs")) =>
+            Some(StateVarSlice::Single(StateRef::Basic("numDimensions"))),
+
+        // A vertex's coordinates are only part of an array, so they are reached element by element
+        StateVarSlice::Array("xs") |
+        StateVarSlice::Array("unconstrainedXs") |
+        StateVarSlice::Array("numericalXs") |
+        StateVarSlice::Single(StateRef::Basic("latex")) |
+        StateVarSlice::Single(StateRef::Basic("text")) => None,
+
+        StateVarSlice::Single(StateRef::Basic("draggable")) =>
+            Some(StateVarSlice::Single(StateRef::Basic("verticesDraggable"))),
+
+        _ => Some(state_var_slice.clone()),
+    }
+}
+
+pub(super) fn vertex_batch() -> BatchDefinition {
+    BatchDefinition {
+        member_definition: &super::point::MY_COMPONENT_DEFINITION,
+        size: StateRef::Basic("numVertices"),
+        member_state_var: vertex_member_state_var,
+    }
+}
+
+/// `moveVertex` moves one vertex (1-indexed) to `x` and `y`.
+/// `move_action` moves the whole shape, which is `movePolygon` or `movePolyline` with the
+/// flat list `pointCoords`, where the vertices that do not move are null,
+/// or `moveLineSegment` with the endpoints `point1coords` and `point2coords`.
+/// Actions with a vertex out of range or coordinates that are not numbers are ignored.
+pub(super) fn on_vertex_action(
+    action_name: &str,
+    args: HashMap<String, Vec<StateVarValue>>,
+    resolve_and_retrieve_state_var: &dyn Fn(&StateRef) -> Option<StateVarValue>,
+    move_action: &str,
+    component_type: &str,
+) -> Vec<(StateRef, StateVarValue)> {
+
+    let num_vertices: i64 = resolve_and_retrieve_state_var(&StateRef::Basic("numVertices"))
+        .and_then(|num_vertices| num_vertices.try_into().ok())
+        .unwrap_or(0);
+
+    // The new coordinates starting at 1-indexed coordinate `first_coord`, skipping nulls
+    let coord_updates = |first_coord: usize, coords: &[StateVarValue]| -> Option<Vec<(StateRef, StateVarValue)>> {
+        if first_coord + coords.len() - 1 > 2 * num_vertices as usize {
+            return None
+        }
+        coords.iter().enumerate()
+            .filter(|(_, coord)| **coord != StateVarValue::Null)
+            .map(|(i, coord)| {
+                let coord: f64 = coord.clone().try_into().ok()?;
+                Some((StateRef::ArrayElement("vertices", vec![first_coord + i]), StateVarValue::Number(coord)))
+            })
+            .collect()
+    };
+
+    let updates = match action_name {
+        "moveVertex" => {
+            let vertex = args.get("vertexIndex")
+                .and_then(|vertex| vertex.first())
+                .and_then(|vertex| i64::try_from(vertex.clone()).ok())
+                .filter(|vertex| (1..=num_vertices).contains(vertex));
+
+            match (vertex, args.get("x").map(Vec::as_slice), args.get("y").map(Vec::as_slice)) {
+                (Some(vertex), Some([x]), Some([y])) =>
+                    coord_updates(2 * vertex as usize - 1, &[x.clone(), y.clone()]),
+                _ => None,
+            }
+        },
+        "moveLineSegment" if move_action == action_name => {
+            ["point1coords", "point2coords"].iter().enumerate()
+                .filter_map(|(i, arg)| args.get(*arg).map(|coords| (i, coords)))
+                .map(|(i, coords)| match coords.len() {
+                    2 => coord_updates(2 * i + 1, coords),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|updates| updates.concat())
+        },
+        _ if move_action == action_name => {
+            args.get("pointCoords").and_then(|coords| coord_updates(1, coords))
+        },
+
+        _ => {
+            log!("Unknown action '{}' called on {}", action_name, component_type);
+            return vec![]
+        }
+    };
+
+    updates.unwrap_or_else(|| {
+        log!("Ignoring {} on {} with {} vertices and arguments {:?}", action_name, component_type, num_vertices, args);
+        vec![]
+    })
+}
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = vertex_state_var_definitions(0);
+
+        state_var_definitions.insert("perimeter", StateVarVariant::Number(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+
+                Ok( SetValue( path_length(&coords, false) ) )
+            },
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("geometry", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("vertices", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Array("numericalVertices"),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let coords = dependency_values.dep_value("vertices")?
+                    .into_number_list()?;
+
+                Ok( SetValue( Geometry::Polyline { vertices: vertex_list(&coords) }.to_json() ) )
+            },
+            ..Default::default()
+        }));
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "polyline",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: VERTEX_ATTRIBUTE_NAMES.to_vec(),
+
+        component_profiles: vec![
+            (ComponentProfile::Graphical, "geometry"),
+        ],
+
+        renderer_objects: vec![style_definitions::selected_style_renderer_object()],
+
+        batches: HashMap::from([
+            ("vertices", vertex_batch()),
+        ]),
+
+        action_names: || vec!["moveVertex", "movePolyline"],
+
+        on_action: |action_name, args, resolve_and_retrieve_state_var| {
+            on_vertex_action(action_name, args, resolve_and_retrieve_state_var, "movePolyline", "polyline")
+        },
+
+        ..Default::default()
+    };
+}
@@ -96,6 +96,10 @@ lazy_static! {
             "disabled",
         ],
 
+        static_attribute_names: vec![
+            "newNamespace",
+        ],
+
         should_render_children: true,
         
         action_names: || vec!["recordVisibilityChange"],
@@ -130,10 +130,10 @@ fn member_state_var<'a>(
 
     let slice = match state_var_slice {
         StateVarSlice::Single(StateRef::Basic("value")) => {
-            StateVarSlice::Single(StateRef::ArrayElement("value", index))
+            StateVarSlice::Single(StateRef::ArrayElement("value", vec![index]))
         }
         StateVarSlice::Single(StateRef::Basic("text")) => {
-            StateVarSlice::Single(StateRef::ArrayElement("text", index))
+            StateVarSlice::Single(StateRef::ArrayElement("text", vec![index]))
         },
         _ => state_var_slice.clone(),
     };
@@ -160,6 +160,10 @@ lazy_static! {
             "from",
         ],
 
+        static_attribute_names: vec![
+            "assignNames",
+        ],
+
         component_profiles: vec![
             (ComponentProfile::Number, "value"),
             (ComponentProfile::Text, "value"),
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::base_definitions::*;
+use crate::utils::log;
+
+
+use lazy_static::lazy_static;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("sectionName", StateVarVariant::String(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| Ok(SetValue("Solution".to_string())),
+            for_renderer: true,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("message", StateVarVariant::String(StateVarDefinition {
+            for_renderer: true,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("canBeClosed", StateVarVariant::Boolean(StateVarDefinition {
+            determine_state_var_from_dependencies: |_| Ok(SetValue(true)),
+            for_renderer: true,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("canReveal", StateVarVariant::Boolean(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("can_reveal_solutions", DependencyInstruction::Document {
+                        state_var: "canRevealSolutions",
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let can_reveal = dependency_values.dep_value("can_reveal_solutions")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                Ok(SetValue(can_reveal))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("open", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+            return_dependency_instructions: USE_ESSENTIAL_DEPENDENCY_INSTRUCTION,
+            determine_state_var_from_dependencies: DETERMINE_FROM_ESSENTIAL,
+            request_dependencies_to_update_value: REQUEST_ESSENTIAL_TO_UPDATE,
+            initial_essential_value: false,
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("hidden", StateVarVariant::Boolean(StateVarDefinition {
+            for_renderer: true,
+
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("parent_hidden", DependencyInstruction::Parent {
+                        state_var: "hidden",
+                    }),
+                    ("my_hide", DependencyInstruction::Attribute {
+                        attribute_name: "hide",
+                        index: crate::state_variables::StateIndex::Basic,
+                    }),
+                    ("solution_display_mode", DependencyInstruction::Document {
+                        state_var: "solutionDisplayMode",
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let parent_hidden = dependency_values.dep_value("parent_hidden")?
+                    .has_exactly_one_element()?
+                    .into_bool()?;
+
+                let (attribute, _) = dependency_values.dep_value("my_hide")?;
+                let my_hide = (!attribute.is_empty())
+                    .then(|| DETERMINE_BOOLEAN(attribute).ok())
+                    .flatten()
+                    .unwrap_or(false);
+
+                let solution_display_mode = dependency_values.dep_value("solution_display_mode")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+
+                Ok(SetValue(parent_hidden || my_hide || solution_display_mode == "none"))
+            },
+
+            ..Default::default()
+        }));
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "solution",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: vec![
+            "hide",
+            "disabled",
+        ],
+
+        should_render_children: true,
+
+        valid_children_profiles: ValidChildTypes::AllComponents,
+
+        action_names: || vec!["revealSolution", "closeSolution", "recordVisibilityChange"],
+
+        on_action: |action_name, _, resolve_and_retrieve_state_var| {
+            match action_name {
+                "revealSolution" => {
+                    let can_reveal: bool = resolve_and_retrieve_state_var(&StateRef::Basic("canReveal"))
+                        .unwrap().try_into().unwrap();
+
+                    if can_reveal {
+                        vec![(StateRef::Basic("open"), StateVarValue::Boolean(true))]
+                    } else {
+                        vec![]
+                    }
+                },
+                "closeSolution" => vec![(StateRef::Basic("open"), StateVarValue::Boolean(false))],
+                "recordVisibilityChange" => vec![],
+
+                _ => {
+                    log!("Unknown action '{}' called on solution", action_name);
+                    vec![]
+                }
+            }
+        },
+
+        ..Default::default()
+    };
+}
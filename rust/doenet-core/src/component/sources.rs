@@ -48,6 +48,7 @@ lazy_static! {
 
         static_attribute_names: vec![
             "alias",
+            "indexAlias",
             "componentType",
         ],
 
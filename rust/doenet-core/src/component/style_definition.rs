@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use serde_json::{Map, Value};
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::style::*;
+
+use super::*;
+
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("styleNumber", integer_definition_from_attribute!("styleNumber", 1));
+
+        // An empty string is a property that was not given
+        state_var_definitions.insert("lineColor", string_definition_from_attribute!("lineColor", ""));
+        state_var_definitions.insert("lineColorWord", string_definition_from_attribute!("lineColorWord", ""));
+        state_var_definitions.insert("lineOpacity", string_definition_from_attribute!("lineOpacity", ""));
+        state_var_definitions.insert("lineWidth", string_definition_from_attribute!("lineWidth", ""));
+        state_var_definitions.insert("lineWidthWord", string_definition_from_attribute!("lineWidthWord", ""));
+        state_var_definitions.insert("lineStyle", string_definition_from_attribute!("lineStyle", ""));
+        state_var_definitions.insert("lineStyleWord", string_definition_from_attribute!("lineStyleWord", ""));
+        state_var_definitions.insert("markerColor", string_definition_from_attribute!("markerColor", ""));
+        state_var_definitions.insert("markerColorWord", string_definition_from_attribute!("markerColorWord", ""));
+        state_var_definitions.insert("markerOpacity", string_definition_from_attribute!("markerOpacity", ""));
+        state_var_definitions.insert("markerStyle", string_definition_from_attribute!("markerStyle", ""));
+        state_var_definitions.insert("markerStyleWord", string_definition_from_attribute!("markerStyleWord", ""));
+        state_var_definitions.insert("markerSize", string_definition_from_attribute!("markerSize", ""));
+        state_var_definitions.insert("fillColor", string_definition_from_attribute!("fillColor", ""));
+        state_var_definitions.insert("fillColorWord", string_definition_from_attribute!("fillColorWord", ""));
+        state_var_definitions.insert("fillOpacity", string_definition_from_attribute!("fillOpacity", ""));
+
+        // Only the properties that were given, to be applied over the existing definition
+        state_var_definitions.insert("styleDefinitions", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                NUMBER_STYLE_PROPERTIES.iter()
+                    .chain(STRING_STYLE_PROPERTIES.iter())
+                    .chain(std::iter::once(&"styleNumber"))
+                    .map(|&name| (name, DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic(name)),
+                    }))
+                    .collect()
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let style_number = dependency_values.dep_value("styleNumber")?
+                    .has_exactly_one_element()?
+                    .into_integer()?;
+
+                let mut properties = Map::new();
+                for property in NUMBER_STYLE_PROPERTIES {
+                    let value = dependency_values.dep_value(property)?
+                        .has_exactly_one_element()?
+                        .into_string()?;
+                    if let Ok(number) = value.trim().parse::<f64>() {
+                        properties.insert(property.to_string(), Value::from(number));
+                    }
+                }
+                for property in STRING_STYLE_PROPERTIES {
+                    let value = dependency_values.dep_value(property)?
+                        .has_exactly_one_element()?
+                        .into_string()?;
+                    if !value.is_empty() {
+                        properties.insert(property.to_string(), Value::from(value.to_lowercase()));
+                    }
+                }
+
+                let changes = StyleChanges::from([(style_number, properties)]);
+                Ok( SetValue( style_changes_to_json(&changes) ) )
+            },
+            ..Default::default()
+        }));
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "styleDefinition",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        attribute_names: std::iter::once("styleNumber")
+            .chain(NUMBER_STYLE_PROPERTIES)
+            .chain(STRING_STYLE_PROPERTIES)
+            .collect(),
+
+        component_profiles: vec![
+            (ComponentProfile::StyleDefinitions, "styleDefinitions"),
+        ],
+
+        renderer_type: RendererType::DoNotRender,
+
+        ..Default::default()
+    };
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+use crate::style::*;
+
+use super::*;
+
+
+
+/// The style changes of `<styleDefinitions>` and `<styleDefinition>` children, in order
+pub(super) fn style_definition_children_instruction() -> DependencyInstruction {
+    DependencyInstruction::Child {
+        desired_profiles: vec![ComponentProfile::StyleDefinitions],
+        parse_into_expression: false,
+    }
+}
+
+pub(super) fn style_changes_from_dependency_values(
+    dependency_values: &HashMap<InstructionName, Vec<DependencyValue>>,
+) -> Result<StyleChanges, String> {
+    let mut changes = StyleChanges::new();
+    for json in dependency_values.dep_value("style_definition_children")?.into_string_list()? {
+        merge_style_changes(&mut changes, style_changes_from_json(&json));
+    }
+    Ok(changes)
+}
+
+
+macro_rules! style_property_definition {
+    ( $property:expr, $variant:ident ) => {
+        StateVarVariant::$variant(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([
+                    ("style", DependencyInstruction::StateVar {
+                        component_ref: None,
+                        state_var: StateVarSlice::Single(StateRef::Basic("style")),
+                    }),
+                ])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let style = dependency_values.dep_value("style")?
+                    .has_exactly_one_element()?
+                    .into_string()?;
+                let property = StyleDefinition::from_json(&style)
+                    .unwrap_or_default()
+                    .property($property)
+                    .ok_or_else(|| format!("Style definitions have no property {}", $property))?;
+
+                serde_json::from_value(property)
+                    .map(StateVarUpdateInstruction::SetValue)
+                    .map_err(|e| e.to_string())
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// The style state vars of a graphical component, selected from the
+/// document's style definitions by its `styleNumber` attribute.
+/// The properties are sent to the renderer as `selectedStyle`.
+pub(super) fn style_state_var_definitions() -> HashMap<StateVarName, StateVarVariant> {
+    use StateVarUpdateInstruction::*;
+
+    let mut state_var_definitions = HashMap::new();
+
+    state_var_definitions.insert("styleNumber", integer_definition_from_attribute!("styleNumber", 1));
+
+    state_var_definitions.insert("style", StateVarVariant::String(StateVarDefinition {
+        return_dependency_instructions: |_| {
+            HashMap::from([
+                ("styleNumber", DependencyInstruction::StateVar {
+                    component_ref: None,
+                    state_var: StateVarSlice::Single(StateRef::Basic("styleNumber")),
+                }),
+                ("styleDefinitions", DependencyInstruction::Document {
+                    state_var: "styleDefinitions",
+                }),
+            ])
+        },
+
+        determine_state_var_from_dependencies: |dependency_values| {
+            let style_number = dependency_values.dep_value("styleNumber")?
+                .has_exactly_one_element()?
+                .into_integer()?;
+            let definitions = dependency_values.dep_value("styleDefinitions")?
+                .has_exactly_one_element()?
+                .into_string()?;
+
+            let style = select_style(&style_definitions_from_json(&definitions), style_number);
+            Ok( SetValue( style.to_json() ) )
+        },
+        ..Default::default()
+    }));
+
+    state_var_definitions.insert("lineColor", style_property_definition!("lineColor", String));
+    state_var_definitions.insert("lineColorWord", style_property_definition!("lineColorWord", String));
+    state_var_definitions.insert("lineOpacity", style_property_definition!("lineOpacity", Number));
+    state_var_definitions.insert("lineWidth", style_property_definition!("lineWidth", Number));
+    state_var_definitions.insert("lineWidthWord", style_property_definition!("lineWidthWord", String));
+    state_var_definitions.insert("lineStyle", style_property_definition!("lineStyle", String));
+    state_var_definitions.insert("lineStyleWord", style_property_definition!("lineStyleWord", String));
+    state_var_definitions.insert("markerColor", style_property_definition!("markerColor", String));
+    state_var_definitions.insert("markerColorWord", style_property_definition!("markerColorWord", String));
+    state_var_definitions.insert("markerOpacity", style_property_definition!("markerOpacity", Number));
+    state_var_definitions.insert("markerStyle", style_property_definition!("markerStyle", String));
+    state_var_definitions.insert("markerStyleWord", style_property_definition!("markerStyleWord", String));
+    state_var_definitions.insert("markerSize", style_property_definition!("markerSize", Number));
+    state_var_definitions.insert("fillColor", style_property_definition!("fillColor", String));
+    state_var_definitions.insert("fillColorWord", style_property_definition!("fillColorWord", String));
+    state_var_definitions.insert("fillOpacity", style_property_definition!("fillOpacity", Number));
+
+    state_var_definitions
+}
+
+/// For the `renderer_objects` of a component with `style_state_var_definitions`
+pub(super) fn selected_style_renderer_object() -> (&'static str, Vec<StateVarName>) {
+    let properties = NUMBER_STYLE_PROPERTIES.iter()
+        .chain(STRING_STYLE_PROPERTIES.iter())
+        .copied()
+        .collect();
+    ("selectedStyle", properties)
+}
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("styleDefinitions", StateVarVariant::String(StateVarDefinition {
+            return_dependency_instructions: |_| {
+                HashMap::from([("style_definition_children", style_definition_children_instruction())])
+            },
+
+            determine_state_var_from_dependencies: |dependency_values| {
+                let changes = style_changes_from_dependency_values(&dependency_values)?;
+                Ok( SetValue( style_changes_to_json(&changes) ) )
+            },
+            ..Default::default()
+        }));
+
+        return state_var_definitions
+    };
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "styleDefinitions",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        component_profiles: vec![
+            (ComponentProfile::StyleDefinitions, "styleDefinitions"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::StyleDefinitions]),
+
+        renderer_type: RendererType::DoNotRender,
+
+        ..Default::default()
+    };
+}
@@ -28,6 +28,10 @@ lazy_static! {
             "disabled",
         ],
 
+        static_attribute_names: vec![
+            "newNamespace",
+        ],
+
         renderer_type: RendererType::Special { component_type: "containerInline", state_var_aliases: HashMap::new() },
 
         should_render_children: true,
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::state_variables::*;
+use crate::base_definitions::*;
+
+use super::*;
+
+use crate::ComponentProfile;
+
+
+lazy_static! {
+    pub static ref MY_STATE_VAR_DEFINITIONS: HashMap<StateVarName, StateVarVariant> = {
+
+        use StateVarUpdateInstruction::*;
+
+        let mut state_var_definitions = HashMap::new();
+
+        state_var_definitions.insert("value", StateVarVariant::StringArray(StateVarArrayDefinition {
+            for_renderer: true,
+
+            return_array_dependency_instructions: |_| {
+                list_dependency_instructions(vec![ComponentProfile::Text])
+            },
+
+            determine_element_from_dependencies: |index, dependency_values| {
+                let items = list_items(&dependency_values)?;
+                Ok(SetValue(items.get(index).map(list_item_as_string).unwrap_or_default()))
+            },
+
+            request_element_dependencies_to_update_value: |index, desired_value, sources| {
+                HashMap::from([
+                    ("children", list_item_dependencies(index, StateVarValue::String(desired_value), &sources)),
+                ])
+            },
+
+            return_size_dependency_instructions: |_| {
+                list_dependency_instructions(vec![ComponentProfile::Text])
+            },
+
+            determine_size_from_dependencies: |dependency_values| {
+                Ok(SetValue(list_size(&dependency_values)?))
+            },
+
+            ..Default::default()
+        }));
+
+        state_var_definitions.insert("maxNumber", number_definition_from_attribute!("maxNumber", f64::INFINITY));
+
+        state_var_definitions.insert("hidden", HIDDEN_DEFAULT_DEFINITION());
+
+        state_var_definitions.insert("disabled", DISABLED_DEFAULT_DEFINITION());
+
+        state_var_definitions.insert("fixed", FIXED_DEFAULT_DEFINITION());
+
+        return state_var_definitions
+    };
+}
+
+fn member_state_var(
+    index: usize,
+    state_var_slice: &StateVarSlice,
+) -> Option<StateVarSlice> {
+
+    let slice = match state_var_slice {
+        StateVarSlice::Single(StateRef::Basic("value")) |
+        StateVarSlice::Single(StateRef::Basic("text")) => {
+            StateVarSlice::Single(StateRef::ArrayElement("value", vec![index]))
+        },
+        _ => state_var_slice.clone(),
+    };
+
+    Some(slice)
+}
+
+lazy_static! {
+    pub static ref MY_COMPONENT_DEFINITION: ComponentDefinition = ComponentDefinition {
+        component_type: "textList",
+
+        state_var_definitions: &MY_STATE_VAR_DEFINITIONS,
+
+        replacement_components: Some(ReplacementComponents::Batch(BatchDefinition {
+            member_definition: &super::text::MY_COMPONENT_DEFINITION,
+            size: StateRef::SizeOf("value"),
+            member_state_var,
+        })),
+
+        attribute_names: vec![
+            "maxNumber",
+            "hide",
+            "disabled",
+            "fixed",
+        ],
+
+        static_attribute_names: vec![
+            "assignNames",
+        ],
+
+        primary_input_state_var: Some("value"),
+
+        component_profiles: vec![
+            (ComponentProfile::Text, "value"),
+        ],
+
+        valid_children_profiles: ValidChildTypes::ValidProfiles(vec![ComponentProfile::Text]),
+
+        ..Default::default()
+    };
+}
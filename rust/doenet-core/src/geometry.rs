@@ -0,0 +1,339 @@
+use serde::{Serialize, Deserialize};
+
+
+/// The shape of a graphical component, given by its `Graphical` profile state var.
+/// State vars carry shapes and constraints as JSON strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Geometry {
+    Point { xs: Vec<f64> },
+    /// The infinite line through two points
+    Line { p1: Vec<f64>, p2: Vec<f64> },
+    Polyline { vertices: Vec<Vec<f64>> },
+    Polygon { vertices: Vec<Vec<f64>> },
+    Circle { center: Vec<f64>, radius: f64 },
+}
+
+/// Something that changes requested coordinates of a point before they are used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Constraint {
+    ConstrainToGrid { dx: f64, dy: f64, xoffset: f64, yoffset: f64 },
+    /// Move to the nearest object when closer than the threshold
+    AttractTo { objects: Vec<Geometry>, threshold: f64 },
+    ConstrainTo { objects: Vec<Geometry> },
+    /// Stay inside the objects, or on the object itself when it has no interior
+    ConstrainToInterior { objects: Vec<Geometry> },
+}
+
+
+impl Geometry {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// None when the shape has unresolved (NaN) coordinates, which do not survive JSON
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    pub fn nearest_point(&self, xs: &[f64]) -> Vec<f64> {
+        match self {
+            Self::Point { xs: point } => point.clone(),
+            Self::Line { p1, p2 } => project_onto_line(xs, p1, p2, false),
+            Self::Polyline { vertices } => nearest_on_path(xs, vertices, false),
+            Self::Polygon { vertices } => nearest_on_path(xs, vertices, true),
+            Self::Circle { center, radius } => {
+                let distance = distance(xs, center);
+                if distance == 0.0 {
+                    // Every point on the circle is as near, so take the rightmost
+                    let mut nearest = center.clone();
+                    nearest[0] += radius;
+                    nearest
+                } else {
+                    center.iter().zip(xs)
+                        .map(|(c, x)| c + (x - c) * radius / distance)
+                        .collect()
+                }
+            },
+        }
+    }
+
+    /// Whether the coordinates are inside or on the shape.
+    /// Shapes without an interior only contain their own points.
+    pub fn contains(&self, xs: &[f64]) -> bool {
+        match self {
+            Self::Circle { center, radius } => distance(xs, center) <= *radius,
+            Self::Polygon { vertices } => {
+                // Count the edges crossed by a ray going right from xs
+                let (x, y) = (xs[0], xs[1]);
+                let n = vertices.len();
+                let crossings = (0..n).filter(|&i| {
+                    let (a, b) = (&vertices[i], &vertices[(i + 1) % n]);
+                    (a[1] > y) != (b[1] > y)
+                        && x < a[0] + (y - a[1]) * (b[0] - a[0]) / (b[1] - a[1])
+                }).count();
+                crossings % 2 == 1
+            },
+            _ => distance(xs, &self.nearest_point(xs)) == 0.0,
+        }
+    }
+}
+
+
+impl Constraint {
+    pub fn apply(&self, xs: &[f64]) -> Vec<f64> {
+        match self {
+            Self::ConstrainToGrid { dx, dy, xoffset, yoffset } => {
+                let snap = |x: f64, step: f64, offset: f64|
+                    if step == 0.0 { x } else { offset + step * ((x - offset) / step).round() };
+
+                let mut snapped = xs.to_vec();
+                snapped[0] = snap(xs[0], *dx, *xoffset);
+                snapped[1] = snap(xs[1], *dy, *yoffset);
+                snapped
+            },
+            Self::AttractTo { objects, threshold } => {
+                match nearest_of_objects(xs, objects) {
+                    Some(nearest) if distance(xs, &nearest) < *threshold => nearest,
+                    _ => xs.to_vec(),
+                }
+            },
+            Self::ConstrainTo { objects } => {
+                nearest_of_objects(xs, objects).unwrap_or(xs.to_vec())
+            },
+            Self::ConstrainToInterior { objects } => {
+                if objects.is_empty() || objects.iter().any(|object| object.contains(xs)) {
+                    xs.to_vec()
+                } else {
+                    nearest_of_objects(xs, objects).unwrap()
+                }
+            },
+        }
+    }
+}
+
+
+/// The points where two shapes cross, in the order they are found along the first shape.
+/// Overlapping lines or circles have no intersection points.
+pub fn intersections(a: &Geometry, b: &Geometry) -> Vec<Vec<f64>> {
+    let mut points: Vec<Vec<f64>> = Vec::new();
+
+    for piece_a in a.pieces() {
+        for piece_b in b.pieces() {
+            for point in piece_a.intersections(&piece_b) {
+                // Adjacent edges share a vertex, which is only one intersection
+                if !points.iter().any(|existing| distance(existing, &point) < TOLERANCE) {
+                    points.push(point);
+                }
+            }
+        }
+    }
+
+    points
+}
+
+
+const TOLERANCE: f64 = 1e-10;
+
+/// The straight or round parts of a shape
+enum Piece<'a> {
+    Point(&'a [f64]),
+    /// Through `a` and `b`, and only between them for a segment
+    Line { a: &'a [f64], b: &'a [f64], segment: bool },
+    Circle { center: &'a [f64], radius: f64 },
+}
+
+impl Geometry {
+    fn pieces(&self) -> Vec<Piece<'_>> {
+        let edges = |vertices: &'_ [Vec<f64>], closed: bool| -> Vec<(usize, usize)> {
+            let n = vertices.len();
+            let num_edges = if closed && n > 2 { n } else { n.saturating_sub(1) };
+            (0..num_edges).map(|i| (i, (i + 1) % n)).collect()
+        };
+
+        match self {
+            Self::Point { xs } => vec![Piece::Point(xs)],
+            Self::Line { p1, p2 } => vec![Piece::Line { a: p1, b: p2, segment: false }],
+            Self::Polyline { vertices } | Self::Polygon { vertices } => {
+                let closed = matches!(self, Self::Polygon { .. });
+                edges(vertices, closed).into_iter()
+                    .map(|(i, j)| Piece::Line { a: &vertices[i], b: &vertices[j], segment: true })
+                    .collect()
+            },
+            Self::Circle { center, radius } => vec![Piece::Circle { center, radius: *radius }],
+        }
+    }
+}
+
+impl<'a> Piece<'a> {
+    fn contains(&self, xs: &[f64]) -> bool {
+        let nearest = match self {
+            Self::Point(point) => point.to_vec(),
+            Self::Line { a, b, segment } => project_onto_line(xs, a, b, *segment),
+            Self::Circle { center, radius } => Geometry::Circle { center: center.to_vec(), radius: *radius }
+                .nearest_point(xs),
+        };
+        distance(xs, &nearest) < TOLERANCE
+    }
+
+    fn intersections(&self, other: &Piece<'a>) -> Vec<Vec<f64>> {
+        match (self, other) {
+            (Self::Point(xs), piece) | (piece, Self::Point(xs)) =>
+                if piece.contains(xs) { vec![xs.to_vec()] } else { vec![] },
+
+            (Self::Line { a, b, segment }, Self::Line { a: c, b: d, segment: other_segment }) => {
+                let direction = [b[0] - a[0], b[1] - a[1]];
+                let other_direction = [d[0] - c[0], d[1] - c[1]];
+                let offset = [c[0] - a[0], c[1] - a[1]];
+
+                let denominator = cross(&direction, &other_direction);
+                if denominator.abs() < TOLERANCE {
+                    return vec![]
+                }
+                let t = cross(&offset, &other_direction) / denominator;
+                let s = cross(&offset, &direction) / denominator;
+
+                let within = |t: f64, segment: bool| !segment || (-TOLERANCE..=1.0 + TOLERANCE).contains(&t);
+                if within(t, *segment) && within(s, *other_segment) {
+                    vec![vec![a[0] + t * direction[0], a[1] + t * direction[1]]]
+                } else {
+                    vec![]
+                }
+            },
+
+            (Self::Line { a, b, segment }, Self::Circle { center, radius }) |
+            (Self::Circle { center, radius }, Self::Line { a, b, segment }) => {
+                // Solve |a + t(b - a) - center| = radius for t
+                let direction = [b[0] - a[0], b[1] - a[1]];
+                let offset = [a[0] - center[0], a[1] - center[1]];
+
+                let quadratic = dot(&direction, &direction);
+                let linear = 2.0 * dot(&offset, &direction);
+                let constant = dot(&offset, &offset) - radius * radius;
+                if quadratic == 0.0 {
+                    return vec![]
+                }
+
+                let discriminant = linear * linear - 4.0 * quadratic * constant;
+                let ts = if discriminant.abs() < TOLERANCE {
+                    vec![-linear / (2.0 * quadratic)]
+                } else if discriminant < 0.0 {
+                    vec![]
+                } else {
+                    let root = discriminant.sqrt();
+                    vec![(-linear - root) / (2.0 * quadratic), (-linear + root) / (2.0 * quadratic)]
+                };
+
+                ts.into_iter()
+                    .filter(|t| !segment || (-TOLERANCE..=1.0 + TOLERANCE).contains(t))
+                    .map(|t| vec![a[0] + t * direction[0], a[1] + t * direction[1]])
+                    .collect()
+            },
+
+            (Self::Circle { center, radius }, Self::Circle { center: other_center, radius: other_radius }) => {
+                let between = distance(center, other_center);
+                if between < TOLERANCE
+                    || between > radius + other_radius + TOLERANCE
+                    || between < (radius - other_radius).abs() - TOLERANCE {
+                    return vec![]
+                }
+
+                // The crossing points are on the chord perpendicular to the line between the centers
+                let along = (radius * radius - other_radius * other_radius + between * between) / (2.0 * between);
+                let half_chord = (radius * radius - along * along).max(0.0).sqrt();
+                let unit = [(other_center[0] - center[0]) / between, (other_center[1] - center[1]) / between];
+                let middle = [center[0] + along * unit[0], center[1] + along * unit[1]];
+
+                if half_chord < TOLERANCE {
+                    vec![middle.to_vec()]
+                } else {
+                    vec![
+                        vec![middle[0] - half_chord * unit[1], middle[1] + half_chord * unit[0]],
+                        vec![middle[0] + half_chord * unit[1], middle[1] - half_chord * unit[0]],
+                    ]
+                }
+            },
+        }
+    }
+}
+
+
+/// The value of a `Constraint` profile state var: the constraints in the order they apply
+pub fn constraints_to_json(constraints: &[Constraint]) -> String {
+    serde_json::to_string(constraints).unwrap()
+}
+
+pub fn constraints_from_json(json: &str) -> Result<Vec<Constraint>, String> {
+    serde_json::from_str(json).map_err(|err| format!("invalid constraints {}: {}", json, err))
+}
+
+/// Constraints compose in order, each one changing the result of the last.
+/// They act in the plane, so any coordinates past the first two are left alone.
+pub fn apply_constraints(constraints: &[Constraint], xs: &[f64]) -> Vec<f64> {
+    if xs.len() < 2 {
+        return xs.to_vec()
+    }
+    let (plane, rest) = xs.split_at(2);
+    let mut constrained = constraints.iter().fold(plane.to_vec(), |xs, constraint| constraint.apply(&xs));
+    constrained.extend_from_slice(rest);
+    constrained
+}
+
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn cross(a: &[f64], b: &[f64]) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn nearest_of_objects(xs: &[f64], objects: &[Geometry]) -> Option<Vec<f64>> {
+    objects.iter()
+        .map(|object| object.nearest_point(xs))
+        .min_by(|a, b| distance(xs, a).total_cmp(&distance(xs, b)))
+}
+
+/// Project onto the line through `a` and `b`, staying between them for a segment
+fn project_onto_line(xs: &[f64], a: &[f64], b: &[f64], segment: bool) -> Vec<f64> {
+    let direction: Vec<f64> = a.iter().zip(b).map(|(a, b)| b - a).collect();
+    let length_squared: f64 = direction.iter().map(|d| d * d).sum();
+    if length_squared == 0.0 {
+        return a.to_vec()
+    }
+
+    let mut t = xs.iter().zip(a).zip(&direction)
+        .map(|((x, a), d)| (x - a) * d)
+        .sum::<f64>() / length_squared;
+    if segment {
+        t = t.clamp(0.0, 1.0);
+    }
+
+    a.iter().zip(&direction).map(|(a, d)| a + t * d).collect()
+}
+
+fn nearest_on_path(xs: &[f64], vertices: &[Vec<f64>], closed: bool) -> Vec<f64> {
+    if vertices.len() < 2 {
+        return vertices.first().cloned().unwrap_or(xs.to_vec())
+    }
+
+    let mut edges: Vec<(&Vec<f64>, &Vec<f64>)> = vertices.windows(2)
+        .map(|pair| (&pair[0], &pair[1]))
+        .collect();
+    if closed {
+        edges.push((vertices.last().unwrap(), &vertices[0]));
+    }
+
+    edges.into_iter()
+        .map(|(a, b)| project_onto_line(xs, a, b, true))
+        .min_by(|a, b| distance(xs, a).total_cmp(&distance(xs, b)))
+        .unwrap()
+}
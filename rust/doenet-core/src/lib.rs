@@ -6,22 +6,26 @@ pub mod parse_json;
 pub mod utils;
 pub mod base_definitions;
 pub mod math_expression;
+pub mod geometry;
+pub mod style;
+pub mod sampling;
 
 use base_definitions::{PROP_INDEX_SV, prop_index_determine_value, get_children_of_type};
 use lazy_static::lazy_static;
 use parse_json::{DoenetMLError, DoenetMLWarning, MLComponent};
 use state::StateForStateVar;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
 
-use state::{State, EssentialStateVar};
+use state::{State, EssentialStateVar, SavedEssentialValue};
 use component::*;
 use state_variables::*;
 
 use crate::math_expression::MathExpression;
 use crate::utils::{log_json, log_debug, log};
-use serde::Serialize;
+use serde::{Serialize, Deserialize};
 
 
 /// A static DoenetCore is created from parsed DoenetML at the beginning.
@@ -54,6 +58,105 @@ pub struct DoenetCore {
     /// Endpoints of the dependency graph.
     /// Every update instruction will lead to these.
     pub essential_data: HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>,
+
+    pub flags: CoreFlags,
+
+    /// What saving, undo and event recording need to know about past actions.
+    /// Transient actions do not show up here until a final action commits them.
+    pub action_history: RefCell<ActionHistory>,
+
+    /// The state values last sent to each renderer, by rendered name.
+    /// While a drag is in progress, hidden components reuse these
+    /// instead of recomputing their state.
+    pub rendered_state_values: RefCell<HashMap<String, serde_json::Map<String, serde_json::Value>>>,
+
+    /// The sizes of the sources of each map instance, by the name of the map,
+    /// as they were last found. When a size shrinks, the map instances that no longer
+    /// exist are released, but their essential data is kept so that the values
+    /// return with the instance.
+    pub map_sources_sizes: RefCell<HashMap<(ComponentName, Instance), Vec<usize>>>,
+
+    /// The entry last sent to the renderer for each component inside a map instance,
+    /// along with that instance. Only the entries that changed are sent again.
+    pub rendered_instance_entries: RefCell<HashMap<String, (Instance, serde_json::Value)>>,
+}
+
+
+/// Settings given by the viewer when the core is created.
+/// Components read these through state variables of the <document>.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CoreFlags {
+    pub show_correctness: bool,
+
+    /// Every action is rejected, so that the document cannot be changed
+    pub read_only: bool,
+
+    pub solution_display_mode: SolutionDisplayMode,
+    pub show_feedback: bool,
+    pub show_hints: bool,
+    pub allow_save_state: bool,
+
+    /// The labels of the submit buttons, with and without correctness shown
+    pub submit_label: String,
+    pub submit_label_no_correctness: String,
+
+    /// The variant of the document, which seeds its random choices
+    pub variant_index: i64,
+}
+
+impl Default for CoreFlags {
+    fn default() -> Self {
+        CoreFlags {
+            show_correctness: true,
+            read_only: false,
+            solution_display_mode: SolutionDisplayMode::Button,
+            show_feedback: true,
+            show_hints: true,
+            allow_save_state: false,
+            submit_label: "Check Work".to_string(),
+            submit_label_no_correctness: "Submit Response".to_string(),
+            variant_index: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SolutionDisplayMode {
+    /// Solutions can be revealed with a button
+    Button,
+    /// Solutions have a button, but can only be revealed once
+    /// `set_solution_display_permission` allows it
+    ButtonRequirePermission,
+    /// Solutions are hidden
+    None,
+}
+
+impl SolutionDisplayMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SolutionDisplayMode::Button => "button",
+            SolutionDisplayMode::ButtonRequirePermission => "buttonRequirePermission",
+            SolutionDisplayMode::None => "none",
+        }
+    }
+}
+
+impl CoreFlags {
+    /// The <document> state vars that hold each flag
+    fn document_state_values(&self) -> Vec<(StateVarName, StateVarValue)> {
+        vec![
+            ("showCorrectness", StateVarValue::Boolean(self.show_correctness)),
+            ("readOnly", StateVarValue::Boolean(self.read_only)),
+            ("solutionDisplayMode", StateVarValue::String(self.solution_display_mode.as_str().to_string())),
+            ("showFeedback", StateVarValue::Boolean(self.show_feedback)),
+            ("showHints", StateVarValue::Boolean(self.show_hints)),
+            ("submitLabel", StateVarValue::String(self.submit_label.clone())),
+            ("submitLabelNoCorrectness", StateVarValue::String(self.submit_label_no_correctness.clone())),
+            ("variantIndex", StateVarValue::Integer(self.variant_index)),
+        ]
+    }
 }
 
 
@@ -169,6 +272,10 @@ pub enum Dependency {
         map_sources: ComponentRelative, // sources component, a collection
         state_var_slice: StateVarSlice,
     },
+    /// The index of the member of the sources, which is a number
+    MapSourcesIndex {
+        map_sources: ComponentRelative,
+    },
     /// Necessary when a child dependency instruction encounters a groups
     /// whose members replace themselves with (unknown) children
     /// For example, <template> inside <map>, or <case> inside <conditionalContent>
@@ -195,7 +302,8 @@ pub enum CollectionMembers {
     /// The members of this are the same component, but different instances
     InstanceBySources {
         template: ComponentRelative,
-        sources: ComponentRelative, // a collection
+        sources: Vec<ComponentRelative>, // collections
+        behavior: SourcesBehavior,
     },
 
     // /// Points to collection of whose members are undetermined children of a certain type
@@ -205,11 +313,50 @@ pub enum CollectionMembers {
 
 
 
+/// How a map pairs up the members of several sources into instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SourcesBehavior {
+    /// Every combination of members, with one index of the Instance for each sources
+    Combination,
+    /// The nth members of all the sources together, with one index of the Instance
+    Parallel,
+}
+
+impl SourcesBehavior {
+    pub fn from_static_attributes(static_attributes: &HashMap<AttributeName, String>) -> Self {
+        match static_attributes.get("behavior") {
+            Some(behavior) if behavior.eq_ignore_ascii_case("parallel") => SourcesBehavior::Parallel,
+            _ => SourcesBehavior::Combination,
+        }
+    }
+}
+
+
 
 pub fn create_doenet_core(
     program: &str,
+    flags: CoreFlags,
     existing_essential_data: Option<HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>>,
 ) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
+    create_doenet_core_with(program, flags, existing_essential_data, vec![])
+}
+
+/// Create the core with the essential data saved from an earlier visit.
+/// Saved data that the components no longer have is ignored.
+pub fn create_doenet_core_from_saved_state(
+    program: &str,
+    flags: CoreFlags,
+    saved_state: Vec<SavedEssentialState>,
+) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
+    create_doenet_core_with(program, flags, None, saved_state)
+}
+
+fn create_doenet_core_with(
+    program: &str,
+    flags: CoreFlags,
+    existing_essential_data: Option<HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>>,
+    saved_state: Vec<SavedEssentialState>,
+) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
 
     log!("===== DoenetCore creation =====");
 
@@ -222,8 +369,10 @@ pub fn create_doenet_core(
     let component_nodes = convert_ml_components_into_component_nodes(ml_components, map_sources_alias, &mut doenet_ml_warnings)?;
 
     doenet_ml_warnings.extend(check_for_invalid_childen_component_profiles(&component_nodes));
+    doenet_ml_warnings.extend(check_for_array_elements_out_of_range(&component_nodes, &component_attributes));
     check_for_cyclical_copy_sources(&component_nodes)?;
     check_for_invalid_component_names(&component_nodes, &component_attributes)?;
+    check_for_missing_dependency_state_vars(&component_nodes, &component_attributes)?;
 
     let collection_members = fill_collection_members(&component_nodes);
     let (dependencies, essential_data) = create_dependencies_and_essential_data(
@@ -231,6 +380,8 @@ pub fn create_doenet_core(
         &component_attributes,
         existing_essential_data
     );
+    load_saved_state(saved_state, &essential_data);
+    set_document_flags(&root_component_name, &flags, &essential_data);
     check_for_cyclical_dependencies(&dependencies)?;
 
     let component_states = create_stale_component_states(&component_nodes);
@@ -252,10 +403,71 @@ pub fn create_doenet_core(
         dependencies,
         collection_members,
         essential_data,
+        flags,
+        action_history: RefCell::new(ActionHistory::default()),
+        rendered_state_values: RefCell::new(HashMap::new()),
+        map_sources_sizes: RefCell::new(HashMap::new()),
+        rendered_instance_entries: RefCell::new(HashMap::new()),
     }, doenet_ml_warnings))
 }
 
 
+/// Set the essential data to the values saved from an earlier visit.
+fn load_saved_state(
+    saved_state: Vec<SavedEssentialState>,
+    essential_data: &HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>,
+) {
+    for saved in saved_state {
+        let essential = essential_data.get(&saved.component_name)
+            .and_then(|component_data| component_data.iter()
+                .find(|(origin, _)| SavedEssentialDataOrigin::from(*origin) == saved.origin))
+            .map(|(_, essential)| essential);
+
+        let loaded = match essential {
+            Some(essential) => essential.load_saved_instance(&saved.instance, saved.value),
+            None => Err("the component has no such essential data".to_string()),
+        };
+        if let Err(err) = loaded {
+            log!("Ignoring saved {:?} of {}: {}", saved.origin, saved.component_name, err);
+        }
+    }
+}
+
+/// Store the flags in the essential data of the document's flag state vars.
+/// Flags always overwrite reloaded essential data, but a solution permission
+/// that was already granted is kept.
+fn set_document_flags(
+    document_name: &ComponentName,
+    flags: &CoreFlags,
+    essential_data: &HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>,
+) {
+    let Some(document_essential_data) = essential_data.get(document_name) else {
+        log!("Ignoring flags for a document without essential data");
+        return;
+    };
+
+    let permission_granted = document_essential_data
+        .get(&EssentialDataOrigin::StateVar("solutionPermissionGranted"))
+        .and_then(|essential| essential.get_value(StateIndex::Basic, &Instance::default()))
+        == Some(StateVarValue::Boolean(true));
+    let can_reveal_solutions = permission_granted
+        || flags.solution_display_mode == SolutionDisplayMode::Button;
+
+    let mut flag_values = flags.document_state_values();
+    flag_values.push(("canRevealSolutions", StateVarValue::Boolean(can_reveal_solutions)));
+
+    for (state_var_name, value) in flag_values {
+        let Some(essential) = document_essential_data.get(&EssentialDataOrigin::StateVar(state_var_name)) else {
+            log!("Ignoring flag {} that the document has no essential data for", state_var_name);
+            continue;
+        };
+        if let Err(err) = essential.set_value(StateIndex::Basic, value, &Instance::default()) {
+            log!("Ignoring flag {}: {}", state_var_name, err);
+        }
+    }
+}
+
+
 /// Add CopySource info
 fn convert_ml_components_into_component_nodes(
     ml_components: HashMap<ComponentName, MLComponent>,
@@ -311,6 +523,9 @@ fn copy_source_for_ml_component(
             name: map_source.clone(),
             relative_instance,
         };
+        if parse_json::is_index_alias(source_comp_name, source_comp) {
+            return Ok(Some(CopySource::MapSourcesIndex(copy_map_source)));
+        }
         return Ok(Some(CopySource::MapSources(copy_map_source)));
     }
 
@@ -327,6 +542,53 @@ fn copy_source_for_ml_component(
     };
 
     let component_index = &ml_component.component_index;
+
+    if let (true, Some(copy_prop), Some(ReplacementComponents::Collection(def))) = (
+        component_index.is_empty(),
+        &ml_component.copy_prop,
+        &source_comp.definition.replacement_components,
+    ) {
+        // The prop of every member is copied into a list
+        if !matches!(ml_component.definition.replacement_components, Some(ReplacementComponents::Batch(_))) {
+            return Err(DoenetMLError::ComponentCannotCopyOtherType {
+                component_name: ml_component.name.clone(),
+                component_type: ml_component.definition.component_type,
+                source_type: source_comp.definition.component_type,
+            });
+        }
+        if source_comp.definition.component_type == "collect" {
+            let mut member_types = collect::component_types(&source_comp.static_attributes);
+            member_types.sort();
+            member_types.dedup();
+            if member_types.len() > 1 {
+                return Err(DoenetMLError::CannotCopyPropOfMembersOfDifferentTypes {
+                    source_comp_name: source_comp.name.clone(),
+                    source_sv_name: copy_prop.clone(),
+                });
+            }
+        }
+        let member_def = (def.member_definition)(&source_comp.static_attributes);
+        let state_ref = match member_def.array_aliases.get(copy_prop.as_str()) {
+            Some(state_ref) => state_ref.clone(),
+            None => {
+                let (&source_sv_name, source_sv_def) = member_def
+                    .state_var_definitions
+                    .get_key_value_ignore_case(copy_prop.as_str())
+                    .ok_or(DoenetMLError::StateVarDoesNotExist {
+                        comp_name: source_comp.name.clone(),
+                        sv_name: copy_prop.clone(),
+                    })?;
+                if source_sv_def.is_array() {
+                    return Err(DoenetMLError::CannotCopyArrayStateVar {
+                        source_comp_name: source_comp.name.clone(),
+                        source_sv_name,
+                    });
+                }
+                StateRef::Basic(source_sv_name)
+            },
+        };
+        return Ok(Some(CopySource::CollectionStateVar(copy_node_relative, state_ref)));
+    }
     let (copy_ref_relative, source_def) = match (component_index.len(), component_index.first()) {
         (1, Some(ObjectName::String(first_string))) => {
 
@@ -405,6 +667,12 @@ fn copy_source_for_ml_component(
         .unwrap();
 
     let prop_index = &ml_component.prop_index;
+    if !prop_index.is_empty() && !source_sv_def.inner_dimensions().is_empty() {
+        return Err(DoenetMLError::CannotCopyIndexForMultiDimensionalStateVar {
+            source_comp_name: copy_ref_relative.of_node_relative().name.clone(),
+            source_sv_name,
+        });
+    }
     match (prop_index.len(), prop_index.first()) {
         (1, Some(ObjectName::String(first_string))) => {
 
@@ -429,13 +697,19 @@ fn copy_source_for_ml_component(
 
             Ok(Some(CopySource::StateVar(ComponentRefStateRelative(
                 copy_ref_relative,
-                StateRef::ArrayElement(source_sv_name, index)
+                StateRef::ArrayElement(source_sv_name, vec![index])
             ))))
         },
         (0, _) => {
 
             // no index
             if source_sv_def.is_array() {
+                if matches!(ml_component.definition.replacement_components, Some(ReplacementComponents::Batch(_))) {
+                    // a list holds every element
+                    return Ok(Some(CopySource::StateVarArray(
+                        ComponentRefStateArrayRelative(copy_ref_relative, source_sv_name)
+                    )));
+                }
                 return Err(DoenetMLError::CannotCopyArrayStateVar {
                     source_comp_name: copy_ref_relative.of_node_relative().name.clone(),
                     source_sv_name,
@@ -535,9 +809,18 @@ fn create_dependencies_and_essential_data(
             None
         };
 
+        // The attribute that the elements read, which need not have the state var's name
+        let attribute_name = sv_def.return_element_dependency_instructions(1, HashMap::new())
+            .into_values()
+            .find_map(|instruction| match instruction {
+                DependencyInstruction::Attribute { attribute_name, .. } => Some(attribute_name),
+                _ => None,
+            })
+            .unwrap_or(sv_name);
+
         if let Some(attribute_for_comp) = possible_attributes {
 
-            if let Some(attribute_for_sv) = attribute_for_comp.get(sv_name) {
+            if let Some(attribute_for_sv) = attribute_for_comp.get(attribute_name) {
                 let element_dep_flags: Vec<usize> = attribute_for_sv.iter().map(|(id, _)| *id).collect();
                 element_specific_dependencies.insert(
                     (comp_name.clone(), sv_name),
@@ -689,7 +972,10 @@ fn create_all_dependencies_for_component<'a>(
 
                 let component_slice = ComponentStateSliceAllInstances(
                     component,
-                    StateVarSlice::Single(StateRef::ArrayElement(state_var_name, index)),
+                    StateVarSlice::Single(StateRef::ArrayElement(
+                        state_var_name,
+                        element_index(index, state_var_variant.inner_dimensions()),
+                    )),
                 );
                 for (instruct_name, ref dep_instruction) in element_dep_instructions.into_iter() {
                     let instruct_dependencies =
@@ -767,8 +1053,10 @@ fn create_dependencies_from_instruction(
             let source_relative = get_recursive_copy_source_component_when_exists(components, component);
             let essential_origin = EssentialDataOrigin::StateVar(state_var_slice.name());
 
-            if should_initialize_essential_data && source_relative.name == component.name {
-                // Components only create their own essential data
+            if should_initialize_essential_data && source_relative.name == component.name
+                && !essential_data_exists_for(&source_relative.name, &essential_origin, essential_data) {
+                // Components only create their own essential data,
+                // and an array's size and elements share the same essential data
 
                 let sv_def = component.definition.state_var_definitions.get(state_var_slice.name()).unwrap();
 
@@ -786,7 +1074,7 @@ fn create_dependencies_from_instruction(
                     .unwrap_or(sv_def.initial_essential_value());
 
                 let initial_data = if sv_def.is_array() {
-                    InitialEssentialData::Array(Vec::new(), initial_data)
+                    InitialEssentialData::Array(vec![initial_data.clone(); sv_def.initial_essential_size()], initial_data)
                 } else {
                     InitialEssentialData::Single(initial_data)
                 };
@@ -828,16 +1116,24 @@ fn create_dependencies_from_instruction(
             }]
         },
 
-        DependencyInstruction::Parent { state_var } => {
+        DependencyInstruction::Parent { state_var } |
+        DependencyInstruction::ParentOfType { state_var, .. } => {
 
             let parent_name = component.parent.clone().expect(&format!(
                 "Component {}:{} asks for a parent but there is none.",
                     component_slice, instruction_name
             ));
 
+            let parent_component = components.get(&parent_name).unwrap();
+            if let DependencyInstruction::ParentOfType { component_type, .. } = instruction {
+                if parent_component.definition.component_type != *component_type {
+                    return vec![];
+                }
+            }
+
             // Look up what kind of child state var it is
             // If the state var is an array, depend on the array, otherwise as normal
-            let parent_component = components.get(&parent_name).unwrap();
+            // The state var was checked by check_for_missing_dependency_state_vars
             let sv_def = parent_component.definition.state_var_definitions.get(state_var).unwrap();
             let sv_slice = if sv_def.is_array() {
                     StateVarSlice::Array(state_var)
@@ -847,7 +1143,7 @@ fn create_dependencies_from_instruction(
             let relative_instance = 
                 if component.definition.component_type == "template"
                 && parent_component.definition.component_type == "map" {
-                    RelativeInstance(1, Vec::new())
+                    RelativeInstance(sources_instancing_map(components, parent_component).len(), Vec::new())
                 } else {
                     RelativeInstance::default()
                 };
@@ -863,6 +1159,59 @@ fn create_dependencies_from_instruction(
             vec![Dependency::StateVar { states }]
         },
 
+        DependencyInstruction::Document { state_var } => {
+
+            let document = parent_chain(components, component).last().unwrap();
+
+            // The document is outside of every map that instances this component
+            let maps_above = sources_that_instance_component(components, component).len();
+            let document_ref = ComponentRefRelative::Component(NodeRel(ComponentRelative {
+                name: document.name.clone(),
+                relative_instance: RelativeInstance(maps_above, Vec::new()),
+            }));
+
+            let states = GroupStateSliceRelative(
+                ComponentGroupRelative::Single(document_ref),
+                StateVarSlice::Single(StateRef::Basic(state_var))
+            );
+            vec![Dependency::StateVar { states }]
+        },
+
+        DependencyInstruction::ComponentsOfType { component_type, state_var } => {
+
+            let document = parent_chain(components, component).last().unwrap();
+
+            descendants_of_type(components, document, component_type).into_iter()
+                .filter(|descendant| component_inside_maps(components, descendant) == 0)
+                .map(|descendant| {
+                    let states = GroupStateSliceRelative(
+                        ComponentGroupRelative::Single(ComponentRefRelative::node(descendant.name.clone())),
+                        StateVarSlice::Single(StateRef::Basic(state_var))
+                    );
+                    Dependency::StateVar { states }
+                })
+                .collect()
+        },
+
+        DependencyInstruction::AttributeComponent { attribute_name, index, state_var } => {
+
+            let Some(ObjectName::Component(comp_name)) = component_attributes
+                .get(attribute_name)
+                .and_then(|attribute| attribute.get(index))
+                .and_then(|objects| objects.first()) else {
+                return vec![];
+            };
+            if components.get(comp_name).unwrap().definition.primary_input_state_var.is_some() {
+                return vec![];
+            }
+
+            let states = GroupStateSliceRelative(
+                ComponentGroupRelative::Single(ComponentRefRelative::node(comp_name.clone())),
+                state_var.clone()
+            );
+            vec![Dependency::StateVar { states }]
+        },
+
         DependencyInstruction::Child { desired_profiles, parse_into_expression } => {
 
             enum RelevantChild<'a> {
@@ -887,6 +1236,22 @@ fn create_dependencies_from_instruction(
                 relevant_children.push(
                     RelevantChild::StateVar(Dependency::StateVar { states })
                 );
+            } else if let Some(CopySource::CollectionStateVar(ref collection, ref state_ref)) = source.copy_source {
+                let states = GroupStateSliceRelative(
+                    ComponentGroupRelative::Collection(collection.clone()),
+                    StateVarSlice::Single(state_ref.clone())
+                );
+                relevant_children.push(
+                    RelevantChild::StateVar(Dependency::StateVar { states })
+                );
+            } else if let Some(CopySource::StateVarArray(ref source_array)) = source.copy_source {
+                let states = GroupStateSliceRelative(
+                    ComponentGroupRelative::Single(source_array.0.clone()),
+                    StateVarSlice::Array(source_array.1)
+                );
+                relevant_children.push(
+                    RelevantChild::StateVar(Dependency::StateVar { states })
+                );
             } else if let Some(CopySource::DynamicElement(ref source_array, _, _)) = source.copy_source {
                 relevant_children.push(
                     RelevantChild::StateVar(Dependency::StateVarArrayDynamicElement {
@@ -912,6 +1277,12 @@ fn create_dependencies_from_instruction(
                         state_var_slice: state_var_slice.clone(),
                     })
                 );
+            } else if let Some(CopySource::MapSourcesIndex(map_sources)) = &component.copy_source {
+                relevant_children.push(
+                    RelevantChild::StateVar(Dependency::MapSourcesIndex {
+                        map_sources: map_sources.clone(),
+                    })
+                );
             }
 
 
@@ -1024,8 +1395,10 @@ fn create_dependencies_from_instruction(
 
                         let essential_origin = EssentialDataOrigin::ComponentChild(*index);
 
-                        if should_initialize_essential_data && std::ptr::eq(component, actual_parent) {
-                            // Components create their own essential data
+                        if should_initialize_essential_data && std::ptr::eq(component, actual_parent)
+                            && !essential_data_exists_for(&actual_parent.name, &essential_origin, essential_data) {
+                            // Components create their own essential data,
+                            // which is shared if several child instructions see the same string
 
                             let value = StateVarValue::String(string_value.clone());
                             create_essential_data_for(
@@ -1054,14 +1427,15 @@ fn create_dependencies_from_instruction(
 
             // log_debug!("Getting attribute {} for {}", attribute_name, component_slice);
             let state_var_name = state_var_slice.name();
-            let state_var_ref = StateRef::from_name_and_index(state_var_name, *index);
+            let state_var_ref = StateRef::from_name_and_index(state_var_name, index.clone());
             let sv_def = component.definition.state_var_definitions.get(state_var_name).unwrap();
             let essential_origin = EssentialDataOrigin::StateVar(state_var_name);
 
 
             let default_value = match sv_def {
 
-                StateVarVariant::NumberArray(_)| 
+                StateVarVariant::NumberArray(_) |
+                StateVarVariant::IntegerArray(_) |
                 StateVarVariant::Number(_) | 
                 StateVarVariant::Integer(_) => {
                     StateVarValue::MathExpr(MathExpression::new(
@@ -1087,14 +1461,24 @@ fn create_dependencies_from_instruction(
                     return vec![Dependency::StateVar { states }]
                 }
 
-                if should_initialize_essential_data {
+                // The size and elements of an array share this essential data
+                if should_initialize_essential_data
+                    && !essential_data_exists_for(&component.name, &essential_origin, essential_data) {
+
+                    let initial_data = if sv_def.is_array() {
+                        let size = sv_def.initial_essential_size();
+                        InitialEssentialData::Array(vec![default_value.clone(); size], default_value)
+                    } else {
+                        InitialEssentialData::Single(default_value)
+                    };
+
                     create_essential_data_for(
                         &component.name,
                         components,
-                        EssentialDataOrigin::StateVar(state_var_name),
-                        InitialEssentialData::Single(default_value),
+                        essential_origin.clone(),
+                        initial_data,
                         essential_data
-                    );    
+                    );
                 }
 
                 return vec![Dependency::Essential {
@@ -1116,7 +1500,10 @@ fn create_dependencies_from_instruction(
                     if matches!(sv_def, StateVarVariant::Number(_)
                         | StateVarVariant::NumberArray(_)
                         | StateVarVariant::Integer(_)
+                        | StateVarVariant::IntegerArray(_)
                         | StateVarVariant::Boolean(_)
+                        | StateVarVariant::BooleanArray(_)
+                        | StateVarVariant::MathArray(_)
                     ) {
                         StateVarValue::MathExpr(
                             MathExpression::new(obj_list)
@@ -1187,21 +1574,30 @@ fn create_dependencies_from_instruction(
                 }]
             }
 
+            // Attributes are one-dimensional
             let attribute_index = match index {
-                StateIndex::Element(i) => *i,
+                StateIndex::Element(i) => element_id(i, &[]).unwrap_or(0),
                 _ => 1,
             };
 
-            let attr_objects = attribute.get(&attribute_index)
-                .expect(&format!("attribute {} does not have index {}. Attribute: {:?}",
-                    component_slice, &attribute_index, attribute));
+            // An element past the end of the attribute only has the essential value
+            let Some(attr_objects) = attribute.get(&attribute_index) else {
+                return vec![Dependency::Essential {
+                    component: ComponentRelative::same_instance(component.name.clone()),
+                    origin: essential_origin,
+                }]
+            };
 
             let mut dependencies = Vec::new();
 
             let relevant_attr_objects = match sv_def {
                 StateVarVariant::Number(_) |
                 StateVarVariant::NumberArray(_) |
-                StateVarVariant::Integer(_) => {
+                StateVarVariant::Integer(_) |
+                StateVarVariant::IntegerArray(_) |
+                StateVarVariant::Boolean(_) |
+                StateVarVariant::BooleanArray(_) |
+                StateVarVariant::MathArray(_) => {
                     // First add an essential dependency to the expression
                     dependencies.push(Dependency::Essential {
                         component: ComponentRelative::same_instance(component.name.clone()),
@@ -1224,9 +1620,12 @@ fn create_dependencies_from_instruction(
                     },
                     ObjectName::Component(comp_name) => {
                         let comp = components.get(&comp_name).unwrap();
-                        let primary_input_sv = comp.definition.primary_input_state_var.expect(
-                            &format!("An attribute cannot depend on a non-primitive component. Try adding '.value' to the macro.")
-                        );
+
+                        // A component like a point has no single value for the attribute.
+                        // Its state vars are found with DependencyInstruction::AttributeComponent.
+                        let Some(primary_input_sv) = comp.definition.primary_input_state_var else {
+                            continue;
+                        };
 
                         let component_group_relative = ComponentGroupRelative::Single(ComponentRefRelative::node(comp_name.clone()));
                         let states = GroupStateSliceRelative(
@@ -1305,6 +1704,7 @@ fn package_string_as_state_var_value(input_string: String, state_var_variant: &S
             Ok(StateVarValue::String(input_string))
         },
 
+        StateVarVariant::BooleanArray(_) |
         StateVarVariant::Boolean(_) => {
 
             if input_string == "true" {
@@ -1316,6 +1716,7 @@ fn package_string_as_state_var_value(input_string: String, state_var_variant: &S
             }
         },
 
+        StateVarVariant::IntegerArray(_) |
         StateVarVariant::Integer(_) => {
             if let Ok(val) = evalexpr::eval_int(&input_string) {
                 Ok(StateVarValue::Integer(val))
@@ -1332,6 +1733,14 @@ fn package_string_as_state_var_value(input_string: String, state_var_variant: &S
                 Err(format!("Cannot package string '{}' as number", input_string))
             }
         },
+
+        StateVarVariant::MathArray(_) => {
+            if evalexpr::build_operator_tree(&input_string).is_ok() {
+                Ok(StateVarValue::MathExpr(MathExpression::new(&vec![ObjectName::String(input_string)])))
+            } else {
+                Err(format!("Cannot package string '{}' as math", input_string))
+            }
+        },
     }
 }
 
@@ -1374,6 +1783,62 @@ pub enum EssentialDataOrigin {
     // AttributeString(usize),
 }
 
+impl EssentialDataOrigin {
+    /// The index into this essential data for a state var.
+    /// Only the data of a state var itself can be an array.
+    fn index_for(&self, state_ref: &StateRef) -> StateIndex {
+        match self {
+            EssentialDataOrigin::StateVar(_) => state_ref.index(),
+            _ => StateIndex::Basic,
+        }
+    }
+}
+
+/// What the essential data is for, as it is saved
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum SavedEssentialDataOrigin {
+    StateVar(String),
+    ComponentChild(usize),
+}
+
+impl From<&EssentialDataOrigin> for SavedEssentialDataOrigin {
+    fn from(origin: &EssentialDataOrigin) -> Self {
+        match origin {
+            EssentialDataOrigin::StateVar(name) => Self::StateVar(name.to_string()),
+            EssentialDataOrigin::ComponentChild(index) => Self::ComponentChild(*index),
+        }
+    }
+}
+
+/// One instance of the essential data of a component, in the JSON that is saved between visits
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedEssentialState {
+    pub component_name: ComponentName,
+    pub origin: SavedEssentialDataOrigin,
+    pub instance: Instance,
+    pub value: SavedEssentialValue,
+}
+
+/// The essential data in the form that is saved, in a stable order
+pub fn saved_state(
+    essential_data: &HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>,
+) -> Vec<SavedEssentialState> {
+    let mut saved_state: Vec<SavedEssentialState> = essential_data.iter()
+        .flat_map(|(component_name, component_data)| component_data.iter()
+            .flat_map(move |(origin, essential)| essential.saved_instances().into_iter()
+                .map(move |(instance, value)| SavedEssentialState {
+                    component_name: component_name.clone(),
+                    origin: origin.into(),
+                    instance,
+                    value,
+                })))
+        .collect();
+    saved_state.sort_by(|a, b| (&a.component_name, &a.origin, &a.instance).cmp(&(&b.component_name, &b.origin, &b.instance)));
+    saved_state
+}
+
 /// A single essential state
 enum InitialEssentialData {
     Single(StateVarValue),
@@ -1543,6 +2008,11 @@ fn resolve_state_variable(
 
                     for component_ref in group_instance.group_members(core) {
 
+                        // The members of a collection of several types might not have the state var
+                        if !component_ref.component_definition(core).state_var_definitions.contains_key(component_group_sv_slice.name()) {
+                            continue;
+                        }
+
                         let comp_ref_slice = ComponentRefStateSlice(component_ref, component_group_sv_slice.clone());
                         let sv_slice = comp_ref_slice.convert_to_state_slice(core).unwrap();
 
@@ -1594,6 +2064,17 @@ fn resolve_state_variable(
                         get_dependency_values_for_state_var_slice(core, &sv_slice)
                     );
                 },
+
+                Dependency::MapSourcesIndex { map_sources } => {
+                    let (_, index) = map_sources_instance_and_index(core, &component_state.0, map_sources);
+                    values_for_this_dep.push(DependencyValue {
+                        source: DependencySource::StateVar {
+                            component_type: "number",
+                            state_var_name: "value",
+                        },
+                        value: StateVarValue::Integer(index as i64),
+                    });
+                },
                 Dependency::StateVarArrayCorrespondingElement { array_state } => {
 
                     let (component_ref_relative, sv_slice) = array_state.split_array_with_index(component_state.1.index());
@@ -1611,10 +2092,7 @@ fn resolve_state_variable(
 
                     let dependency_map = component.instance_relative_to(&core.component_nodes, &component_state.0);
 
-                    let index = match origin {
-                        EssentialDataOrigin::StateVar(_) => component_state.1.index(),
-                        _ => StateIndex::Basic,
-                    };
+                    let index = origin.index_for(&component_state.1);
 
                     let value = core.essential_data
                         .get(&dependency_map.node.name).unwrap()
@@ -1643,7 +2121,7 @@ fn resolve_state_variable(
 
                         // log_debug!("got prop index which is {}", index);
 
-                        let (component_ref_relative, sv_slice) = array_state.split_array_with_index(StateIndex::Element(index));
+                        let (component_ref_relative, sv_slice) = array_state.split_array_with_index(StateIndex::Element(vec![index]));
                         let component_ref = component_ref_relative.instance_relative_to(&core.component_nodes, &component_state.0);
 
                         let slice_variable = ComponentRefStateSlice(component_ref, sv_slice);
@@ -1691,6 +2169,10 @@ fn resolve_state_variable(
         StateVarUpdateInstruction::SetValue(new_value) => {
 
             updated_value = component_state.set_value(&core.component_states, new_value);
+        },
+        StateVarUpdateInstruction::SetNull => {
+
+            updated_value = component_state.set_value(&core.component_states, StateVarValue::Null);
         }
 
     };
@@ -1716,8 +2198,11 @@ fn resolve_slice(
             .expect("Array size should always resolve to a StateVarValue")
             .try_into().unwrap();
             
-            indices_for_size(size_value).map(|id| {
-                let element_variable = component_state_slice.clone().index(StateIndex::Element(id));
+            let inner_dimensions = component_state_slice.0.node.definition
+                .state_var_definitions.get(component_state_slice.1.name()).unwrap()
+                .inner_dimensions();
+            array_indices(size_value, inner_dimensions).map(|index| {
+                let element_variable = component_state_slice.clone().index(StateIndex::Element(index));
                 resolve_state_variable(core, &element_variable)
             }).collect()
         }
@@ -1734,6 +2219,7 @@ fn generate_update_instruction_for_state_ref(
     if component_state.1.name() == PROP_INDEX_SV {
         prop_index_determine_value(dependency_values).map(|update_instruction| match update_instruction {
             StateVarUpdateInstruction::NoChange => StateVarUpdateInstruction::NoChange,
+            StateVarUpdateInstruction::SetNull => StateVarUpdateInstruction::SetNull,
             StateVarUpdateInstruction::SetValue(num_val) => StateVarUpdateInstruction::SetValue(num_val.into()),
         })
     } else {
@@ -1741,14 +2227,16 @@ fn generate_update_instruction_for_state_ref(
         let state_var_def = &component_state.0.node.definition
             .state_var_definitions.get(component_state.1.name()).unwrap();
 
-        match component_state.1 {
+        match &component_state.1 {
             StateRef::Basic(_) => {
                 state_var_def.determine_state_var_from_dependencies(dependency_values)
             },
             StateRef::SizeOf(_) => {
                 state_var_def.determine_size_from_dependencies(dependency_values)
             },
-            StateRef::ArrayElement(_, id) => {
+            StateRef::ArrayElement(_, index) => {
+                let id = element_id(index, state_var_def.inner_dimensions())
+                    .ok_or(format!("{} is outside of the array", component_state))?;
                 let internal_id = id - 1;
                 state_var_def.determine_element_from_dependencies(internal_id, dependency_values)
             }
@@ -1874,6 +2362,12 @@ fn get_source_for_dependency(
                 state_var_name: state_var_slice.name()
             }
         },
+        Dependency::MapSourcesIndex { .. } => {
+            DependencySource::StateVar {
+                component_type: "number",
+                state_var_name: "value",
+            }
+        },
 
         Dependency::StateVarArrayDynamicElement { array_state, .. } => {
             let component_type = array_state.0
@@ -1905,11 +2399,21 @@ fn get_dependency_sources_for_state_var(
             let source = get_source_for_dependency(&core.component_nodes, &core.essential_data, &dependency);
 
             let essential_value = if let Dependency::Essential { origin, .. } = dependency {
+                // An element past the end of the essential data has no value
                 let data = core.essential_data
                     .get(&component_state.0.node.name).unwrap()
                     .get(origin).unwrap();
-                let value = data.get_value(state_ref.index(), map).unwrap();
-                Some(value)
+                data.get_value(origin.index_for(state_ref), map)
+
+            } else if let Dependency::StateVar { states: GroupStateSliceRelative(
+                ComponentGroupRelative::Single(comp_ref),
+                StateVarSlice::Single(dep_state_ref),
+            ) } = dependency {
+                // the current value, so that a definition can leave this dependency as it is
+                let component_ref = comp_ref.replace_instance(&core.component_nodes, map.clone());
+                ComponentRefStateSlice(component_ref, StateVarSlice::Single(dep_state_ref.clone()))
+                    .convert_to_state_slice(core)
+                    .and_then(|sv_slice| resolve_slice(core, sv_slice).into_iter().next().flatten())
 
             } else {
                 None
@@ -1941,7 +2445,7 @@ fn state_vars_for_undetermined_children<'a>(
         match member_child {
             ObjectRef::Component(child_ref_instance) => {
 
-                let child_def = child_ref_instance.component_definition();
+                let child_def = child_ref_instance.component_definition(core);
 
                 match  &child_def.replacement_components {
                     Some(ReplacementComponents::Children) => {
@@ -1980,13 +2484,30 @@ fn map_sources_dependency_member<'a>(
     component_instance: &ComponentInstance,
     sources: &'a ComponentRelative,
 ) -> ComponentRef<'a> {
+    let (sources_instance, collection_index) = map_sources_instance_and_index(core, component_instance, sources);
+    ComponentRef::CollectionMember(sources_instance, collection_index)
+}
+
+/// The instance of the sources, and the index of its member that the component's instance uses
+fn map_sources_instance_and_index<'a>(
+    core: &'a DoenetCore,
+    component_instance: &ComponentInstance,
+    sources: &'a ComponentRelative,
+) -> (ComponentInstance<'a>, usize) {
+    let sources_node = core.component_nodes.get(&sources.name).unwrap();
     let sources_instance = sources.relative_instance.relative_to(&component_instance.instance);
-    let collection_index = *component_instance.instance.get(sources_instance.len()).unwrap();
-    let component_instance = ComponentInstance {
-        node: &core.component_nodes.get(&sources.name).unwrap(),
+
+    // With several sources in combination, each has its own index after the instance of the map
+    let map = core.component_nodes.get(sources_node.parent.as_ref().unwrap()).unwrap();
+    let dimension = sources_instancing_map(&core.component_nodes, map).iter()
+        .position(|s| s.name == sources_node.name)
+        .unwrap_or(0);
+    let collection_index = *component_instance.instance.get(sources_instance.len() + dimension).unwrap();
+    let sources_instance = ComponentInstance {
+        node: sources_node,
         instance: sources_instance,
     };
-    ComponentRef::CollectionMember(component_instance, collection_index)
+    (sources_instance, collection_index)
 }
 
 
@@ -2036,15 +2557,18 @@ fn mark_stale_essential_datum_dependencies(
 
     let my_dependencies = core.dependencies.iter().filter_map( |(key, deps) | {
         if deps.contains(&search_dep) {
-            let state_ref_option = match &key.1 {
-                StateVarSlice::Single(s) => Some(s.clone()),
+            let state_slice_option = match &key.1 {
+                StateVarSlice::Single(s) => Some(StateVarSlice::Single(s.clone())),
+                // data that is not an array, such as a string child, is used by every element
+                StateVarSlice::Array(_) if matches!(state_index, StateIndex::Basic) => Some(key.1.clone()),
                 StateVarSlice::Array(_) => key.1.clone().specify_index(state_index.clone())
+                    .map(StateVarSlice::Single),
             };
-            state_ref_option.map (|state_var_ref|
+            state_slice_option.map (|state_var_slice|
                 ComponentInstancesStateSlice(ComponentInstances {
                     node: core.component_nodes.get(&key.0).unwrap(),
                     instance_group: map.clone(),
-                }, StateVarSlice::Single(state_var_ref))
+                }, state_var_slice)
             )
         } else {
             None
@@ -2121,15 +2645,23 @@ fn get_state_variables_depending_on_me<'a>(
                 },
 
                 Dependency::MapSources { map_sources, state_var_slice } => {
-                    if sv_component.name == *map_sources.name
+                    if collection_may_contain(core.collection_members.get(&map_sources.name).unwrap(), sv_component)
                     && slice_depends_on_slice(state_var_slice, sv_slice) {
+                        // A member can be deeper inside maps than the sources, like one collected
+                        // from another map, and then any instance of the sources may use it
+                        let sources_node = core.component_nodes.get(&map_sources.name).unwrap();
+                        let deeper = component_inside_maps(&core.component_nodes, sv_component)
+                            .saturating_sub(component_inside_maps(&core.component_nodes, sources_node));
                         add_dependent = Some((
-                            RelativeInstance::default(),
+                            RelativeInstance(0, vec![0; deeper]),
                             &dependency_key.1,
                         ));
                     }
                 },
 
+                // The index of an instance never changes
+                Dependency::MapSourcesIndex { .. } => {},
+
                 Dependency::StateVarArrayCorrespondingElement { array_state } => {
                     if group_includes_component(&core.collection_members, &ComponentGroupRelative::Single(array_state.0.clone()), sv_component)
                     && array_state.1 == sv_slice.name() {
@@ -2274,82 +2806,147 @@ fn generate_render_tree(core: &DoenetCore) -> serde_json::Value {
         child_of_copy: None
     };
     let mut json_obj: Vec<serde_json::Value> = vec![];
+    let mut instance_names = HashSet::new();
 
     log!("===== Render tree ======");
-    generate_render_tree_internal(core, root_comp_rendered, &mut json_obj);
+    generate_render_tree_internal(core, root_comp_rendered, &mut json_obj, &mut instance_names);
+
+    // A component that is rendered again later is sent in full
+    core.rendered_instance_entries.borrow_mut()
+        .retain(|name, _| instance_names.contains(name));
 
     serde_json::Value::Array(json_obj)
 }
 
-fn generate_render_tree_internal(
+/// Mark stale the state of map instances that no longer exist because the
+/// sources of their map shrank, along with everything depending on them.
+/// Essential data is not touched, so the values return with the instance.
+fn release_removed_map_instances(
     core: &DoenetCore,
-    component: RenderedComponent,
-    json_obj: &mut Vec<serde_json::Value>,
+    sources_sizes_before_update: HashMap<(ComponentName, Instance), Vec<usize>>,
 ) {
-    use serde_json::{Map, Value, json};
-
-    log_debug!("generating render tree for {}", component);
+    let mut maps: Vec<((ComponentName, Instance), Vec<usize>)> = sources_sizes_before_update
+        .into_iter()
+        .collect();
+    // Outer maps first, so that the maps inside their removed instances are forgotten
+    maps.sort_by_key(|((_, instance), _)| instance.len());
 
-    let component_definition = component.component_ref
-        .component_definition();
+    for ((map_name, map_instance), sizes_before_update) in maps {
+        if !core.map_sources_sizes.borrow().contains_key(&(map_name.clone(), map_instance.clone())) {
+            continue;
+        }
+        let map = ComponentInstance {
+            node: core.component_nodes.get(&map_name).unwrap(),
+            instance: map_instance,
+        };
 
-    let renderered_state_vars = component_definition
-        .state_var_definitions
-        .into_iter()
-        .filter_map(|(k, v)| {
-            v.for_renderer().then(|| match v.is_array() {
-                true => StateVarSlice::Array(k),
-                false => StateVarSlice::Single(StateRef::Basic(k)),
-            })
-        });
+        for c in core.collection_members.get(&map_name).unwrap() {
+            let CollectionMembers::InstanceBySources { template, sources, behavior } = c else {
+                continue;
+            };
+            let sizes = sources_sizes(core, &map, sources);
+            if sizes == sizes_before_update {
+                continue;
+            }
 
-    let state_var_aliases = match &component_definition.renderer_type {
-        RendererType::Special { state_var_aliases, .. } => state_var_aliases.clone(),
-        RendererType::Myself => HashMap::new(),
-    };
+            let indices_after_update = map_instance_indices(&sizes, *behavior);
+            let template_node = core.component_nodes.get(&template.name).unwrap();
+            for indices in map_instance_indices(&sizes_before_update, *behavior) {
+                if indices_after_update.contains(&indices) {
+                    continue;
+                }
+                let mut instance = template.relative_instance.relative_to(&map.instance);
+                instance.extend(indices);
 
-    let mut state_values = serde_json::Map::new();
-    for state_var_slice in renderered_state_vars {
-        let comp_ref_slice = ComponentRefStateSlice(component.component_ref.clone(), state_var_slice);
-        let sv_slice = comp_ref_slice.convert_to_state_slice(core).unwrap();
+                log_debug!("Releasing instance {:?} of {}", instance, template.name);
+                release_map_instance(core, template_node, &instance);
 
-        let sv_renderer_name = state_var_aliases
-            .get(&sv_slice.1.name())
-            .map(|x| *x)
-            .unwrap_or(sv_slice.1.name())
-            .to_string();
+                // An instance that returns later is sent in full again
+                core.rendered_instance_entries.borrow_mut()
+                    .retain(|_, (rendered_instance, _)| !rendered_instance.starts_with(&instance));
+            }
+        }
+    }
+}
 
-        let values = resolve_slice(core, sv_slice.clone());
+/// The indices that each instance of a map adds to the Instance
+fn map_instance_indices(sizes: &[usize], behavior: SourcesBehavior) -> HashSet<Vec<usize>> {
+    let size = match behavior {
+        SourcesBehavior::Combination => sizes.iter().product(),
+        SourcesBehavior::Parallel => sizes.iter().copied().min().unwrap_or(0),
+    };
+    (1..=size)
+        .map(|index| instance_indices_by_sources(sizes, behavior, index))
+        .collect()
+}
 
-        let mut json_value = match sv_slice.1 {
-            StateVarSlice::Array(_) => json!(values),
-            StateVarSlice::Single(_) => json!(values.first().unwrap()),
+fn release_map_instance(
+    core: &DoenetCore,
+    node: &ComponentNode,
+    instance_group: &InstanceGroup,
+) {
+    for (&state_var_name, state) in core.component_states.get(&node.name).unwrap() {
+        let slices: Vec<StateVarSlice> = match state {
+            StateForStateVar::Single(_) => vec![
+                StateVarSlice::Single(StateRef::Basic(state_var_name))
+            ],
+            StateForStateVar::Array { inner_dimensions, .. } => std::iter::once(
+                StateVarSlice::Single(StateRef::SizeOf(state_var_name))
+            ).chain(
+                (1..=state.elements_len_in_instance_group(instance_group)).map(|id|
+                    StateVarSlice::Single(StateRef::ArrayElement(state_var_name, element_index(id, inner_dimensions))))
+            ).collect(),
         };
 
-        // hardcoded exceptions
-        if sv_renderer_name == "numericalPoints"
-        && matches!(sv_slice.1, StateVarSlice::Array(_)) {
-            let array_2d =
-                [[values.get(0).unwrap(), values.get(1).unwrap()],
-                [values.get(2).unwrap(), values.get(3).unwrap()]];
-            json_value = json!(array_2d)
-        }
-        if sv_renderer_name == "selectedStyle" {
-            let string: String = values.get(0).unwrap().clone().unwrap().try_into().unwrap();
-            json_value = serde_json::from_str(&string).unwrap();
+        for slice in slices {
+            mark_stale_state_var_and_dependencies(core, &ComponentInstancesStateSlice(
+                ComponentInstances { node, instance_group: instance_group.clone() },
+                slice,
+            ));
         }
 
-        state_values.insert(sv_renderer_name, json_value);
+        state.release_instance_group(instance_group);
     }
 
-    let name_to_render = name_rendered_component(&component, component_definition.component_type);
+    // The instances of a map inside the released instance are released with it
+    core.map_sources_sizes.borrow_mut()
+        .retain(|(map_name, instance), _| map_name != &node.name || !instance.starts_with(instance_group));
+
+    for child in node.children.iter().filter_map(|c| c.as_component()) {
+        release_map_instance(core, core.component_nodes.get(child).unwrap(), instance_group);
+    }
+}
+
+fn generate_render_tree_internal(
+    core: &DoenetCore,
+    component: RenderedComponent,
+    json_obj: &mut Vec<serde_json::Value>,
+    instance_names: &mut HashSet<String>,
+) {
+    use serde_json::{Map, Value, json};
+
+    log_debug!("generating render tree for {}", component);
+
+    let component_definition = component.component_ref
+        .component_definition(core);
+
+    let instance = component.component_ref.clone()
+        .convert_to_node_instance(core)
+        .map(|x| x.instance)
+        .unwrap_or(component.component_ref.instance());
+
+    let name_to_render = name_rendered_component(core, &component, component_definition.component_type);
+
+    let state_values = rendered_state_values(core, &component, &name_to_render);
 
     let mut children_instructions = Vec::new();
     if component_definition.should_render_children {
         for (child, actual_parent) in get_child_refs_including_copy_and_members(core, component.component_ref.clone()) {
             match child {
                 ObjectRef::String(string) => {
-                    children_instructions.push(json!(string));
+                    if component_definition.should_render_string_children {
+                        children_instructions.push(json!(string));
+                    }
                 },
                 ObjectRef::Component(comp_ref_instance) => {
                     let child_component = RenderedComponent {
@@ -2360,9 +2957,13 @@ fn generate_render_tree_internal(
                     };
 
                     let child_definition = child_component.component_ref
-                        .component_definition();
+                        .component_definition(core);
+
+                    if matches!(child_definition.renderer_type, RendererType::DoNotRender) {
+                        continue;
+                    }
 
-                    let child_name = name_rendered_component(&child_component, child_definition.component_type);
+                    let child_name = name_rendered_component(core, &child_component, child_definition.component_type);
 
                     let action_component_name = child_component.component_ref.clone()
                         .convert_to_node_instance(core)
@@ -2380,6 +2981,7 @@ fn generate_render_tree_internal(
                     let renderer_type = match &child_definition.renderer_type {
                         RendererType::Special{ component_type, .. } => *component_type,
                         RendererType::Myself => child_definition.component_type,
+                        RendererType::DoNotRender => unreachable!(),
                     };
 
                     children_instructions.push(json!({
@@ -2390,43 +2992,261 @@ fn generate_render_tree_internal(
                         "rendererType": renderer_type,
                     }));
 
-                    generate_render_tree_internal(core, child_component, json_obj); 
+                    generate_render_tree_internal(core, child_component, json_obj, instance_names);
                 },
             }
         }
     }
 
-    json_obj.push(json!({
+    let entry = json!({
         "componentName": name_to_render,
         "stateValues": serde_json::Value::Object(state_values),
         "childrenInstructions": json!(children_instructions),
-    }));
+    });
+
+    if !instance.is_empty() {
+        instance_names.insert(name_to_render.clone());
+        let mut rendered_entries = core.rendered_instance_entries.borrow_mut();
+        if rendered_entries.get(&name_to_render).is_some_and(|(_, rendered_entry)| rendered_entry == &entry) {
+            return;
+        }
+        rendered_entries.insert(name_to_render, (instance, entry.clone()));
+    }
 
+    json_obj.push(entry);
 }
 
-fn name_rendered_component(component: &RenderedComponent, component_type: &str) -> String {
+/// The state values sent to the renderer of a component.
+/// During a drag, a hidden component keeps the values it last rendered
+/// so that nothing it depends on has to be recomputed.
+fn rendered_state_values(
+    core: &DoenetCore,
+    component: &RenderedComponent,
+    name_to_render: &str,
+) -> serde_json::Map<String, serde_json::Value> {
+    use serde_json::{Map, Value, json};
+
+    let component_definition = component.component_ref
+        .component_definition(core);
+
+    if core.action_history.borrow().drag_in_progress()
+        && component_definition.state_var_definitions.contains_key("hidden") {
+
+        let hidden = resolve_rendered_slice(core, &component.component_ref, &StateVarSlice::Single(StateRef::Basic("hidden")));
+        if let Some(Some(StateVarValue::Boolean(true))) = hidden.first() {
+            if let Some(state_values) = core.rendered_state_values.borrow().get(name_to_render) {
+                return state_values.clone();
+            }
+        }
+    }
+
+    let renderered_state_vars = component_definition
+        .state_var_definitions
+        .into_iter()
+        .filter_map(|(k, v)| {
+            v.for_renderer().then(|| match v.is_array() {
+                true => StateVarSlice::Array(k),
+                false => StateVarSlice::Single(StateRef::Basic(k)),
+            })
+        });
+
+    let state_var_aliases = match &component_definition.renderer_type {
+        RendererType::Special { state_var_aliases, .. } => state_var_aliases.clone(),
+        RendererType::Myself | RendererType::DoNotRender => HashMap::new(),
+    };
+
+    let mut state_values = serde_json::Map::new();
+    for state_var_slice in renderered_state_vars {
+        let sv_renderer_name = state_var_aliases
+            .get(&state_var_slice.name())
+            .copied()
+            .unwrap_or(state_var_slice.name())
+            .to_string();
+
+        let values = resolve_rendered_slice(core, &component.component_ref, &state_var_slice);
+
+        let sv_def = component_definition.state_var_definitions.get(state_var_slice.name()).unwrap();
+        let json_value = match state_var_slice {
+            StateVarSlice::Array(_) => nest_array_values(&values, sv_def.inner_dimensions()),
+            StateVarSlice::Single(_) => json!(values.first().unwrap()),
+        };
+
+        state_values.insert(sv_renderer_name, sv_def.reshape_for_renderer(json_value));
+    }
+
+    for (object_name, state_var_names) in &component_definition.renderer_objects {
+        let mut object = Map::new();
+        for state_var_name in state_var_names {
+            let comp_ref_slice = ComponentRefStateSlice(
+                component.component_ref.clone(),
+                StateVarSlice::Single(StateRef::Basic(state_var_name)),
+            );
+            let sv_slice = comp_ref_slice.convert_to_state_slice(core).unwrap();
+            let values = resolve_slice(core, sv_slice);
+
+            let sv_def = component_definition.state_var_definitions.get(state_var_name).unwrap();
+            object.insert(state_var_name.to_string(), sv_def.reshape_for_renderer(json!(values.first().unwrap())));
+        }
+        state_values.insert(object_name.to_string(), Value::Object(object));
+    }
+
+    core.rendered_state_values.borrow_mut().insert(name_to_render.to_string(), state_values.clone());
+
+    state_values
+}
+
+/// A batch member's array can be part of a larger array of the batch's component,
+/// so then it is resolved element by element.
+fn resolve_rendered_slice(
+    core: &DoenetCore,
+    component_ref: &ComponentRef,
+    state_var_slice: &StateVarSlice,
+) -> Vec<Option<StateVarValue>> {
+    let convert = |slice: StateVarSlice| ComponentRefStateSlice(component_ref.clone(), slice)
+        .convert_to_state_slice(core);
+
+    if let Some(sv_slice) = convert(state_var_slice.clone()) {
+        return resolve_slice(core, sv_slice)
+    }
+
+    let name = state_var_slice.name();
+    let size_slice = convert(StateVarSlice::Single(StateRef::SizeOf(name)))
+        .unwrap_or_else(|| panic!("{:?} has no state var {} to render", component_ref, name));
+    let size: usize = resolve_slice(core, size_slice)
+        .first().unwrap().clone()
+        .expect("Array size should always resolve to a StateVarValue")
+        .try_into().unwrap();
+
+    let inner_dimensions = component_ref.component_definition(core)
+        .state_var_definitions.get(name).unwrap()
+        .inner_dimensions();
+    array_indices(size, inner_dimensions).map(|index| {
+        let element_slice = convert(StateVarSlice::Single(StateRef::ArrayElement(name, index))).unwrap();
+        resolve_slice(core, element_slice).first().unwrap().clone()
+    }).collect()
+}
+
+/// The values of an array, which are in row-major order, nested with one level for each dimension
+fn nest_array_values(values: &[Option<StateVarValue>], inner_dimensions: &[usize]) -> serde_json::Value {
+    use serde_json::{Value, json};
+
+    match inner_dimensions.split_first() {
+        None => json!(values),
+        Some((_, rest)) => {
+            let row_len = inner_dimensions.iter().product();
+            Value::Array(values.chunks(row_len).map(|row| nest_array_values(row, rest)).collect())
+        },
+    }
+}
+
+fn name_rendered_component(core: &DoenetCore, component: &RenderedComponent, component_type: &str) -> String {
     let name_to_render = match &component.component_ref {
         ComponentRef::CollectionMember(n, i) |
         ComponentRef::Component(BatchMember(n, _, i)) =>
-            format!("__{}_from_({}[{}])", component_type, n.node.name, *i),
+            parse_json::assigned_name_of_member(&n.node.name, &n.node.static_attributes, *i)
+                .unwrap_or(format!("__{}_from_({}[{}])", component_type, n.node.name, *i)),
         ComponentRef::Component(Node(n)) => n.node.name.clone(),
     };
-    let name_to_render = match &component.child_of_copy {
-        Some(copy_name) => format!("__cp:{}({})", name_to_render, copy_name),
-        None => name_to_render,
+    let name_in_copy = match &component.child_of_copy {
+        Some(ComponentRef::Component(Node(copy))) =>
+            name_in_copy_namespace(&core.component_nodes, copy.node, &name_to_render),
+        _ => None,
+    };
+    let name_to_render = match (&component.child_of_copy, name_in_copy) {
+        (_, Some(name_in_copy)) => name_in_copy,
+        (Some(copy_name), None) => format!("__cp:{}({})", name_to_render, copy_name),
+        (None, None) => name_to_render,
     };
-    let name_to_render = if component.component_ref.instance().is_empty() {
+    let instance = component.component_ref.instance();
+    let name_to_render = if instance.is_empty() {
             name_to_render
         } else {
-            format!("__{}_map{:?}", name_to_render, component.component_ref.instance())
+            name_in_assigned_map_instances(&core.component_nodes, &name_to_render, &instance)
+                .unwrap_or(format!("__{}_map{:?}", name_to_render, instance))
         };
     name_to_render
 }
 
+/// Inside map instances given names with assignNames, a component is named like
+/// `/a/x` for the child `x` of the template in the instance named `a`.
+fn name_in_assigned_map_instances(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    name: &ComponentName,
+    instance: &Instance,
+) -> Option<ComponentName> {
+
+    let mut name = name.clone();
+    let mut ancestor = component_nodes.get(&name)?.parent.clone();
+
+    // The last index of the instance belongs to the innermost map
+    for &index in instance.iter().rev() {
+        let (template, map) = loop {
+            let node = component_nodes.get(ancestor.as_ref()?)?;
+            let parent = node.parent.as_ref().and_then(|p| component_nodes.get(p));
+            ancestor = node.parent.clone();
+            if let Some(map) = parent.filter(|p| p.definition.component_type == "map") {
+                if node.definition.component_type == "template" {
+                    break (node, map);
+                }
+            }
+        };
+        // Instances named by assignNames have just one index
+        if sources_instancing_map(component_nodes, map).len() != 1 {
+            return None;
+        }
+        let template_namespace = parse_json::namespace_for_children(&template.name, &template.static_attributes);
+        let relative_name = name.strip_prefix(&template_namespace)?;
+        let instance_name = parse_json::assigned_name_of_member(&map.name, &map.static_attributes, index)?;
+
+        name = format!("{}/{}", instance_name, relative_name);
+    }
+    Some(name)
+}
+
+/// The namespace of the copied children, if the copy has `newNamespace`
+/// and so renames them into its own namespace.
+fn namespace_renamed_by_copy(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    copy: &ComponentNode,
+) -> Option<String> {
+    match copy.static_attributes.get("newNamespace") {
+        Some(new_namespace) if new_namespace != "false" => (),
+        _ => return None,
+    }
+    let Some(CopySource::Component(ComponentRefRelative::Component(NodeRel(source)))) = &copy.copy_source else {
+        return None;
+    };
+    let source = component_nodes.get(&source.name)?;
+    Some(parse_json::namespace_for_children(&source.name, &source.static_attributes))
+}
 
+/// Inside a copy with `newNamespace`, a copied component is named relative to the copy
+/// the same way it is named relative to the copy source, like `/sec2/x` for `/sec/x`.
+fn name_in_copy_namespace(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    copy: &ComponentNode,
+    copied_name: &str,
+) -> Option<ComponentName> {
+    let source_namespace = namespace_renamed_by_copy(component_nodes, copy)?;
+    copied_name.strip_prefix(&source_namespace)
+        .map(|relative_name| format!("{}/{}", copy.name, relative_name))
+}
 
+/// The inverse of `name_in_copy_namespace`: `/sec/x` for `/sec2/x`.
+fn name_outside_copy_namespace(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    name: &str,
+) -> Option<ComponentName> {
+    name.match_indices('/').rev().find_map(|(slash, _)| {
+        let copy = component_nodes.get(&name[..slash])?;
+        let source_namespace = namespace_renamed_by_copy(component_nodes, copy)?;
+        Some(format!("{}{}", source_namespace, &name[slash + 1..]))
+    })
+}
 
-#[derive(Debug)]
+
+
+#[derive(Debug, Clone)]
 pub struct Action {
     pub component_name: ComponentName,
     pub action_name: String,
@@ -2434,6 +3254,51 @@ pub struct Action {
     /// The keys are not state variable names.
     /// They are whatever name the renderer calls the new value.
     pub args: HashMap<String, Vec<StateVarValue>>,
+
+    /// An intermediate step, like a position in the middle of a drag.
+    /// State and renderers update, but nothing is saved, added to the undo history
+    /// or recorded until a non-transient action arrives.
+    pub transient: bool,
+}
+
+/// The number of recorded actions and undo states kept. Older ones are dropped first.
+pub const MAX_ACTION_HISTORY: usize = 100;
+
+#[derive(Debug, Default)]
+pub struct ActionHistory {
+    /// Committed actions in the order they arrived, for event recording.
+    pub recorded_actions: VecDeque<Action>,
+
+    /// The essential data from before each committed action, most recent last.
+    /// A drag is a single entry, taken before its first transient action.
+    pub undo_states: VecDeque<HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>>,
+
+    /// The essential data from before the transient actions that have not been committed.
+    /// Only present while a drag is in progress.
+    pub state_before_transient_actions: Option<HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>>,
+
+    /// Whether a committed action has changed the essential data since it was last saved.
+    pub unsaved_changes: bool,
+}
+
+impl ActionHistory {
+    pub fn drag_in_progress(&self) -> bool {
+        self.state_before_transient_actions.is_some()
+    }
+
+    fn push_undo_state(&mut self, state: HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>) {
+        if self.undo_states.len() == MAX_ACTION_HISTORY {
+            self.undo_states.pop_front();
+        }
+        self.undo_states.push_back(state);
+    }
+
+    fn record_action(&mut self, action: Action) {
+        if self.recorded_actions.len() == MAX_ACTION_HISTORY {
+            self.recorded_actions.pop_front();
+        }
+        self.recorded_actions.push_back(action);
+    }
 }
 
 /// Internal structure used to track changes
@@ -2445,8 +3310,13 @@ enum UpdateRequest<'a> {
 
 pub fn handle_action_from_json(core: &DoenetCore, action: &str) -> String {
 
-    let (action, action_id) = parse_json::parse_action_from_json(action)
-        .expect(&format!("Error parsing json action: {}", action));
+    let (action, action_id) = match parse_json::parse_action_from_json(action) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            log!("Ignoring action {} that could not be parsed: {}", action, err);
+            return String::new();
+        },
+    };
 
     handle_action(core, action);
 
@@ -2457,6 +3327,11 @@ pub fn handle_action(core: &DoenetCore, action: Action) {
 
     log_debug!("Handling action {:#?}", action);
 
+    if core.flags.read_only {
+        log_debug!("Rejected action {} because the core is read only", action.action_name);
+        return;
+    }
+
     let component_instance  = ComponentInstance::dealias(&core.component_nodes, &action.component_name);
 
     let component = component_instance.node;
@@ -2468,10 +3343,34 @@ pub fn handle_action(core: &DoenetCore, action: Action) {
 
     let state_vars_to_update = (component.definition.on_action)(
         &action.action_name,
-        action.args,
+        action.args.clone(),
         &state_var_resolver,
     );
 
+    let mut history = core.action_history.borrow_mut();
+    let changes_state = !state_vars_to_update.is_empty() || history.drag_in_progress();
+
+    if action.transient {
+        if history.state_before_transient_actions.is_none() {
+            history.state_before_transient_actions = Some(core.essential_data.clone());
+        }
+    } else if changes_state {
+        let state_before_action = history.state_before_transient_actions.take()
+            .unwrap_or_else(|| core.essential_data.clone());
+        history.push_undo_state(state_before_action);
+        history.unsaved_changes = true;
+    }
+    if !action.transient && changes_state {
+        history.record_action(action);
+    }
+    drop(history);
+
+    if state_vars_to_update.is_empty() {
+        return;
+    }
+
+    let sources_sizes_before_update = core.map_sources_sizes.borrow().clone();
+
     for (state_var_ref, requested_value) in state_vars_to_update {
 
         let component_state = ComponentState(component_instance.clone(), state_var_ref.clone());
@@ -2479,10 +3378,70 @@ pub fn handle_action(core: &DoenetCore, action: Action) {
         process_update_request(core, &request);
     }
 
+    release_removed_map_instances(core, sources_sizes_before_update);
+
     // log_json!("Component tree after action", utils::json_components(&core.component_nodes, &core.component_states));
 }
 
 
+/// The essential data to save, if a committed action has changed it since the last save.
+/// Nothing is saved in the middle of a drag, or when the flags do not allow saving state.
+pub fn take_essential_data_to_save(core: &DoenetCore)
+    -> Option<HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>> {
+
+    let mut history = core.action_history.borrow_mut();
+    if !core.flags.allow_save_state || !history.unsaved_changes || history.drag_in_progress() {
+        return None;
+    }
+    history.unsaved_changes = false;
+    Some(core.essential_data.clone())
+}
+
+/// The essential data from before the most recent committed action.
+/// Reload the core with it to undo that action.
+pub fn pop_undo_state(core: &DoenetCore)
+    -> Option<HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>> {
+
+    let mut history = core.action_history.borrow_mut();
+    if history.drag_in_progress() {
+        return None;
+    }
+    history.undo_states.pop_back()
+}
+
+/// Undo the most recent committed action by creating the core again from the program
+/// with the essential data from before it. The rest of the action history is kept.
+/// False when there is nothing to undo.
+pub fn undo(core: &mut DoenetCore, program: &str) -> bool {
+
+    let Some(essential_data) = pop_undo_state(core) else {
+        return false;
+    };
+    let (undone_core, _) = create_doenet_core(program, core.flags.clone(), Some(essential_data))
+        .expect("The program already created this core");
+
+    undone_core.action_history.swap(&core.action_history);
+    undone_core.action_history.borrow_mut().unsaved_changes = true;
+    *core = undone_core;
+    true
+}
+
+
+/// Allow or prevent every <solution> in the document from being revealed.
+/// Solutions that are already open stay open.
+pub fn set_solution_display_permission(core: &DoenetCore, allowed: bool) {
+
+    let document = ComponentInstance::dealias(&core.component_nodes, &core.root_component_name);
+
+    // The granted permission is saved, so it survives the core being created again
+    for state_var in ["canRevealSolutions", "solutionPermissionGranted"] {
+        let component_state = ComponentState(document.clone(), StateRef::Basic(state_var));
+        let request = UpdateRequest::SetStateVar(component_state, StateVarValue::Boolean(allowed));
+        process_update_request(core, &request);
+    }
+}
+
+
 /// Convert the results of `request_dependencies_to_update_value`
 /// into UpdateRequest struct.
 fn convert_dependency_values_to_update_request<'a>(
@@ -2532,7 +3491,7 @@ fn convert_dependency_values_to_update_request<'a>(
                 Dependency::Essential { component, origin } => {
                     let component_instance = component.instance_relative_to(&core.component_nodes, &component_state.0);
                     update_requests.push(UpdateRequest::SetEssentialValue(
-                        EssentialState(component_instance, origin.clone(), state_var.index()),
+                        EssentialState(component_instance, origin.clone(), origin.index_for(state_var)),
                         request.value.clone(),
                     ))
                 },
@@ -2561,6 +3520,18 @@ fn convert_dependency_values_to_update_request<'a>(
                         }
                     }
                 },
+                Dependency::StateVarArrayCorrespondingElement { array_state } => {
+                    let (component_ref_relative, sv_slice) = array_state.split_array_with_index(state_var.index());
+                    let component_ref = component_ref_relative.instance_relative_to(&core.component_nodes, &component_state.0);
+
+                    let comp_ref_slice = ComponentRefStateSlice(component_ref, sv_slice);
+                    if let Some(sv_slice) = comp_ref_slice.convert_to_state_slice(core) {
+                        if let StateVarSlice::Single(state_var_ref) = sv_slice.1 {
+                            let component_state = ComponentState(sv_slice.0, state_var_ref);
+                            update_requests.push(UpdateRequest::SetStateVar(component_state, request.value.clone()))
+                        }
+                    }
+                },
                 _ => (),
             }
         }
@@ -2690,6 +3661,32 @@ fn state_var_is_shadowing<'a>(component_state: &'a ComponentStateAllInstances)
             panic!("{} component type doesn't have a primary input state var", component.definition.component_type);
         }
 
+    } else if let Some(CopySource::StateVarArray(ref source_array)) = component.copy_source {
+        // each item of a list shadows the same element of the array
+        match state_var {
+            StateRef::ArrayElement(name, index) if component.definition.primary_input_state_var == Some(name) => {
+                Some(ComponentRefStateSliceRelative::new(
+                    source_array.0.clone(),
+                    StateVarSlice::Single(StateRef::ArrayElement(source_array.1, index.clone()))
+                ))
+            },
+            _ => None,
+        }
+
+    } else if let Some(CopySource::CollectionStateVar(ref collection, ref state_ref)) = component.copy_source {
+        // each item of a list shadows the state var of the same member
+        match state_var {
+            StateRef::ArrayElement(name, index) if component.definition.primary_input_state_var == Some(name) => {
+                match index.as_slice() {
+                    [i] => Some(ComponentRefStateSliceRelative::new(
+                        ComponentRefRelative::CollectionMember(collection.clone(), *i),
+                        StateVarSlice::Single(state_ref.clone())
+                    )),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
 
     } else {
         None
@@ -2811,11 +3808,14 @@ impl<'a> ComponentGenerated<'a> {
 }
 
 impl<'a> ComponentRef<'a> {
-    fn component_definition(&self) -> &'static ComponentDefinition {
+    fn component_definition(&self, core: &DoenetCore) -> &'static ComponentDefinition {
         let node = self.of_node().node;
         match self {
-            ComponentRef::CollectionMember(_, _) =>
-                (node.definition.unwrap_collection_def().member_definition)(&node.static_attributes),
+            // A collection can have members of several types
+            ComponentRef::CollectionMember(n, i) => match nth_collection_member(core, n, *i) {
+                Some(member) => ComponentRef::Component(member).component_definition(core),
+                None => (node.definition.unwrap_collection_def().member_definition)(&node.static_attributes),
+            },
             ComponentRef::Component(BatchMember(_, n, _)) =>
                 node.definition.unwrap_batch_def(n).member_definition,
             ComponentRef::Component(Node(_)) => node.definition,
@@ -2893,8 +3893,13 @@ impl ComponentInstance<'_> {
         let chars: Vec<char> = alias.chars().collect();
 
         if chars[0] != '[' {
+            // Renderers name components inside a copy with newNamespace by their own path
+            let node = component_nodes.get(alias).or_else(||
+                name_outside_copy_namespace(component_nodes, alias)
+                    .and_then(|name| component_nodes.get(&name))
+            );
             return ComponentInstance {
-                node: component_nodes.get(alias).unwrap(),
+                node: node.unwrap(),
                 instance: Instance::default(),
             };
         }
@@ -3111,10 +4116,21 @@ fn collection_size(
     component_instance: &ComponentInstance,
 ) -> usize {
 
-    core.collection_members.get(&component_instance.node.name).unwrap()
+    let size = core.collection_members.get(&component_instance.node.name).unwrap()
         .iter()
         .map(|c| collection_members_size(core, &component_instance, c))
-        .sum()
+        .sum();
+    match collection_max_number(component_instance.node) {
+        Some(max_number) => std::cmp::min(size, max_number),
+        None => size,
+    }
+}
+
+/// The most members a collection can have, given by its maxNumber static attribute
+fn collection_max_number(component: &ComponentNode) -> Option<usize> {
+    component.static_attributes.get("maxNumber")
+        .and_then(|max_number| max_number.trim().parse().ok())
+        .and_then(convert_float_to_usize)
 }
 
 fn collection_members_size(
@@ -3132,7 +4148,49 @@ fn collection_members_size(
                 _ => 0,
             }
         },
-        CollectionMembers::InstanceBySources { sources, .. } => collection_size(core, &sources.instance_relative_to(&core.component_nodes, map)),
+        CollectionMembers::InstanceBySources { sources, behavior, .. } => {
+            let sizes = sources_sizes(core, map, sources);
+            match behavior {
+                SourcesBehavior::Combination => sizes.iter().product(),
+                SourcesBehavior::Parallel => sizes.into_iter().min().unwrap_or(0),
+            }
+        },
+    }
+}
+
+fn sources_sizes(
+    core: &DoenetCore,
+    map: &ComponentInstance,
+    sources: &[ComponentRelative],
+) -> Vec<usize> {
+    let sizes: Vec<usize> = sources.iter()
+        .map(|s| collection_size(core, &s.instance_relative_to(&core.component_nodes, map)))
+        .collect();
+    core.map_sources_sizes.borrow_mut()
+        .insert((map.node.name.clone(), map.instance.clone()), sizes.clone());
+    sizes
+}
+
+/// The indices that the nth instance of a map adds to the Instance
+fn instance_indices_by_sources(
+    sizes: &[usize],
+    behavior: SourcesBehavior,
+    index: usize,
+) -> Vec<usize> {
+    match behavior {
+        SourcesBehavior::Parallel => vec![index],
+        SourcesBehavior::Combination => {
+            // The last sources varies the fastest
+            let Some(mut remaining) = index.checked_sub(1) else {
+                return vec![0; sizes.len()];
+            };
+            let mut indices = vec![0; sizes.len()];
+            for (i, size) in sizes.iter().enumerate().rev() {
+                indices[i] = remaining % size + 1;
+                remaining /= size;
+            }
+            indices
+        },
     }
 }
 
@@ -3169,6 +4227,10 @@ fn nth_collection_member<'a>(
     index: usize,
 ) -> Option<ComponentGenerated<'a>> {
 
+    if collection_max_number(component_instance.node).is_some_and(|max_number| index > max_number) {
+        return None;
+    }
+
     let mut index = index;
     for c in core.collection_members.get(&component_instance.node.name).unwrap() {
         let (size, group_member);
@@ -3191,10 +4253,13 @@ fn nth_collection_member<'a>(
                 size = (condition == Some(StateVarValue::Boolean(true))) as usize;
                 group_member = Node(component_instance);
             },
-            CollectionMembers::InstanceBySources { sources, template } => {
-                size = collection_size(core, &sources.instance_relative_to(&core.component_nodes, component_instance));
+            CollectionMembers::InstanceBySources { sources, template, behavior } => {
+                size = collection_members_size(core, component_instance, c);
                 let mut map_new = template.relative_instance.relative_to(&component_instance.instance);
-                map_new.push(index);
+                if index <= size {
+                    let sizes = sources_sizes(core, component_instance, sources);
+                    map_new.extend(instance_indices_by_sources(&sizes, *behavior, index));
+                }
                 group_member = Node(ComponentInstance {
                     node: core.component_nodes.get(&template.name).unwrap(),
                     instance: map_new,
@@ -3443,7 +4508,8 @@ fn calculate_relative_instance(
         for child in parents {
             if parent.definition.component_type == "map"
             && child.definition.component_type != "sources" {
-                sources.push(parent);
+                let dimensions = parse_json::map_instance_dimensions(components, parent);
+                sources.extend(std::iter::repeat_n(parent, dimensions));
             }
             parent = child;
         }
@@ -3501,15 +4567,25 @@ fn sources_that_instance_component<'a>(
     for child in parents {
         if parent.definition.component_type == "map"
         && child.definition.component_type != "sources" {
-            let sources_child = get_children_of_type(component_nodes, parent, "sources", false)
-                .next().unwrap();
-            sources.push(sources_child);
+            sources.extend(sources_instancing_map(component_nodes, parent));
         }
         parent = child;
     }
     sources
 }
 
+/// The sources of a map that pick its instances, one for each index that the map adds to the Instance
+fn sources_instancing_map<'a>(
+    component_nodes: &'a HashMap<ComponentName, ComponentNode>,
+    map: &'a ComponentNode,
+) -> Vec<&'a ComponentNode> {
+    let sources = get_children_of_type(component_nodes, map, "sources", false);
+    match SourcesBehavior::from_static_attributes(&map.static_attributes) {
+        SourcesBehavior::Combination => sources.collect(),
+        SourcesBehavior::Parallel => sources.take(1).collect(),
+    }
+}
+
 /// Vector of parents beginning with the component, then its parent, and so on until the root
 fn parent_chain<'a>(
     component_nodes: &'a HashMap<ComponentName, ComponentNode>,
@@ -3526,6 +4602,28 @@ fn parent_chain<'a>(
 }
 
 
+/// The descendants of a component with the given type, in document order
+fn descendants_of_type<'a>(
+    component_nodes: &'a HashMap<ComponentName, ComponentNode>,
+    component: &'a ComponentNode,
+    component_type: ComponentType,
+) -> Vec<&'a ComponentNode> {
+    component.children.iter()
+        .filter_map(|child| match child {
+            ComponentChild::Component(name) => Some(component_nodes.get(name).unwrap()),
+            ComponentChild::String(_) => None,
+        })
+        .flat_map(|child| {
+            let mut found = Vec::new();
+            if child.definition.component_type == component_type {
+                found.push(child);
+            }
+            found.extend(descendants_of_type(component_nodes, child, component_type));
+            found
+        })
+        .collect()
+}
+
 
 // ==== Error and warning checks during core creating ====
 
@@ -3562,6 +4660,79 @@ fn check_for_invalid_childen_component_profiles(component_nodes: &HashMap<Compon
     doenet_ml_warnings
 }
 
+/// Copying an element past the end of an array, like the z of a two dimensional point,
+/// gives no value. Only sizes that are known before any state var is resolved are checked.
+fn check_for_array_elements_out_of_range(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    component_attributes: &HashMap<ComponentName, HashMap<AttributeName, HashMap<usize, Vec<ObjectName>>>>,
+) -> Vec<DoenetMLWarning> {
+    let mut doenet_ml_warnings = vec![];
+    for component in component_nodes.values() {
+        let Some(CopySource::StateVar(ComponentRefStateRelative(
+            ComponentRefRelative::Component(ComponentGeneratedRelative::NodeRel(source)),
+            StateRef::ArrayElement(sv_name, index),
+        ))) = &component.copy_source else {
+            continue
+        };
+        let [index] = index.as_slice() else {
+            continue
+        };
+
+        let size = static_array_size(component_nodes, component_attributes, &source.name, sv_name);
+        if let Some(size) = size.filter(|size| index > size) {
+            doenet_ml_warnings.push(DoenetMLWarning::ArrayIndexOutOfRange {
+                comp_name: component.name.clone(),
+                source_comp_name: source.name.clone(),
+                source_sv_name: sv_name,
+                index: *index,
+                size,
+            });
+        }
+    }
+    doenet_ml_warnings
+}
+
+/// The size of an array state var when it only depends on attributes
+fn static_array_size(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    component_attributes: &HashMap<ComponentName, HashMap<AttributeName, HashMap<usize, Vec<ObjectName>>>>,
+    comp_name: &ComponentName,
+    sv_name: StateVarName,
+) -> Option<usize> {
+    let component = component_nodes.get(comp_name)?;
+    let sv_def = component.definition.state_var_definitions.get(sv_name)?;
+    if !sv_def.is_array() {
+        return None;
+    }
+
+    let instructions: Vec<DependencyInstruction> = sv_def
+        .return_size_dependency_instructions(HashMap::new())
+        .into_values()
+        .collect();
+
+    match instructions.as_slice() {
+        [DependencyInstruction::Attribute { attribute_name, index: StateIndex::SizeOf }] => {
+            let attribute = component_attributes.get(comp_name)
+                .and_then(|attributes| attributes.get(attribute_name));
+            match (attribute, &component.copy_source) {
+                (Some(attribute), _) => Some(attribute.len()),
+                (None, None) => Some(sv_def.initial_essential_size()),
+
+                // Without the attribute, a copy has the size of its source
+                (None, Some(CopySource::Component(ComponentRefRelative::Component(
+                    ComponentGeneratedRelative::NodeRel(source)
+                )))) => static_array_size(component_nodes, component_attributes, &source.name, sv_name),
+                (None, Some(_)) => None,
+            }
+        },
+        [DependencyInstruction::StateVar {
+            component_ref: None,
+            state_var: StateVarSlice::Single(StateRef::SizeOf(other_sv_name)),
+        }] => static_array_size(component_nodes, component_attributes, comp_name, other_sv_name),
+        _ => None,
+    }
+}
+
 /// Do this before dependency generation so it doesn't crash
 fn check_for_cyclical_copy_sources(component_nodes: &HashMap<ComponentName, ComponentNode>) -> Result<(), DoenetMLError> {
     // All the components that copy another component, along with the name of the component they copy
@@ -3644,6 +4815,67 @@ fn check_for_invalid_component_names(
     Ok(())
 }
 
+/// The parents and attribute components that dependency instructions ask for must have the state var
+fn check_for_missing_dependency_state_vars(
+    component_nodes: &HashMap<ComponentName, ComponentNode>,
+    component_attributes: &HashMap<ComponentName, HashMap<AttributeName, HashMap<usize, Vec<ObjectName>>>>,
+) -> Result<(), DoenetMLError> {
+
+    for component in component_nodes.values() {
+        let attributes = component_attributes.get(&component.name);
+
+        for sv_def in component.definition.state_var_definitions.values() {
+            let instructions: Vec<DependencyInstruction> = if sv_def.is_array() {
+                sv_def.return_size_dependency_instructions(HashMap::new()).into_values()
+                    .chain(sv_def.return_array_dependency_instructions(HashMap::new()).into_values())
+                    .chain(sv_def.return_element_dependency_instructions(1, HashMap::new()).into_values())
+                    .collect()
+            } else {
+                sv_def.return_dependency_instructions(HashMap::new()).into_values().collect()
+            };
+
+            for instruction in instructions {
+                let (referenced, state_var) = match instruction {
+                    DependencyInstruction::Parent { state_var } => {
+                        let Some(parent) = &component.parent else { continue };
+                        (component_nodes.get(parent).unwrap(), state_var)
+                    },
+                    DependencyInstruction::ParentOfType { component_type, state_var } => {
+                        let Some(parent) = &component.parent else { continue };
+                        let parent = component_nodes.get(parent).unwrap();
+                        if parent.definition.component_type != component_type {
+                            continue;
+                        }
+                        (parent, state_var)
+                    },
+                    DependencyInstruction::AttributeComponent { attribute_name, index, state_var } => {
+                        let Some(ObjectName::Component(comp_name)) = attributes
+                            .and_then(|attributes| attributes.get(attribute_name))
+                            .and_then(|attribute| attribute.get(&index))
+                            .and_then(|objects| objects.first()) else { continue };
+                        let referenced = component_nodes.get(comp_name).unwrap();
+                        if referenced.definition.primary_input_state_var.is_some() {
+                            continue;
+                        }
+                        (referenced, state_var.name())
+                    },
+                    _ => continue,
+                };
+
+                if !referenced.definition.state_var_definitions.contains_key(state_var) {
+                    // A macro like $A is a copy, so name the component it copies
+                    let source = get_recursive_copy_source_component_when_exists(component_nodes, referenced);
+                    return Err(DoenetMLError::StateVarDoesNotExist {
+                        comp_name: source.name,
+                        sv_name: state_var.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn check_for_cyclical_dependencies(dependencies: &HashMap<DependencyKey, Vec<Dependency>>) -> Result<(), DoenetMLError> {
    // Now that the dependency graph has been created, use it to check for cyclical dependencies
     // for all the components
@@ -73,6 +73,13 @@ impl MathExpression {
 
 
 
+/// The empty expression
+impl Default for MathExpression {
+    fn default() -> Self {
+        MathExpression::new(&vec![])
+    }
+}
+
 impl From<f64> for MathExpression {
     fn from(input: f64) -> Self {
         MathExpression {
@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 
 use crate::utils::{log_json, log_debug, log};
-use crate::{Action, ComponentName};
+use crate::{Action, ComponentName, SourcesBehavior};
 use crate::component::{COMPONENT_DEFINITIONS, ComponentType, ComponentDefinition,
 KeyValueIgnoreCase, AttributeName, ObjectName, ReplacementComponents};
 
@@ -50,6 +50,10 @@ pub enum DoenetMLError {
         source_comp_name: ComponentName,
         source_sv_name: StateVarName,
     },
+    CannotCopyIndexForMultiDimensionalStateVar {
+        source_comp_name: ComponentName,
+        source_sv_name: StateVarName,
+    },
 
     DuplicateName {
         name: String,
@@ -63,6 +67,11 @@ pub enum DoenetMLError {
         source_type: ComponentType,
     },
 
+    CannotCopyPropOfMembersOfDifferentTypes {
+        source_comp_name: ComponentName,
+        source_sv_name: String,
+    },
+
     /// For the componentType static attr of <sources>
     CannotImplySourcesComponentType {
         component_name: ComponentName,
@@ -91,6 +100,8 @@ impl Display for DoenetMLError {
                 write!(f, "Cannot copy array state variable '{}' from component {}", source_sv_name, source_comp_name),
             CannotCopyIndexForStateVar { source_comp_name, source_sv_name } =>
                 write!(f, "Cannot use propIndex for state variable '{}' from component {} because this state variable is not an array", source_sv_name, source_comp_name),
+            CannotCopyIndexForMultiDimensionalStateVar { source_comp_name, source_sv_name } =>
+                write!(f, "Cannot use propIndex for state variable '{}' from component {} because this state variable has more than one dimension", source_sv_name, source_comp_name),
             DuplicateName { name} =>
                 write!(f, "The component name {} is used multiple times", name),
             CyclicalDependency { component_chain } => {
@@ -106,6 +117,8 @@ impl Display for DoenetMLError {
             ComponentCannotCopyOtherType { component_name, component_type, source_type } => {
                 write!(f, "The {} component '{}' cannot copy a {} component.", component_type, component_name, source_type)
             },
+            CannotCopyPropOfMembersOfDifferentTypes { source_comp_name, source_sv_name } =>
+                write!(f, "Cannot copy state variable '{}' from component {} because its members are of different types", source_sv_name, source_comp_name),
             CannotImplySourcesComponentType { component_name } => write!(f, "Cannot impy 'componentType' attribute of {}", component_name),
         }
     }
@@ -126,6 +139,13 @@ pub enum DoenetMLWarning {
         child_comp_name: ComponentName,
         child_comp_type: ComponentType,
     },
+    ArrayIndexOutOfRange {
+        comp_name: ComponentName,
+        source_comp_name: ComponentName,
+        source_sv_name: StateVarName,
+        index: usize,
+        size: usize,
+    },
 }
 
 impl std::error::Error for DoenetMLWarning {}
@@ -139,6 +159,9 @@ impl Display for DoenetMLWarning {
             InvalidChildType { parent_comp_name, child_comp_name: _, child_comp_type } => {
                 write!(f, "Component {} cannot have a child component of type {}", parent_comp_name, child_comp_type)
             },
+            ArrayIndexOutOfRange { comp_name, source_comp_name, source_sv_name, index, size } => {
+                write!(f, "Component {} copies element {} of {} of {}, which only has {}", comp_name, index, source_sv_name, source_comp_name, size)
+            },
         }
 
     }
@@ -158,6 +181,8 @@ struct ComponentTree {
 #[serde(rename_all = "camelCase")]
 struct Props {
     name: Option<String>,
+    new_namespace: Option<AttributeValue>,
+    assign_names: Option<AttributeValue>,
     copy_source: Option<String>,
     copy_collection: Option<String>,
     copy_prop: Option<String>,
@@ -258,48 +283,67 @@ pub fn create_components_tree_from_json(program: &str)
         &mut map_sources_alias,
         &component_tree,
         None,
+        "/",
         &mut component_type_counter,
     )?
     .unwrap();
 
+    let assigned_names = assigned_names_of_members(&components)?;
 
-    // Determine <sources>'s componentType static attribute, if not specified
-    // TODO: <sources> inside <sources>
-    // TODO: <sources> with copySource another <sources>
-    let mut sources_component_types: HashMap<ComponentName, ComponentType> = HashMap::new();
-    for (comp_name, comp) in components.iter().filter(|(_, c)|
-        c.definition.component_type == "sources" && !c.static_attributes.contains_key("componentType")
-    ) {
-        let comp_children: Vec<&MLComponent> = comp.children.iter().filter_map(|child|
-            child.as_component().and_then(|name| Some(components.get(name).unwrap()))
-        ).collect();
-            
-        if comp_children.len() == 0 {
-            // Every <sources> needs a componentType attr, so default to <number> since it doesn't matter
-            sources_component_types.insert(comp_name.clone(), "number");
-        } else {
-            // log!("{} is <source> without componentType attr, child is {}", comp_name, comp_children[0].name);
-            let first_comp_child_def = comp_children[0].definition;
-            let child_type = first_comp_child_def.definition_as_replacement_children(&HashMap::new()).unwrap().component_type;
-            sources_component_types.insert(comp_name.clone(), child_type);
+    for collect in components.values().filter(|c| c.definition.component_type == "collect") {
+        crate::component::collect::check_static_attributes(&collect.name, &collect.static_attributes)?;
+    }
+
+    // Now that every component has its full name, resolve the names
+    // that copySources and the source of <collect> refer to
+    let lookup = NameLookup { components: &components, map_sources_alias: &map_sources_alias, assigned_names: &assigned_names };
+    let resolve = |c: &MLComponent, reference: &String|
+        resolve_name(reference, namespace_of(&c.name), &lookup);
+    let resolved_copy_sources: Vec<(ComponentName, ResolvedName)> = components.values()
+        .filter_map(|c| c.copy_source.as_ref().map(|source| Ok((c.name.clone(), resolve(c, source)?))))
+        .collect::<Result<_, DoenetMLError>>()?;
+    let resolved_collect_sources: Vec<(ComponentName, ResolvedName)> = components.values()
+        .filter(|c| c.definition.component_type == "collect")
+        .filter_map(|c| c.static_attributes.get("source").map(|source| {
+            // The source can also be given as a macro
+            let source = source.trim().strip_prefix('$').unwrap_or(source.trim()).to_string();
+            Ok((c.name.clone(), resolve(c, &source)?))
+        }))
+        .collect::<Result<_, DoenetMLError>>()?;
+    for (comp_name, copy_source) in resolved_copy_sources {
+        let component = components.get_mut(&comp_name).unwrap();
+        component.copy_source = Some(copy_source.name);
+        if component.copy_instance.is_none() {
+            component.copy_instance = copy_source.map_instance;
         }
+        if let Some(member_index) = copy_source.member_index {
+            component_indices.get_mut(&comp_name).unwrap()
+                .get_or_insert(member_index.to_string());
+        }
+    }
+    for (comp_name, source) in resolved_collect_sources {
+        components.get_mut(&comp_name).unwrap().static_attributes.insert("source", source.name);
     }
 
+
+    // Determine <sources>'s componentType static attribute, if not specified
+    let sources_component_types: HashMap<ComponentName, String> = components.values()
+        .filter(|c| c.definition.component_type == "sources" && !c.static_attributes.contains_key("componentType"))
+        .map(|c| (c.name.clone(), sources_component_type(&components, c)))
+        .collect();
+
     for (comp_name, child_comp_type) in sources_component_types {
         let comp = components.get_mut(&comp_name).unwrap();
-        comp.static_attributes.insert("componentType", String::from(child_comp_type));
+        comp.static_attributes.insert("componentType", child_comp_type);
     }
 
-
-
     let (replacement_children, macro_components, attributes_parsed, prop_indices_parsed, component_indices_parsed) =
  
         parse_attributes_and_macros(
-            &components,
+            &NameLookup { components: &components, map_sources_alias: &map_sources_alias, assigned_names: &assigned_names },
             attributes,
             prop_indices,
             component_indices,
-            &map_sources_alias,
         );
 
     // log_debug!("Components to add from macros: {:#?}", components_to_add);
@@ -341,6 +385,60 @@ pub fn create_components_tree_from_json(program: &str)
     Ok((components, attributes_parsed, root_component_name, map_sources_alias))
 }
 
+/// The type of the members of a <sources>: its componentType attribute, or else the
+/// type of its first member, looking through nested <sources>, copies and maps.
+fn sources_component_type(
+    components: &HashMap<ComponentName, MLComponent>,
+    sources: &MLComponent,
+) -> String {
+    if let Some(component_type) = sources.static_attributes.get("componentType") {
+        return component_type.clone();
+    }
+    match component_children_including_copy(components, sources).first() {
+        Some(first_child) => member_component_type(components, first_child),
+
+        // Every <sources> needs a componentType attr, so default to <number> since it doesn't matter
+        None => "number".to_string(),
+    }
+}
+
+/// The type of the components that this component stands for inside a collection
+fn member_component_type(
+    components: &HashMap<ComponentName, MLComponent>,
+    component: &MLComponent,
+) -> String {
+    if component.definition.component_type == "sources" {
+        return sources_component_type(components, component);
+    }
+    match component.definition.definition_as_replacement_children(&component.static_attributes) {
+        Some(member_def) if matches!(member_def.replacement_components, Some(ReplacementComponents::Children)) => {
+            // A collection of template instances (like a <map>) stands for what is in its template
+            component_children_including_copy(components, component).into_iter()
+                .find(|child| child.definition.component_type == member_def.component_type)
+                .and_then(|template| component_children_including_copy(components, template).first()
+                    .map(|first_child| member_component_type(components, first_child)))
+                .unwrap_or_else(|| "number".to_string())
+        },
+        Some(member_def) => member_def.component_type.to_string(),
+        None => component.definition.component_type.to_string(),
+    }
+}
+
+/// The component children of a copy come before its own
+fn component_children_including_copy<'a>(
+    components: &'a HashMap<ComponentName, MLComponent>,
+    component: &'a MLComponent,
+) -> Vec<&'a MLComponent> {
+    let mut children = component.copy_source.as_ref()
+        .and_then(|source| components.get(source))
+        .map(|source| component_children_including_copy(components, source))
+        .unwrap_or_default();
+    children.extend(component.children.iter()
+        .filter_map(|child| child.as_component())
+        .map(|child| components.get(child).unwrap()));
+    children
+}
+
 /// Recursive function
 /// The return is the name of the child, if it exists
 /// (it might not because of invalid doenet ml)
@@ -352,6 +450,7 @@ fn add_component_from_json(
     map_sources_alias: &mut HashMap<String, ComponentName>,
     component_tree: &ComponentTree,
     parent: Option<String>,
+    namespace: &str,
     component_type_counter: &mut HashMap<String, u32>,
 ) -> Result<Option<ComponentName>, DoenetMLError> {
 
@@ -364,12 +463,14 @@ fn add_component_from_json(
         )?
         .1;
 
-    let count = component_type_counter.entry(component_type.to_string()).or_insert(0);
+    // Unnamed components are numbered separately in each namespace
+    let unnamed_prefix = format!("{}_{}", namespace, component_type);
+    let count = component_type_counter.entry(unnamed_prefix.clone()).or_insert(0);
     *count += 1;
 
     let name = match &component_tree.props.name {
-        Some(name) => name.clone(),
-        None => format!("/_{}{}", component_type, count),
+        Some(name) => format!("{}{}", namespace, name),
+        None => format!("{}{}", unnamed_prefix, count),
     };
 
     if components.contains_key(&name) {
@@ -383,6 +484,7 @@ fn add_component_from_json(
         .attribute_names
         .iter()
         .map(|n| (n.to_lowercase(), *n))
+        .chain(definition.attribute_aliases.iter().map(|(alias, n)| (alias.to_lowercase(), *n)))
         .collect();
     let lower_case_static_attributes: HashMap<String, AttributeName> = definition
         .static_attribute_names
@@ -404,13 +506,33 @@ fn add_component_from_json(
         }
     }
 
-    // Add alias
+    // Copies can always start a namespace and name their members,
+    // other components only if they say so
+    for (attr_name, attr_value) in [
+        ("newNamespace", &component_tree.props.new_namespace),
+        ("assignNames", &component_tree.props.assign_names),
+    ] {
+        if let Some(attr_value) = attr_value {
+            if component_tree.props.copy_source.is_none()
+                && !definition.static_attribute_names.contains(&attr_name) {
+                return Err(DoenetMLError::AttributeDoesNotExist {
+                    comp_name: name.clone(),
+                    attr_name: attr_name.to_string(),
+                });
+            }
+            static_attributes.insert(attr_name, attr_value.to_string());
+        }
+    }
+
+    // Add alias and index alias
     if component_type == "sources" {
-        if let Some(alias) = static_attributes.get("alias") {
-            map_sources_alias.insert(alias.clone(), name.clone());
+        for alias in ["alias", "indexAlias"].iter().filter_map(|a| static_attributes.get(a)) {
+            map_sources_alias.insert(format!("{}{}", namespace, alias), name.clone());
         }
     }
 
+    let children_namespace = namespace_for_children(&name, &static_attributes);
+
     // Recurse the children
     let mut children: Vec<ComponentChild> = Vec::new();
     for child in &component_tree.children {
@@ -429,6 +551,7 @@ fn add_component_from_json(
                     map_sources_alias,
                     &child_tree,
                     Some(name.clone()),
+                    &children_namespace,
                     component_type_counter,
                 )?;
 
@@ -472,7 +595,7 @@ fn add_component_from_json(
 /// Temporary implementation to test if maps are working.
 /// returns (copy source, copy instance)
 /// Ex: [1 2]myname -> (myname, [1, 2])
-/// TODO: The final implementation should incorporate namespaces, not just map instancing.
+/// The name itself is resolved within its namespace later, by `resolve_name`.
 fn convert_copy_source_name(name: Option<String>) -> (Option<String>, Option<Vec<usize>>) {
     if let Some(name) = name {
         if name.chars().next() == Some('(') {
@@ -490,13 +613,190 @@ fn convert_copy_source_name(name: Option<String>) -> (Option<String>, Option<Vec
     }
 }
 
+/// The namespace that a full component name is in, like `/sec/` for `/sec/x`.
+pub fn namespace_of(name: &str) -> &str {
+    &name[..name.rfind('/').map_or(0, |i| i + 1)]
+}
 
-fn parse_attributes_and_macros(
+/// Children are named inside the component's own namespace if it has `newNamespace`,
+/// otherwise inside the namespace of the component.
+pub fn namespace_for_children(name: &str, static_attributes: &HashMap<AttributeName, String>) -> String {
+    match static_attributes.get("newNamespace") {
+        Some(new_namespace) if new_namespace != "false" => format!("{}/", name),
+        _ => namespace_of(name).to_string(),
+    }
+}
+
+/// A name given with assignNames refers to a member of a group:
+/// `<sequence name='s' assignNames='a b'/>` makes `$a` the same as `$s[1]`.
+/// Keyed by the full assigned name, the values are the group and the member index.
+type AssignedNames = HashMap<ComponentName, (ComponentName, usize)>;
+
+/// Everything a name can refer to, once every component has its full name
+struct NameLookup<'a> {
+    components: &'a HashMap<ComponentName, MLComponent>,
+    map_sources_alias: &'a HashMap<String, ComponentName>,
+    assigned_names: &'a AssignedNames,
+}
+
+/// Assigned names are in the namespace the group's children would be in.
+fn assigned_names_of_members(components: &HashMap<ComponentName, MLComponent>)
+    -> Result<AssignedNames, DoenetMLError> {
+
+    let mut assigned_names = AssignedNames::new();
+    for component in components.values() {
+        let Some(names) = component.static_attributes.get("assignNames") else {
+            continue;
+        };
+        let namespace = namespace_for_children(&component.name, &component.static_attributes);
+        for (index, name) in names.split_whitespace().enumerate() {
+            let name = format!("{}{}", namespace, name);
+            if components.contains_key(&name) || assigned_names.contains_key(&name) {
+                return Err(DoenetMLError::DuplicateName { name });
+            }
+            // DoenetML is 1-indexed
+            assigned_names.insert(name, (component.name.clone(), index + 1));
+        }
+    }
+    Ok(assigned_names)
+}
+
+/// Whether the name given to a <sources> is its indexAlias rather than its alias
+pub fn is_index_alias(alias_name: &str, sources: &MLComponent) -> bool {
+    sources.static_attributes.get("indexAlias")
+        .is_some_and(|index_alias| format!("{}{}", namespace_of(&sources.name), index_alias) == alias_name)
+}
+
+/// How many indices a map adds to the Instance of the components in its template:
+/// one for each of its sources in combination, or just one when they are in parallel.
+pub fn map_instance_dimensions(
     components: &HashMap<ComponentName, MLComponent>,
+    map: &MLComponent,
+) -> usize {
+    match SourcesBehavior::from_static_attributes(&map.static_attributes) {
+        SourcesBehavior::Parallel => 1,
+        SourcesBehavior::Combination => map.children.iter()
+            .filter_map(|child| components.get(child.as_component()?))
+            .filter(|child| child.definition.component_type == "sources")
+            .count(),
+    }
+}
+
+/// The name assigned to a member of a group, if any.
+pub fn assigned_name_of_member(
+    group_name: &ComponentName,
+    group_static_attributes: &HashMap<AttributeName, String>,
+    index: usize,
+) -> Option<ComponentName> {
+    let name = group_static_attributes.get("assignNames")?
+        .split_whitespace()
+        .nth(index.checked_sub(1)?)?;
+    Some(format!("{}{}", namespace_for_children(group_name, group_static_attributes), name))
+}
+
+/// What a reference in DoenetML leads to
+#[derive(Debug)]
+struct ResolvedName {
+    name: ComponentName,
+
+    /// The member of the group `name`, when the reference is an assigned name
+    member_index: Option<usize>,
+
+    /// The map instance, when the reference is inside a map instance with an assigned name
+    map_instance: Option<Vec<usize>>,
+}
+
+impl ResolvedName {
+    fn of_name(reference: &str) -> Self {
+        ResolvedName { name: reference.to_string(), member_index: None, map_instance: None }
+    }
+}
+
+/// What a full name leads to, looking through the names given with assignNames.
+/// Inside a map instance with an assigned name, like `/a/x` for `<map assignNames='a b'>`,
+/// names are those of the template's children.
+fn look_up_full_name(
+    full_name: &str,
+    lookup: &NameLookup,
+) -> Option<ResolvedName> {
+
+    let NameLookup { components, map_sources_alias, assigned_names } = lookup;
+
+    if components.contains_key(full_name) || map_sources_alias.contains_key(full_name) {
+        return Some(ResolvedName::of_name(full_name));
+    }
+    if let Some((group, index)) = assigned_names.get(full_name) {
+        return Some(ResolvedName { name: group.clone(), member_index: Some(*index), map_instance: None });
+    }
+
+    full_name.match_indices('/').rev().find_map(|(slash, _)| {
+        let (map_name, index) = assigned_names.get(&full_name[..slash])?;
+
+        // An assigned name picks one index, which is not enough for several sources in combination
+        let template = components.get(map_name)
+            .filter(|map| map.definition.component_type == "map")
+            .filter(|map| map_instance_dimensions(components, map) == 1)?
+            .children.iter()
+            .filter_map(|child| components.get(child.as_component()?))
+            .find(|child| child.definition.component_type == "template")?;
+
+        let name_in_template = format!("{}{}",
+            namespace_for_children(&template.name, &template.static_attributes),
+            &full_name[slash + 1..],
+        );
+        let resolved = look_up_full_name(&name_in_template, lookup)?;
+
+        let mut map_instance = vec![*index];
+        map_instance.extend(resolved.map_instance.unwrap_or_default());
+        Some(ResolvedName { map_instance: Some(map_instance), ..resolved })
+    })
+}
+
+/// What a name referred to from inside `namespace` leads to.
+/// The reference can be absolute, like `/sec/x`, or relative, like `x`, `sec/x` or `../x`.
+/// A relative name is looked for in its namespace, and then in each enclosing one.
+fn resolve_name(
+    reference: &str,
+    namespace: &str,
+    lookup: &NameLookup,
+) -> Result<ResolvedName, DoenetMLError> {
+
+    let look_up = |name: &str| look_up_full_name(name, lookup);
+    let does_not_exist = || DoenetMLError::ComponentDoesNotExist { comp_name: reference.to_string() };
+
+    if reference.starts_with('/') {
+        return look_up(reference).ok_or_else(does_not_exist);
+    }
+
+    let parent_namespace = |namespace: &str| match namespace_of(namespace.trim_end_matches('/')) {
+        "" => "/".to_string(),
+        parent => parent.to_string(),
+    };
+
+    let mut namespace = namespace.to_string();
+    let mut relative_name = reference;
+    while let Some(rest) = relative_name.strip_prefix("../") {
+        namespace = parent_namespace(&namespace);
+        relative_name = rest;
+    }
+
+    loop {
+        if let Some(resolved) = look_up(&format!("{}{}", namespace, relative_name)) {
+            return Ok(resolved);
+        }
+        if namespace.len() <= 1 {
+            return Err(does_not_exist());
+        }
+        namespace = parent_namespace(&namespace);
+    }
+}
+
+
+fn parse_attributes_and_macros(
+    lookup: &NameLookup,
     attributes: HashMap<ComponentName, HashMap<AttributeName, String>>,
     prop_indices: HashMap<ComponentName, Option<String>>,
     component_indices: HashMap<ComponentName, Option<String>>,
-    map_sources_alias: &HashMap<String, ComponentName>,
 ) -> (
     HashMap<ComponentName, HashMap<usize, Vec<ObjectName>>>,
     Vec<MLComponent>,
@@ -507,6 +807,8 @@ fn parse_attributes_and_macros(
 {
     use std::iter::repeat;
 
+    let components = lookup.components;
+
     let mut attributes_parsed = HashMap::new();
     let mut prop_indices_parsed = HashMap::new();
     let mut component_indices_parsed = HashMap::new();
@@ -549,8 +851,8 @@ fn parse_attributes_and_macros(
         let objects = apply_macro_to_string(
             string_val,
             &component.name,
-            components,
-            map_sources_alias,
+            &namespace_for_children(&component.name, &component.static_attributes),
+            lookup,
             &mut macro_copy_counter,
             &mut components_to_add
         );
@@ -576,8 +878,8 @@ fn parse_attributes_and_macros(
                     apply_macro_to_string(
                         string_element.trim(),
                         &component.name,
-                        components,
-                        map_sources_alias,
+                        namespace_of(&component.name),
+                        lookup,
                         &mut macro_copy_counter,
                         &mut components_to_add,
                     )
@@ -596,8 +898,8 @@ fn parse_attributes_and_macros(
             Some(string) => apply_macro_to_string(
                 &string,
                 &target_name,
-                components,
-                map_sources_alias,
+                namespace_of(&target_name),
+                lookup,
                 &mut macro_copy_counter,
                 &mut components_to_add
             ),
@@ -614,8 +916,8 @@ fn parse_attributes_and_macros(
             Some(string) => apply_macro_to_string(
                 &string,
                 &target_name,
-                components,
-                map_sources_alias,
+                namespace_of(&target_name),
+                lookup,
                 &mut macro_copy_counter,
                 &mut components_to_add
             ),
@@ -637,8 +939,8 @@ fn parse_attributes_and_macros(
 fn apply_macro_to_string(
     string: &str,
     component_name: &ComponentName,
-    components: &HashMap<ComponentName, MLComponent>,
-    map_sources_alias: &HashMap<String, ComponentName>,
+    namespace: &str,
+    lookup: &NameLookup,
     macro_copy_counter: &mut HashMap<ComponentName, usize>,
     components_to_add: &mut Vec<MLComponent>,
 ) -> Vec<ObjectName> {
@@ -665,8 +967,8 @@ fn apply_macro_to_string(
         match macro_comp_ref(string,
             next_macro.end(),
             component_name,
-            components,
-            map_sources_alias,
+            namespace,
+            lookup,
             macro_copy_counter,
             components_to_add
         ) {
@@ -694,29 +996,46 @@ fn macro_comp_ref(
     string: &str,
     start: usize,
     macro_parent: &ComponentName,
-    components: &HashMap<ComponentName, MLComponent>,
-    map_sources_alias: &HashMap<String, ComponentName>,
+    namespace: &str,
+    lookup: &NameLookup,
     macro_copy_counter: &mut HashMap<ComponentName, usize>,
     components_to_add: &mut Vec<MLComponent>,
 ) -> Result<(ComponentName, usize), String> {
 
+    let NameLookup { components, map_sources_alias, .. } = lookup;
+
     // log_debug!("macro at {} of {}", start, string);
 
     let comp_match = regex_at(&COMPONENT, string, start)?;
 
-    let copy_source = comp_match.as_str().to_string();
-    let (copy_source, copy_instance) = convert_copy_source_name(Some(copy_source.clone()));
-    let copy_source = copy_source.unwrap();
+    // A slash only separates the names of a path that leads to a component,
+    // so `$x/y` in math is x divided by y unless there is a component `x/y`
+    let mut name_end = comp_match.end();
+    let resolved = loop {
+        let reference = &string[start..name_end];
+        match resolve_name(reference, namespace, lookup) {
+            Ok(resolved) => break resolved,
+            Err(err) => match reference.rfind('/') {
+                Some(slash) if slash > 0 && !reference[..slash].ends_with("..") => name_end = start + slash,
+                _ => return Err(err.to_string()),
+            },
+        }
+    };
+    let copy_instance = resolved.map_instance;
+    let copy_source = resolved.name;
 
 
     if let Some(sources_name) = map_sources_alias.get(&copy_source) {
         // Special case: the macro references a sources component
 
-        let component_type = components.get(sources_name).unwrap()
-            .static_attributes.get("componentType")
-            .ok_or("Sources did not define component type")?;
+        let sources = components.get(sources_name).unwrap();
+        let component_type = match is_index_alias(&copy_source, sources) {
+            true => "number",
+            false => sources.static_attributes.get("componentType")
+                .ok_or("Sources did not define component type")?,
+        };
         let definition = &COMPONENT_DEFINITIONS
-            .get(component_type.as_str())
+            .get(component_type)
             .ok_or("Sources invalid component type")?;
 
         let macro_copy = MLComponent {
@@ -735,7 +1054,7 @@ fn macro_comp_ref(
 
         let macro_name = macro_copy.name.clone();
         components_to_add.push(macro_copy);
-        return Ok((macro_name, comp_match.end()))
+        return Ok((macro_name, name_end))
     }
 
     let source_component = components.get(&copy_source).ok_or(format!("The component {} does not exist", copy_source))?;
@@ -748,12 +1067,27 @@ fn macro_comp_ref(
     let copy_prop: Option<String>;
     let prop_index: Vec<ObjectName>;
  
+    let group_member_definition = || match &source_component.definition.replacement_components {
+        Some(ReplacementComponents::Batch(def)) => Ok(def.member_definition),
+        Some(ReplacementComponents::Collection(def)) => Ok((def.member_definition)(&source_component.static_attributes)),
+        _ => Err("index of non-group".to_string()),
+    };
+
     // Handle possible component index: brackets after the component name
     let comp_end;
     let source_def;
-    if char_at(comp_match.end()) == Some('[') {
+    if let Some(member_index) = resolved.member_index {
+        // group member given a name with assignNames
+        if char_at(name_end) == Some('[') {
+            return Err("index of non-group".to_string());
+        }
+        component_index = vec![ObjectName::String(member_index.to_string())];
+        comp_end = name_end;
+        source_def = group_member_definition()?;
+
+    } else if char_at(name_end) == Some('[') {
         // group member
-        let index_match = regex_at(&INDEX, string, comp_match.end() + 1)?;
+        let index_match = regex_at(&INDEX, string, name_end + 1)?;
         let index_str = index_match.as_str();
         let index_end: usize;
         if index_str == "$" {
@@ -767,19 +1101,10 @@ fn macro_comp_ref(
         let close_bracket_match = regex_at(&INDEX_END, string, index_end)?;
         comp_end = close_bracket_match.end();
 
-        source_def = match (None, &source_component.definition.replacement_components) {
-            (Some(key), _) => {
-                source_component.definition.batches
-                    .get_key_value_ignore_case(key).unwrap().1
-                    .member_definition
-            },
-            (None, Some(ReplacementComponents::Batch(def)))  => def.member_definition,
-            (None, Some(ReplacementComponents::Collection(def)))  => (def.member_definition)(&source_component.static_attributes),
-            (None, _)  => return Err("index of non-group".to_string()),
-        };
+        source_def = group_member_definition()?;
     } else {
         // no component index
-        comp_end = comp_match.end();
+        comp_end = name_end;
         component_index = vec![];
         source_def = source_component.definition;
     };
@@ -790,6 +1115,14 @@ fn macro_comp_ref(
         let prop_match = regex_at(&PROP, string, comp_end + 1)?;
         let prop = prop_match.as_str();
 
+        // The prop of every member of a collection makes a list
+        let collection_member_def = match &source_component.definition.replacement_components {
+            Some(ReplacementComponents::Collection(def)) if component_index.is_empty() =>
+                Some((def.member_definition)(&source_component.static_attributes)),
+            _ => None,
+        };
+        let source_def = collection_member_def.unwrap_or(source_def);
+
         let variant = match source_def.state_var_definitions.get(prop) {
             Some(v) => v,
             None => source_def.state_var_definitions.get(
@@ -802,7 +1135,7 @@ fn macro_comp_ref(
         // Handle possible prop index: brackets after the prop name
         if string.as_bytes().get(prop_match.end()) == Some(&b'[') {
 
-            if !variant.is_array() {
+            if !variant.is_array() || collection_member_def.is_some() {
                 return Err(format!("{}.{} cannot be indexed", copy_source, prop));
             }
 
@@ -815,8 +1148,8 @@ fn macro_comp_ref(
                 let (index_name, index_macro_end) = macro_comp_ref(string,
                     index_match.end(),
                     &copy_source,
-                    components,
-                    map_sources_alias,
+                    namespace,
+                    lookup,
                     macro_copy_counter,
                     components_to_add,
                 )?;
@@ -838,8 +1171,17 @@ fn macro_comp_ref(
 
         let source_comp_sv_name = format!("{}:{}", copy_source, prop);
 
-        definition = &COMPONENT_DEFINITIONS
-            .get(default_component_type_for_state_var(variant))
+        // Every element of an array, rather than an alias of one element, also makes a list
+        let whole_array = variant.is_array()
+            && prop_index.is_empty()
+            && source_def.state_var_definitions.contains_key(prop);
+
+        let component_type = match collection_member_def.is_some() || whole_array {
+            true => list_component_type_for_state_var(variant),
+            false => default_component_type_for_state_var(variant),
+        };
+        definition = COMPONENT_DEFINITIONS
+            .get(component_type)
             .unwrap();
 
         name = name_macro_component(
@@ -864,14 +1206,12 @@ fn macro_comp_ref(
         macro_end = comp_end;
     };
 
-    let (copy_source, copy_instance) = convert_copy_source_name(Some(copy_source));
-
     let macro_copy = MLComponent {
         name,
         parent: Some(macro_parent.clone()),
         children: vec![],
 
-        copy_source,
+        copy_source: Some(copy_source),
         copy_instance,
         copy_collection: None,
         copy_prop,
@@ -892,8 +1232,11 @@ fn default_component_type_for_state_var(component: &StateVarVariant)
     -> ComponentType {
 
     match component {
+        StateVarVariant::BooleanArray(_) |
         StateVarVariant::Boolean(_) => "boolean",
+        StateVarVariant::IntegerArray(_) |
         StateVarVariant::Integer(_) => "number",
+        StateVarVariant::MathArray(_) |
         StateVarVariant::NumberArray(_) |
         StateVarVariant::Number(_) => "number",
         StateVarVariant::StringArray(_) |
@@ -901,6 +1244,22 @@ fn default_component_type_for_state_var(component: &StateVarVariant)
     }
 }
 
+fn list_component_type_for_state_var(component: &StateVarVariant)
+    -> ComponentType {
+
+    match component {
+        StateVarVariant::BooleanArray(_) |
+        StateVarVariant::Boolean(_) => "booleanList",
+        StateVarVariant::MathArray(_) => "mathList",
+        StateVarVariant::IntegerArray(_) |
+        StateVarVariant::Integer(_) |
+        StateVarVariant::NumberArray(_) |
+        StateVarVariant::Number(_) => "numberList",
+        StateVarVariant::StringArray(_) |
+        StateVarVariant::String(_) => "textList",
+    }
+}
+
 fn name_macro_component(
     source_name: &str,
     component_name: &String,
@@ -913,7 +1272,7 @@ fn name_macro_component(
 }
 
 lazy_static! { static ref MACRO_BEGIN: Regex = Regex::new(r"\$").unwrap(); }
-lazy_static! { static ref COMPONENT: Regex   = Regex::new(r"[a-zA-Z_]\w*").unwrap(); }
+lazy_static! { static ref COMPONENT: Regex   = Regex::new(r"(/|(\.\./)*)[a-zA-Z_]\w*(/[a-zA-Z_]\w*)*").unwrap(); }
 lazy_static! { static ref PROP: Regex        = Regex::new(r"[a-zA-Z]\w*").unwrap(); }
 lazy_static! { static ref INDEX: Regex       = Regex::new(r" *(\d+|\$)").unwrap(); }
 lazy_static! { static ref INDEX_END: Regex   = Regex::new(r" *]").unwrap(); }
@@ -935,16 +1294,7 @@ fn regex_at<'a>(regex: &Regex, string: &'a str, at: usize) -> Result<regex::Matc
 struct ActionStructure {
     component_name: String,
     action_name: String,
-    args: HashMap<String, ArgValue>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-enum ArgValue {
-    Bool(bool),
-    Number(serde_json::Number),
-    NumberArray(Vec<serde_json::Number>),
-    String(String),
+    args: HashMap<String, serde_json::Value>,
 }
 
 /// Returns the Action as well as the action id which the renderer sent
@@ -958,13 +1308,56 @@ pub fn parse_action_from_json(action: &str) -> Result<(Action, String), String>
     let action_name = action_structure.action_name.clone();
     let mut args: HashMap<String, Vec<StateVarValue>> = action_structure.args
         .into_iter()
-        .map(|(k, v)| (k, v.into()))
+        .filter_map(|(k, v)| arg_values(v).map(|values| (k, values)))
         .collect();
 
-    let action_id: String = args.get("actionId").unwrap().first().unwrap().clone().try_into().unwrap();
-    args.remove("actionId");
+    let action_id: String = match args.remove("actionId").as_deref() {
+        Some([StateVarValue::String(action_id)]) => action_id.clone(),
+        Some([StateVarValue::Integer(action_id)]) => action_id.to_string(),
+        _ => return Err("Action has no actionId".to_string()),
+    };
+
+    let transient = match args.remove("transient").as_deref() {
+        Some([StateVarValue::Boolean(transient)]) => *transient,
+        _ => false,
+    };
+
+    Ok((Action { component_name, action_name, args, transient }, action_id))
+}
 
-    Ok((Action { component_name, action_name, args}, action_id))
+/// The values of an action argument. Nested lists are flattened, so `[[1, 2], [3, 4]]`
+/// gives the same values as `[1, 2, 3, 4]`. An object keyed by index, like the
+/// `pointCoords` of a single moved vertex `{"1": [3, 4]}`, gives the values of that list
+/// with nulls at the other indices. Other objects, like `sourceInformation`, have no values.
+fn arg_values(value: serde_json::Value) -> Option<Vec<StateVarValue>> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => Some(vec![StateVarValue::Null]),
+        Value::Bool(v) => Some(vec![StateVarValue::Boolean(v)]),
+        Value::Number(v) => Some(vec![v.into()]),
+        Value::String(v) => Some(vec![StateVarValue::String(v)]),
+        Value::Array(list) => list.into_iter()
+            .map(arg_values)
+            .collect::<Option<Vec<_>>>()
+            .map(|values| values.concat()),
+        Value::Object(map) => {
+            let entries = map.into_iter()
+                .map(|(key, value)| Some((key.parse::<usize>().ok()?, arg_values(value)?)))
+                .collect::<Option<Vec<(usize, Vec<StateVarValue>)>>>()?;
+
+            let width = entries.iter().map(|(_, values)| values.len()).max()?;
+            let length = entries.iter().map(|(index, _)| index + 1).max()?;
+
+            let mut flattened = vec![StateVarValue::Null; width * length];
+            for (index, values) in entries {
+                for (offset, value) in values.into_iter().enumerate() {
+                    flattened[index * width + offset] = value;
+                }
+            }
+            Some(flattened)
+        },
+    }
 }
 
 
@@ -977,14 +1370,3 @@ impl From<serde_json::Number> for StateVarValue {
          }
     }
 }
-
-impl From<ArgValue> for Vec<StateVarValue> {
-    fn from(value: ArgValue) -> Self {
-         match value {
-             ArgValue::Bool(v) => vec![StateVarValue::Boolean(v)],
-             ArgValue::String(v) => vec![StateVarValue::String(v)],
-             ArgValue::Number(v) => vec![v.into()],
-             ArgValue::NumberArray(v) =>  v.into_iter().map(|v| v.into()).collect(),
-         }
-    }
-}
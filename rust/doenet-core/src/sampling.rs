@@ -0,0 +1,240 @@
+use evalexpr::{build_operator_tree, ContextWithMutableFunctions, ContextWithMutableVariables,
+    Function, HashMapContext, Node, Value};
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+
+
+/// A function of one variable given by a formula, such as `x^2 - 1`.
+/// State vars carry functions as JSON strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MathFunction {
+    pub variable: String,
+    pub formula: String,
+}
+
+impl MathFunction {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// The function as a closure that is NaN wherever the formula cannot be evaluated.
+    /// None when the formula does not parse.
+    pub fn compile(&self) -> Option<impl Fn(f64) -> f64> {
+        let tree: Node = build_operator_tree(&self.formula).ok()?;
+        let variable = self.variable.clone();
+        let context = std::cell::RefCell::new(math_context());
+
+        Some(move |t: f64| {
+            let mut context = context.borrow_mut();
+            context.set_value(variable.clone(), Value::Float(t)).unwrap();
+            tree.eval_number_with_context(&*context).unwrap_or(f64::NAN)
+        })
+    }
+}
+
+type RealFunction = fn(f64) -> f64;
+
+/// Common functions and constants that can be used in formulas
+fn math_context() -> HashMapContext {
+    let mut context = HashMapContext::new();
+
+    let functions: [(&str, RealFunction); 9] = [
+        ("sin", f64::sin),
+        ("cos", f64::cos),
+        ("tan", f64::tan),
+        ("exp", f64::exp),
+        ("ln", f64::ln),
+        ("log", f64::ln),
+        ("sqrt", f64::sqrt),
+        ("abs", f64::abs),
+        ("atan", f64::atan),
+    ];
+    for (name, f) in functions {
+        context.set_function(name.to_string(), Function::new(move |argument| {
+            Ok(Value::Float(f(argument.as_number()?)))
+        })).unwrap();
+    }
+
+    context.set_value("pi".to_string(), Value::Float(std::f64::consts::PI)).unwrap();
+    context.set_value("e".to_string(), Value::Float(std::f64::consts::E)).unwrap();
+
+    context
+}
+
+
+/// Intervals of the first pass of sampling
+const INITIAL_INTERVALS: usize = 40;
+
+/// Each interval of the first pass is halved at most this many times
+const MAX_SUBDIVISIONS: usize = 6;
+
+/// How far, relative to the range of values, a midpoint can be
+/// from the straight line between its neighbors
+const RELATIVE_TOLERANCE: f64 = 1e-3;
+
+
+/// Sample `f` on `[min, max]`, with more samples where it curves or is discontinuous.
+/// Returns the parameter values and the values of `f` at them.
+pub fn sample_adaptively(f: impl Fn(f64) -> Vec<f64>, min: f64, max: f64) -> (Vec<f64>, Vec<Vec<f64>>) {
+    // Also when either is NaN
+    if min.partial_cmp(&max) != Some(std::cmp::Ordering::Less) {
+        return (vec![], vec![])
+    }
+
+    let initial_ts: Vec<f64> = (0..=INITIAL_INTERVALS)
+        .map(|i| min + (max - min) * i as f64 / INITIAL_INTERVALS as f64)
+        .collect();
+    let initial_values: Vec<Vec<f64>> = initial_ts.iter().map(|&t| f(t)).collect();
+
+    // The scale of each component, so that the tolerance does not depend on units
+    let num_components = initial_values.first().map_or(0, Vec::len);
+    let tolerances: Vec<f64> = (0..num_components).map(|c| {
+        let finite = initial_values.iter().map(|v| v[c]).filter(|v| v.is_finite());
+        let (low, high) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(l, h), v| (l.min(v), h.max(v)));
+        let range = if low <= high { high - low } else { 0.0 };
+        RELATIVE_TOLERANCE * range.max(1.0)
+    }).collect();
+
+    let mut ts = vec![initial_ts[0]];
+    let mut values = vec![initial_values[0].clone()];
+    for i in 0..INITIAL_INTERVALS {
+        subdivide(
+            &f, &tolerances,
+            (initial_ts[i], &initial_values[i]),
+            (initial_ts[i + 1], &initial_values[i + 1]),
+            MAX_SUBDIVISIONS,
+            &mut ts, &mut values,
+        );
+    }
+
+    (ts, values)
+}
+
+/// Push the samples in `(start, end]`
+fn subdivide(
+    f: &impl Fn(f64) -> Vec<f64>,
+    tolerances: &[f64],
+    start: (f64, &Vec<f64>),
+    end: (f64, &Vec<f64>),
+    depth: usize,
+    ts: &mut Vec<f64>,
+    values: &mut Vec<Vec<f64>>,
+) {
+    if depth > 0 {
+        let mid_t = (start.0 + end.0) / 2.0;
+        let mid = f(mid_t);
+
+        let is_straight = mid.iter().zip(start.1).zip(end.1).zip(tolerances)
+            .all(|(((m, s), e), tolerance)| {
+                match (m.is_finite(), s.is_finite(), e.is_finite()) {
+                    (true, true, true) => (m - (s + e) / 2.0).abs() <= *tolerance,
+                    (false, false, false) => true,
+                    // Look closer at where the function stops being defined
+                    _ => false,
+                }
+            });
+
+        if !is_straight {
+            subdivide(f, tolerances, start, (mid_t, &mid), depth - 1, ts, values);
+            subdivide(f, tolerances, (mid_t, &mid), end, depth - 1, ts, values);
+            return;
+        }
+    }
+
+    ts.push(end.0);
+    values.push(end.1.clone());
+}
+
+
+/// A definition the renderer turns into a function by interpolating
+/// linearly between the samples
+pub fn interpolated_function_definition(xs: &[f64], ys: &[f64]) -> serde_json::Value {
+    if xs.len() < 2 {
+        return json!({
+            "functionType": "interpolated",
+            "xs": null,
+            "coeffs": null,
+            "interpolationPoints": null,
+            "domain": null,
+        })
+    }
+
+    let coeffs: Vec<[f64; 4]> = xs.windows(2).zip(ys.windows(2))
+        .map(|(x, y)| [y[0], (y[1] - y[0]) / (x[1] - x[0]), 0.0, 0.0])
+        .collect();
+
+    json!({
+        "functionType": "interpolated",
+        "xs": xs,
+        "coeffs": coeffs,
+        "interpolationPoints": null,
+        "domain": null,
+    })
+}
+
+
+/// The control vectors of a Bézier curve through the points when none are given.
+/// For each point, the vectors to the control points before and after it.
+/// The curve passes through each point in the direction from the previous point to the next.
+pub fn default_control_vectors(through_points: &[[f64; 2]]) -> Vec<[[f64; 2]; 2]> {
+    let n = through_points.len();
+    (0..n).map(|i| {
+        let (before, after, scale) = match i {
+            _ if n < 2 => return [[0.0, 0.0], [0.0, 0.0]],
+            0 => (through_points[0], through_points[1], 1.0 / 3.0),
+            _ if i == n - 1 => (through_points[n - 2], through_points[n - 1], 1.0 / 3.0),
+            _ => (through_points[i - 1], through_points[i + 1], 1.0 / 6.0),
+        };
+        let next = [(after[0] - before[0]) * scale, (after[1] - before[1]) * scale];
+        [[-next[0], -next[1]], next]
+    }).collect()
+}
+
+/// The cubic coefficients of each segment of a Bézier curve, for each coordinate.
+/// The parameter runs from 0 to 1 along each segment.
+pub fn bezier_spline_coeffs(
+    through_points: &[[f64; 2]],
+    control_vectors: &[[[f64; 2]; 2]],
+) -> Vec<[[f64; 4]; 2]> {
+    through_points.windows(2).zip(control_vectors.windows(2))
+        .map(|(points, vectors)| {
+            let coeffs = |c: usize| {
+                let p0 = points[0][c];
+                let p3 = points[1][c];
+                let c1 = p0 + vectors[0][1][c];
+                let c2 = p3 + vectors[1][0][c];
+                [
+                    p0,
+                    3.0 * (c1 - p0),
+                    3.0 * (p0 - 2.0 * c1 + c2),
+                    p3 - p0 + 3.0 * (c1 - c2),
+                ]
+            };
+            [coeffs(0), coeffs(1)]
+        })
+        .collect()
+}
+
+/// The definitions the renderer turns into the x and y functions of a Bézier curve
+pub fn bezier_function_definitions(
+    through_points: &[[f64; 2]],
+    control_vectors: &[[[f64; 2]; 2]],
+) -> Vec<serde_json::Value> {
+    let spline_coeffs = bezier_spline_coeffs(through_points, control_vectors);
+
+    (0..2).map(|component| json!({
+        "functionType": "bezier",
+        "numThroughPoints": through_points.len(),
+        "numericalThroughPoints": through_points,
+        "splineCoeffs": spline_coeffs,
+        "extrapolateForward": false,
+        "extrapolateForwardCoeffs": null,
+        "extrapolateBackward": false,
+        "extrapolateBackwardCoeffs": null,
+        "component": component,
+    })).collect()
+}
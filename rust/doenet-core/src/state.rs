@@ -1,10 +1,10 @@
 use enum_as_inner::EnumAsInner;
-use serde::{ser::SerializeStruct, Serialize};
+use serde::{ser::SerializeStruct, Serialize, Deserialize};
 
-use crate::{state_variables::*, Instance, InstanceGroup};
+use crate::{state_variables::*, math_expression::MathExpression, Instance, InstanceGroup};
 use std::{cell::{RefCell, RefMut, Ref}, fmt, cmp::max, iter::repeat};
 use self::State::*;
-use ndarray::{ArrayD, SliceInfoElem};
+use ndarray::{ArrayD, Dimension, SliceInfoElem};
 
 #[derive(Clone)]
 pub struct StateVar {
@@ -21,12 +21,14 @@ pub struct StateVar {
 /// which protects state variables from changing type.
 /// We have to store the State enum *inside* each variant
 /// so that the type is retained even when the content is Stale.
+/// A resolved None is a null value of that type.
 #[derive(Clone, Debug)]
 enum ValueTypeProtector {
-    String(State<String>),
-    Boolean(State<bool>),
-    Integer(State<i64>),
-    Number(State<f64>),
+    String(State<Option<String>>),
+    Boolean(State<Option<bool>>),
+    Integer(State<Option<i64>>),
+    Number(State<Option<f64>>),
+    MathExpr(State<Option<MathExpression>>),
 }
 
 
@@ -170,7 +172,9 @@ pub enum StateForStateVar {
     Array {
         size: ForEachMap<StateVar>,
         stale_resize: StateVar,
-        elements: ForEachMap<Vec<StateVar>>,
+        /// Stored in row-major order, and boxed so that arrays do not make every state much larger
+        elements: Box<ForEachMap<Vec<StateVar>>>,
+        inner_dimensions: &'static [usize],
     }
 }
 
@@ -192,28 +196,25 @@ impl StateForStateVar {
             StateVarVariant::String(_) =>  Self::Single(ForEachMap::new(StateVar {
                 value_type_protector: RefCell::new(ValueTypeProtector::String(Stale))
             }, inside_maps)),
-            StateVarVariant::NumberArray(_) => {
-                Self::Array {
-                    size: ForEachMap::new(StateVar {
-                        value_type_protector: RefCell::new(ValueTypeProtector::Integer(Stale)),
-                    }, inside_maps),
-                    stale_resize: StateVar {
-                        value_type_protector: RefCell::new(ValueTypeProtector::Number(Stale)),
-                    },
-                    elements: ForEachMap::new(vec![], inside_maps)
-                }
-            }
-            StateVarVariant::StringArray(_) => {
-                Self::Array {
-                    size: ForEachMap::new(StateVar {
-                        value_type_protector: RefCell::new(ValueTypeProtector::Integer(Stale)),
-                    }, inside_maps),
-                    stale_resize: StateVar {
-                        value_type_protector: RefCell::new(ValueTypeProtector::String(Stale)),
-                    },
-                    elements: ForEachMap::new(vec![], inside_maps)
-                }
-            }
+            StateVarVariant::NumberArray(_) => Self::new_array(ValueTypeProtector::Number(Stale), value_type.inner_dimensions(), inside_maps),
+            StateVarVariant::StringArray(_) => Self::new_array(ValueTypeProtector::String(Stale), value_type.inner_dimensions(), inside_maps),
+            StateVarVariant::IntegerArray(_) => Self::new_array(ValueTypeProtector::Integer(Stale), value_type.inner_dimensions(), inside_maps),
+            StateVarVariant::BooleanArray(_) => Self::new_array(ValueTypeProtector::Boolean(Stale), value_type.inner_dimensions(), inside_maps),
+            StateVarVariant::MathArray(_) => Self::new_array(ValueTypeProtector::MathExpr(Stale), value_type.inner_dimensions(), inside_maps),
+        }
+    }
+
+    /// Stale array whose elements have the type of the given protector
+    fn new_array(element_type: ValueTypeProtector, inner_dimensions: &'static [usize], inside_maps: usize) -> Self {
+        Self::Array {
+            size: ForEachMap::new(StateVar {
+                value_type_protector: RefCell::new(ValueTypeProtector::Integer(Stale)),
+            }, inside_maps),
+            stale_resize: StateVar {
+                value_type_protector: RefCell::new(element_type),
+            },
+            elements: Box::new(ForEachMap::new(vec![], inside_maps)),
+            inner_dimensions,
         }
     }
 
@@ -229,15 +230,16 @@ impl StateForStateVar {
                     _ => Err(format!("Tried to access a non-array State with an index or a size")),
                 }
             },
-            Self::Array { size, elements, .. } => {
+            Self::Array { size, elements, inner_dimensions, .. } => {
                 match sv_ref {
                     StateIndex::SizeOf => Ok(Some(size.instance(map).get_state())),
-                    StateIndex::Element(id) => {
-                        if *id == 0 {
-                            Ok(None)
-                        } else {
-                            let internal_id = id - 1;
-                            Ok(elements.instance(map).get(internal_id).map(|elem| elem.get_state()))
+                    StateIndex::Element(index) => {
+                        match element_id(index, inner_dimensions) {
+                            None => Ok(None),
+                            Some(id) => {
+                                let internal_id = id - 1;
+                                Ok(elements.instance(map).get(internal_id).map(|elem| elem.get_state()))
+                            }
                         }
                     },
                     _ => Err(format!("Tried to access an array State without an index or a size")),
@@ -254,16 +256,17 @@ impl StateForStateVar {
         match self {
             Self::Single(sv) => sv.instance(map).set_value(val).map(|new_val| Some(new_val)),
 
-            Self::Array { size, elements, stale_resize } => match state_var_ref {
-                StateIndex::Element(id) => {
-                    if *id == 0 {
-                        Ok(None)
-                    } else {
-                        let internal_id = id - 1;
-                        if let Some(element) = elements.instance(map).get(internal_id) {
-                            element.set_value(val).map(|new_val| Some(new_val))
-                        } else {
-                            Ok(None)
+            Self::Array { size, elements, stale_resize, inner_dimensions } => match state_var_ref {
+                StateIndex::Element(index) => {
+                    match element_id(index, inner_dimensions) {
+                        None => Ok(None),
+                        Some(id) => {
+                            let internal_id = id - 1;
+                            if let Some(element) = elements.instance(map).get(internal_id) {
+                                element.set_value(val).map(|new_val| Some(new_val))
+                            } else {
+                                Ok(None)
+                            }
                         }
                     }
                 }
@@ -276,7 +279,7 @@ impl StateForStateVar {
                     
                     if new_size.is_ok() {
                         elements.instance_mut(map).resize(
-                            i64::try_from(val).unwrap() as usize,
+                            elements_len(i64::try_from(val).unwrap() as usize, inner_dimensions),
                             stale_resize.clone(),
                         );
                     }
@@ -326,14 +329,16 @@ impl StateForStateVar {
                     let elements = elements.instance(map);
                     Ok((0..elements.len()).map(|i| elements.get(i).unwrap().get_state().is_stale()).reduce(|a,b| a || b).unwrap_or(false))
                 }
-            (Self::Array { elements, .. }, StateVarSlice::Single(StateRef::ArrayElement(_, id))) => {
-                if *id == 0 {
-                    Err("requested id 0".into())
-                } else {
+            (Self::Array { elements, inner_dimensions, .. }, StateVarSlice::Single(StateRef::ArrayElement(_, index))) => {
+                if let Some(id) = element_id(index, inner_dimensions) {
                     let internal_id = id - 1;
-                    elements.instance(map).get(internal_id)
+                    // An element past the end of the array has never been resolved,
+                    // for instance right after an action shrinks or grows the array
+                    Ok(elements.instance(map).get(internal_id)
                         .map(|elem| elem.get_state().is_stale())
-                        .ok_or("index out of range".into())
+                        .unwrap_or(true))
+                } else {
+                    Err(format!("requested index {:?} outside of the dimensions", index))
                 }
             },
             _ => panic!(),
@@ -354,10 +359,9 @@ impl StateForStateVar {
                     i.mark_stale()
                 }
             }
-            (Self::Array { elements, .. }, StateVarSlice::Single(StateRef::ArrayElement(_, id))) => {
-                if *id == 0 {
-                    panic!("Invalid index 0")
-                }
+            (Self::Array { elements, inner_dimensions, .. }, StateVarSlice::Single(StateRef::ArrayElement(_, index))) => {
+                let id = element_id(index, inner_dimensions)
+                    .unwrap_or_else(|| panic!("Invalid index {:?}", index));
                 let internal_id = id - 1;
                 elements.instance(map).get(internal_id).unwrap().mark_stale()
             },
@@ -372,6 +376,30 @@ impl StateForStateVar {
         }
     }
 
+    /// The most elements any instance of the group has.
+    pub fn elements_len_in_instance_group(&self, instance_group: &InstanceGroup) -> usize {
+        match self {
+            Self::Single(_) => 0,
+            Self::Array { elements, .. } => elements.instances_existing_in_instance_group(instance_group)
+                .iter()
+                .map(|instance| elements.instance(instance).len())
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Drop the elements of every instance of the group so that a removed map
+    /// instance does not hold on to them. The size is left stale, which resizes
+    /// the elements again if the instance comes back.
+    pub fn release_instance_group(&self, instance_group: &InstanceGroup) {
+        if let Self::Array { size, elements, .. } = self {
+            for instance in elements.instances_existing_in_instance_group(instance_group) {
+                size.instance(&instance).mark_stale();
+                elements.instance_mut(&instance).clear();
+            }
+        }
+    }
+
 }
 
 
@@ -394,6 +422,7 @@ impl StateVar {
             ValueTypeProtector::Boolean(_) => ValueTypeProtector::Boolean(Stale),
             ValueTypeProtector::Number(_)  => ValueTypeProtector::Number(Stale),
             ValueTypeProtector::Integer(_) => ValueTypeProtector::Integer(Stale),
+            ValueTypeProtector::MathExpr(_) => ValueTypeProtector::MathExpr(Stale),
         }
     }
 
@@ -404,19 +433,28 @@ impl StateVar {
 
         match type_protector {
             ValueTypeProtector::String(value_option) => match value_option {
-                Resolved(val) => Resolved(StateVarValue::String(val.clone())),
+                Resolved(Some(val)) => Resolved(StateVarValue::String(val.clone())),
+                Resolved(None) => Resolved(StateVarValue::Null),
                 Stale => Stale
             },
             ValueTypeProtector::Number(value_option) => match value_option {
-                Resolved(val) => Resolved(StateVarValue::Number(val.clone())),
+                Resolved(Some(val)) => Resolved(StateVarValue::Number(*val)),
+                Resolved(None) => Resolved(StateVarValue::Null),
                 Stale => Stale
             },
             ValueTypeProtector::Boolean(value_option) => match value_option {
-                Resolved(val) => Resolved(StateVarValue::Boolean(val.clone())),
+                Resolved(Some(val)) => Resolved(StateVarValue::Boolean(*val)),
+                Resolved(None) => Resolved(StateVarValue::Null),
                 Stale => Stale
             },
             ValueTypeProtector::Integer(value_option) => match value_option {
-                Resolved(val) => Resolved(StateVarValue::Integer(val.clone())),
+                Resolved(Some(val)) => Resolved(StateVarValue::Integer(*val)),
+                Resolved(None) => Resolved(StateVarValue::Null),
+                Stale => Stale
+            },
+            ValueTypeProtector::MathExpr(value_option) => match value_option {
+                Resolved(Some(val)) => Resolved(StateVarValue::MathExpr(val.clone())),
+                Resolved(None) => Resolved(StateVarValue::Null),
                 Stale => Stale
             }
         }
@@ -436,7 +474,8 @@ impl StateVar {
 
 /// A special endpoint on the dependency graph which is associated with a
 /// particular state var. Actions often update these.
-/// An EssentialStateVar cannot be stale so it does not need a ValueTypeProtector
+/// An EssentialStateVar cannot be stale so it does not need a ValueTypeProtector.
+/// Essential arrays are one-dimensional.
 #[derive(Clone)]
 pub enum EssentialStateVar {
     Single(ForEachMap<StateVarValue>),
@@ -487,8 +526,8 @@ impl EssentialStateVar {
             },
 
             (Self::Array{size, elements, extension }, StateIndex::SizeOf) => {
-                let new_len = max(size.instance(map).clone(), usize::try_from(new_value.clone()).expect(
-                    &format!("tried to set essential size to {}", new_value))
+                let new_len = usize::try_from(new_value.clone()).unwrap_or_else(|_|
+                    panic!("tried to set essential size to {}", new_value)
                 );
                 elements.instance_mut(map).resize(new_len, extension.clone());
 
@@ -497,11 +536,11 @@ impl EssentialStateVar {
                 Ok(())
             },
 
-            (Self::Array{elements, extension, ..}, StateIndex::Element(id)) => {
-                
-                if id == 0 {
+            (Self::Array{elements, extension, ..}, StateIndex::Element(index)) => {
+
+                let Some(id) = element_id(&index, &[]) else {
                     return Err("Index out of range".into())
-                }
+                };
 
                 let internal_id = id - 1;
 
@@ -519,18 +558,16 @@ impl EssentialStateVar {
 
 
     pub fn get_value(&self, state_index: StateIndex, map: &Instance) -> Option<StateVarValue> {
-        match (self, state_index) {
+        match (self, &state_index) {
             (Self::Single(v), StateIndex::Basic) => {
                 Some(v.instance(map).clone())
             },
             (Self::Array{size, .. }, StateIndex::SizeOf) => {
                 Some(StateVarValue::Integer(size.instance(map).clone() as i64))
             },
-            (Self::Array{elements, ..}, StateIndex::Element(id)) => {
+            (Self::Array{elements, ..}, StateIndex::Element(index)) => {
 
-                if id == 0 {
-                    return None
-                }
+                let id = element_id(index, &[])?;
 
                 let internal_id = id - 1;
 
@@ -554,6 +591,117 @@ impl EssentialStateVar {
 
 
 
+// Saving essential data between visits
+
+/// The value of one instance of essential data, as it is saved
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SavedEssentialValue {
+    Single(SavedValue),
+    Array {
+        size: usize,
+        elements: Vec<SavedValue>,
+    },
+}
+
+/// A StateVarValue that keeps its type when saved as JSON
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SavedValue {
+    String(String),
+    /// NaN is saved as null
+    Number(Option<f64>),
+    Integer(i64),
+    Boolean(bool),
+    #[serde(rename_all = "camelCase")]
+    MathExpr {
+        expression: String,
+        variable_prefix: String,
+        external_variables_count: usize,
+    },
+    Null,
+}
+
+impl From<&StateVarValue> for SavedValue {
+    fn from(value: &StateVarValue) -> Self {
+        match value {
+            StateVarValue::String(v) => Self::String(v.clone()),
+            StateVarValue::Number(v) => Self::Number(Some(*v).filter(|v| !v.is_nan())),
+            StateVarValue::Integer(v) => Self::Integer(*v),
+            StateVarValue::Boolean(v) => Self::Boolean(*v),
+            StateVarValue::MathExpr(v) => Self::MathExpr {
+                expression: v.tree.to_string(),
+                variable_prefix: v.variable_prefix.clone(),
+                external_variables_count: v.external_variables_count,
+            },
+            StateVarValue::Null => Self::Null,
+        }
+    }
+}
+
+impl TryFrom<SavedValue> for StateVarValue {
+    type Error = String;
+    fn try_from(value: SavedValue) -> Result<Self, Self::Error> {
+        Ok(match value {
+            SavedValue::String(v) => StateVarValue::String(v),
+            SavedValue::Number(v) => StateVarValue::Number(v.unwrap_or(f64::NAN)),
+            SavedValue::Integer(v) => StateVarValue::Integer(v),
+            SavedValue::Boolean(v) => StateVarValue::Boolean(v),
+            SavedValue::MathExpr { expression, variable_prefix, external_variables_count } => {
+                let tree = evalexpr::build_operator_tree(&expression)
+                    .map_err(|err| format!("invalid saved expression {}: {}", expression, err))?;
+                StateVarValue::MathExpr(MathExpression { tree, variable_prefix, external_variables_count })
+            },
+            SavedValue::Null => StateVarValue::Null,
+        })
+    }
+}
+
+impl EssentialStateVar {
+
+    /// Every instance of this essential data, with its value as it is saved
+    pub fn saved_instances(&self) -> Vec<(Instance, SavedEssentialValue)> {
+        let instance_of = |index: ndarray::IxDyn| -> Instance {
+            index.slice().iter().map(|i| i + 1).collect()
+        };
+
+        match self {
+            Self::Single(values) => values.all_instances().indexed_iter()
+                .map(|(index, value)| (instance_of(index), SavedEssentialValue::Single(value.into())))
+                .collect(),
+            Self::Array { size, elements, .. } => elements.all_instances().indexed_iter()
+                .map(|(index, elements)| {
+                    let instance = instance_of(index);
+                    let saved = SavedEssentialValue::Array {
+                        size: *size.instance(&instance),
+                        elements: elements.iter().map(SavedValue::from).collect(),
+                    };
+                    (instance, saved)
+                })
+                .collect(),
+        }
+    }
+
+    /// Set one instance to a saved value, which has to have the type of this essential data
+    pub fn load_saved_instance(&self, instance: &Instance, saved: SavedEssentialValue) -> Result<(), String> {
+        match (self, saved) {
+            (Self::Single(_), SavedEssentialValue::Single(value)) => {
+                self.set_value(StateIndex::Basic, value.try_into()?, instance)
+            },
+            (Self::Array { .. }, SavedEssentialValue::Array { size, elements }) => {
+                self.set_value(StateIndex::SizeOf, StateVarValue::Integer(size as i64), instance)?;
+                for (i, value) in elements.into_iter().enumerate() {
+                    self.set_value(StateIndex::Element(vec![i + 1]), value.try_into()?, instance)?;
+                }
+                Ok(())
+            },
+            (_, saved) => Err(format!("saved value {:?} does not match essential data {:?}", saved, self)),
+        }
+    }
+}
+
+
+
 // Boilerplate to display EssentialStateVar and StateVar better
 
 impl fmt::Debug for StateVar {
@@ -628,11 +776,24 @@ impl StateVarValue {
             },
             StateVarValue::MathExpr(state) => {
                 *state = new_value.try_into()?;
+            },
+            StateVarValue::Null => {
+                *self = new_value;
             }
         }
 
         Ok(())
     }
+
+    /// Null becomes None, otherwise the value must convert to the type
+    fn try_into_option<T>(self) -> Result<Option<T>, String>
+        where T: TryFrom<StateVarValue, Error = &'static str>
+    {
+        match self {
+            StateVarValue::Null => Ok(None),
+            value => Ok(Some(value.try_into()?)),
+        }
+    }
 }
 
 impl ValueTypeProtector {
@@ -641,16 +802,19 @@ impl ValueTypeProtector {
 
         match self {
             ValueTypeProtector::String(state) => {                
-                *state = Resolved(new_value.clone().try_into()?);
+                *state = Resolved(new_value.clone().try_into_option()?);
             },
             ValueTypeProtector::Integer(state) => {
-                *state = Resolved(new_value.clone().try_into()?);
+                *state = Resolved(new_value.clone().try_into_option()?);
             },
             ValueTypeProtector::Number(state) => {
-                *state = Resolved(new_value.clone().try_into()?);
+                *state = Resolved(new_value.clone().try_into_option()?);
             },
             ValueTypeProtector::Boolean(state) => {
-                *state = Resolved(new_value.clone().try_into()?);
+                *state = Resolved(new_value.clone().try_into_option()?);
+            },
+            ValueTypeProtector::MathExpr(state) => {
+                *state = Resolved(new_value.clone().try_into_option()?);
             }
         }
 
@@ -40,6 +40,9 @@ pub struct StateVarDefinition<T> {
 
     pub for_renderer: bool,
 
+    /// Reshapes the JSON of the value before it is sent to the renderer
+    pub reshape_for_renderer: fn(serde_json::Value) -> serde_json::Value,
+
     /// Determines whether to use essential data
     pub initial_essential_value: T,
 
@@ -93,27 +96,81 @@ pub struct StateVarArrayDefinition<T> {
 
     pub for_renderer: bool,
 
+    /// Reshapes the JSON of the (nested) array before it is sent to the renderer
+    pub reshape_for_renderer: fn(serde_json::Value) -> serde_json::Value,
+
     pub initial_essential_element_value: T,
 
+    /// The size of the essential data made for an attribute that was not specified
+    pub initial_essential_size: usize,
+
+    /// The sizes of the dimensions after the first, which do not change.
+    /// The size of the first dimension is the array's size.
+    pub inner_dimensions: &'static [usize],
+
 }
 
 
 
+/// The (1-based) index of an array element in each dimension of its array
+pub type ArrayIndex = Vec<usize>;
+
 /// A single value
 #[derive(Debug, PartialEq, Eq, Hash, Clone, serde::Serialize, enum_as_inner::EnumAsInner)]
 pub enum StateRef {
     Basic(StateVarName),
-    ArrayElement(StateVarName, usize),
+    ArrayElement(StateVarName, ArrayIndex),
     SizeOf(StateVarName),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum StateIndex {
     Basic,
-    Element(usize),
+    Element(ArrayIndex),
     SizeOf,
 }
 
+/// The elements of an array are stored in row-major order.
+/// This is the (1-based) id of an element in that order,
+/// or None when the index is outside of the dimensions.
+pub fn element_id(index: &[usize], inner_dimensions: &[usize]) -> Option<usize> {
+    let (first, rest) = index.split_first()?;
+    if *first == 0 || rest.len() != inner_dimensions.len() {
+        return None;
+    }
+
+    let mut id = first - 1;
+    for (i, dimension) in rest.iter().zip(inner_dimensions) {
+        if *i == 0 || i > dimension {
+            return None;
+        }
+        id = id * dimension + (i - 1);
+    }
+    Some(id + 1)
+}
+
+/// The inverse of `element_id`
+pub fn element_index(id: usize, inner_dimensions: &[usize]) -> ArrayIndex {
+    let mut rest = id - 1;
+    let mut index = vec![0; inner_dimensions.len() + 1];
+    for (i, dimension) in inner_dimensions.iter().enumerate().rev() {
+        index[i + 1] = rest % dimension + 1;
+        rest /= dimension;
+    }
+    index[0] = rest + 1;
+    index
+}
+
+/// The number of elements in an array whose first dimension has the given size
+pub fn elements_len(size: usize, inner_dimensions: &[usize]) -> usize {
+    size * inner_dimensions.iter().product::<usize>()
+}
+
+/// The index of every element of an array, in row-major order
+pub fn array_indices(size: usize, inner_dimensions: &'static [usize]) -> impl Iterator<Item = ArrayIndex> {
+    (1..=elements_len(size, inner_dimensions)).map(|id| element_index(id, inner_dimensions))
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, serde::Serialize, enum_as_inner::EnumAsInner)]
 pub enum StateVarSlice {
     Single(StateRef),
@@ -158,7 +215,7 @@ impl StateRef {
     pub fn index(&self) -> StateIndex {
         match self {
             Self::Basic(_) => StateIndex::Basic,
-            Self::ArrayElement(_, i) => StateIndex::Element(*i),
+            Self::ArrayElement(_, i) => StateIndex::Element(i.clone()),
             Self::SizeOf(_) => StateIndex::SizeOf,
         }
     }
@@ -187,6 +244,7 @@ impl<T> Default for StateVarDefinition<T>
             determine_state_var_from_dependencies:
                 |_| Ok(StateVarUpdateInstruction::SetValue(T::default())),
             for_renderer: false,
+            reshape_for_renderer: |value| value,
             initial_essential_value: T::default(),
 
             request_dependencies_to_update_value: |_, _| {
@@ -218,8 +276,11 @@ impl<T> Default for StateVarArrayDefinition<T>
                 log!("DEFAULT REQUEST_SIZE_DEPENDENCIES_TO_UPDATE_VALUE DOES NOTHING");
                 HashMap::new()
             },
-            for_renderer: false, 
+            for_renderer: false,
+            reshape_for_renderer: |value| value,
             initial_essential_element_value: T::default(),
+            initial_essential_size: 0,
+            inner_dimensions: &[],
 
         }
     }
@@ -240,6 +301,9 @@ pub enum StateVarVariant {
     Integer(StateVarDefinition<i64>),
     StringArray(StateVarArrayDefinition<String>),
     NumberArray(StateVarArrayDefinition<f64>),
+    IntegerArray(StateVarArrayDefinition<i64>),
+    BooleanArray(StateVarArrayDefinition<bool>),
+    MathArray(StateVarArrayDefinition<MathExpression>),
     // Single(StateVarVariantSingle),
     // Array(StateVarVariantArray),
 }
@@ -269,6 +333,8 @@ pub enum StateVarValue {
     Integer(i64),
     Boolean(bool),
     MathExpr(MathExpression),
+    /// An absent value of any type, which renderers receive as null
+    Null,
 }
 
 
@@ -296,10 +362,33 @@ pub enum DependencyInstruction {
     Parent {
         state_var: StateVarName,
     },
+    /// Like Parent, but with no values when the parent is not of the type
+    ParentOfType {
+        component_type: ComponentType,
+        state_var: StateVarName,
+    },
+    /// A state var of the root <document>, for settings that apply to the whole core
+    Document {
+        state_var: StateVarName,
+    },
+    /// A state var of every component of a type in the document, in document order.
+    /// Components inside maps are left out.
+    ComponentsOfType {
+        component_type: ComponentType,
+        state_var: StateVarName,
+    },
     Attribute {
         attribute_name: AttributeName,
         index: StateIndex,
     },
+    /// A state var of the component in an element of an attribute, like `$A` in `through="$A $B"`.
+    /// No values when the element is not a component, or is one with a primary input
+    /// state var, which DependencyInstruction::Attribute reads instead.
+    AttributeComponent {
+        attribute_name: AttributeName,
+        index: usize,
+        state_var: StateVarSlice,
+    },
     Essential {
         /// Use the string of this attribute
         prefill: Option<AttributeName>,
@@ -317,6 +406,8 @@ pub enum DependencyInstruction {
 #[derive(Debug)]
 pub enum StateVarUpdateInstruction<T> {
     SetValue(T),
+    /// The state var has no value
+    SetNull,
     NoChange,
 }
 
@@ -373,19 +464,27 @@ pub trait DepValueVec {
     fn has_zero_or_one_elements(&self) -> Result<(Option<&DependencyValue>, InstructionName), String>;
     fn has_exactly_one_element(&self) -> Result<(&DependencyValue, InstructionName), String>;
 
-    fn into_string_list(&self) -> Result<Vec<String>, String>;
+    fn into_string_list(self) -> Result<Vec<String>, String>;
+
+    fn into_number_list(self) -> Result<Vec<f64>, String>;
 
-    fn into_number_list(&self) -> Result<Vec<f64>, String>;
+    fn into_integer_list(self) -> Result<Vec<i64>, String>;
+
+    fn into_bool_list(self) -> Result<Vec<bool>, String>;
+
+    fn into_math_expression_list(self) -> Result<Vec<MathExpression>, String>;
 
     fn filter_include_component_type(&self, component_type: &ComponentType) -> (Vec<&DependencyValue>, InstructionName);
 }
 
 impl DepValueVec for (Vec<&DependencyValue>, InstructionName) {
 
+   /// A null value counts as no element
    fn has_zero_or_one_elements(&self) -> Result<(Option<&DependencyValue>, InstructionName), String> {
         let (dep_values, name) = self;
         match dep_values.len() {
             0 => Ok((None, name)),
+            1 if dep_values[0].value == StateVarValue::Null => Ok((None, name)),
             1 => Ok((Some(&dep_values[0]), name)),
             _ => Err(format!("Expected instruction [{}] to have zero or one elements", name))
         }
@@ -401,7 +500,7 @@ impl DepValueVec for (Vec<&DependencyValue>, InstructionName) {
         }
     }
 
-    fn into_string_list(&self) -> Result<Vec<String>, String> {
+    fn into_string_list(self) -> Result<Vec<String>, String> {
         let (dep_values, name) = self;
 
         dep_values.iter().map(|dep_value|
@@ -412,12 +511,42 @@ impl DepValueVec for (Vec<&DependencyValue>, InstructionName) {
     }
 
 
-    fn into_number_list(&self) -> Result<Vec<f64>, String> {
+    fn into_number_list(self) -> Result<Vec<f64>, String> {
         let (dep_values, name) = self;
 
         dep_values.iter().map(|dep_value|
             dep_value.value.clone().try_into().map_err(|_|
-                format!("Not all elements in instruction [{}] were strings", name)
+                format!("Not all elements in instruction [{}] were numbers", name)
+            )
+        ).collect()
+    }
+
+    fn into_integer_list(self) -> Result<Vec<i64>, String> {
+        let (dep_values, name) = self;
+
+        dep_values.iter().map(|dep_value|
+            dep_value.value.clone().try_into().map_err(|_|
+                format!("Not all elements in instruction [{}] were integers", name)
+            )
+        ).collect()
+    }
+
+    fn into_bool_list(self) -> Result<Vec<bool>, String> {
+        let (dep_values, name) = self;
+
+        dep_values.iter().map(|dep_value|
+            dep_value.value.clone().try_into().map_err(|_|
+                format!("Not all elements in instruction [{}] were booleans", name)
+            )
+        ).collect()
+    }
+
+    fn into_math_expression_list(self) -> Result<Vec<MathExpression>, String> {
+        let (dep_values, name) = self;
+
+        dep_values.iter().map(|dep_value|
+            dep_value.value.clone().try_into().map_err(|_|
+                format!("Not all elements in instruction [{}] were math expressions", name)
             )
         ).collect()
     }
@@ -524,6 +653,7 @@ impl TryFrom<StateVarValue> for String {
             StateVarValue::Integer(_) => Err("cannot convert StateVarValue::Integer to string"),
             StateVarValue::Boolean(_) => Err("cannot convert StateVarValue::Boolean to string"),
             StateVarValue::MathExpr(_) => Err("cannot convert StateVarValue::MathExpr to string"),
+            StateVarValue::Null => Err("cannot convert StateVarValue::Null to string"),
         }
     }
 }
@@ -536,6 +666,7 @@ impl TryFrom<StateVarValue> for bool {
             StateVarValue::Integer(_) => Err("cannot convert StateVarValue::Integer to boolean"),
             StateVarValue::String(_) => Err("cannot convert StateVarValue::String to boolean"),
             StateVarValue::MathExpr(_) => Err("cannot convert StateVarValue::MathExpr to boolean"),
+            StateVarValue::Null => Err("cannot convert StateVarValue::Null to boolean"),
         }
     }
 }
@@ -549,6 +680,7 @@ impl TryFrom<StateVarValue> for f64 {
             StateVarValue::Boolean(_) => Err("cannot convert StateVarValue::Boolean to number"),
             StateVarValue::MathExpr(_) => Err("cannot convert StateVarValue::MathExpr to number"),
 
+            StateVarValue::Null => Err("cannot convert StateVarValue::Null to number"),
         }
     }
 }
@@ -561,6 +693,7 @@ impl TryFrom<StateVarValue> for i64 {
             StateVarValue::String(_) => Err("cannot convert StateVarValue::String to integer"),
             StateVarValue::Boolean(_) => Err("cannot convert StateVarValue::Boolean to integer"),
             StateVarValue::MathExpr(_) => Err("cannot convert StateVarValue::MathExpr to integer"),
+            StateVarValue::Null => Err("cannot convert StateVarValue::Null to integer"),
         }
     }
 }
@@ -574,6 +707,7 @@ impl TryFrom<StateVarValue> for MathExpression {
             StateVarValue::Number(_) => Err("cannot convert StateVarValue::Number to MathExpr"),
             StateVarValue::String(_) => Err("cannot convert StateVarValue::String to MathExpr"),
             StateVarValue::Boolean(_) => Err("cannot convert StateVarValue::Boolean to MathExpr"),
+            StateVarValue::Null => Err("cannot convert StateVarValue::Null to MathExpr"),
         }
     }
 }
@@ -586,6 +720,7 @@ impl From<StateVarValue> for serde_json::Value {
             StateVarValue::String(v) =>  serde_json::json!(v),
             StateVarValue::Boolean(v) => serde_json::json!(v),
             StateVarValue::MathExpr(v) => serde_json::json!(v),
+            StateVarValue::Null => serde_json::Value::Null,
         }
     }
 }
@@ -602,6 +737,7 @@ impl TryFrom<StateVarValue> for usize {
             StateVarValue::Boolean(_) => Err("cannot convert StateVarValue::Boolean to usize"),
             StateVarValue::MathExpr(_) => Err("cannot convert StateVarValue::MathExpr to usize"),
 
+            StateVarValue::Null => Err("cannot convert StateVarValue::Null to usize"),
         }
     }
 }
@@ -627,6 +763,11 @@ impl From<i64> for StateVarValue {
     }
 
 }
+impl From<MathExpression> for StateVarValue {
+    fn from(v: MathExpression) -> StateVarValue {
+        StateVarValue::MathExpr(v)
+    }
+}
 
 
 impl StateVarValue {
@@ -638,6 +779,7 @@ impl StateVarValue {
             Self::Integer(_) => "integer",
             Self::Number(_) => "number",
             Self::MathExpr(_) => "mathExpression",
+            Self::Null => "null",
         }
     }
 
@@ -713,6 +855,7 @@ impl StateVarVariant {
                 let instruction = (def.determine_state_var_from_dependencies)(dependency_values)?;
                 Ok(match instruction {                    
                     NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
                     SetValue(val) => SetValue(StateVarValue::String(val)),
                 })
             },
@@ -720,6 +863,7 @@ impl StateVarVariant {
                 let instruction = (def.determine_state_var_from_dependencies)(dependency_values)?;
                 Ok(match instruction {
                     NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
                     SetValue(val) => SetValue(StateVarValue::Integer(val)),
                 })
             },
@@ -727,6 +871,7 @@ impl StateVarVariant {
                 let instruction = (def.determine_state_var_from_dependencies)(dependency_values)?;
                 Ok(match instruction {
                     NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
                     SetValue(val) => SetValue(StateVarValue::Number(val)),
                 })
             },
@@ -734,6 +879,7 @@ impl StateVarVariant {
                 let instruction = (def.determine_state_var_from_dependencies)(dependency_values)?;
                 Ok(match instruction {
                     NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
                     SetValue(val) => SetValue(StateVarValue::Boolean(val)),
                 })
             },
@@ -785,9 +931,10 @@ impl StateVarVariant {
 
             Self::NumberArray(def) => {
                 match state_ref {
-                    StateRef::ArrayElement(_, i) => {
+                    StateRef::ArrayElement(_, index) => {
                         Ok((def.request_element_dependencies_to_update_value)(
-                            *i,
+                            element_id(index, def.inner_dimensions)
+                                .ok_or(format!("{} is outside of the array", state_ref))?,
                             desired_value.clone().try_into().map_err(|_| // only cloned for error msg
                                 format!("Requested NumberArray element be updated to {:#?}", desired_value)
                             )?,
@@ -808,9 +955,10 @@ impl StateVarVariant {
 
             Self::StringArray(def) => {
                 match state_ref {
-                    StateRef::ArrayElement(_, i) => {
+                    StateRef::ArrayElement(_, index) => {
                         Ok((def.request_element_dependencies_to_update_value)(
-                            *i,
+                            element_id(index, def.inner_dimensions)
+                                .ok_or(format!("{} is outside of the array", state_ref))?,
                             desired_value.clone().try_into().map_err(|_| // only cloned for error msg
                                 format!("Requested StringArray element be updated to {:#?}", desired_value)
                             )?,
@@ -829,6 +977,78 @@ impl StateVarVariant {
                 }
             },
 
+            Self::IntegerArray(def) => {
+                match state_ref {
+                    StateRef::ArrayElement(_, index) => {
+                        Ok((def.request_element_dependencies_to_update_value)(
+                            element_id(index, def.inner_dimensions)
+                                .ok_or(format!("{} is outside of the array", state_ref))?,
+                            desired_value.clone().try_into().map_err(|_| // only cloned for error msg
+                                format!("Requested IntegerArray element be updated to {:#?}", desired_value)
+                            )?,
+                            dependency_sources,
+                        ))
+                    },
+                    StateRef::SizeOf(_) => {
+                        Ok((def.request_size_dependencies_to_update_value)(
+                            desired_value.clone().try_into().map_err(|_| // only cloned for error msg
+                                format!("Requested IntegerArray size be updated to {:#?}", desired_value)
+                            )?,
+                            dependency_sources,
+                        ))
+                    }
+                    StateRef::Basic(_) => panic!("reference does not match definition"),
+                }
+            },
+
+            Self::BooleanArray(def) => {
+                match state_ref {
+                    StateRef::ArrayElement(_, index) => {
+                        Ok((def.request_element_dependencies_to_update_value)(
+                            element_id(index, def.inner_dimensions)
+                                .ok_or(format!("{} is outside of the array", state_ref))?,
+                            desired_value.clone().try_into().map_err(|_| // only cloned for error msg
+                                format!("Requested BooleanArray element be updated to {:#?}", desired_value)
+                            )?,
+                            dependency_sources,
+                        ))
+                    },
+                    StateRef::SizeOf(_) => {
+                        Ok((def.request_size_dependencies_to_update_value)(
+                            desired_value.clone().try_into().map_err(|_| // only cloned for error msg
+                                format!("Requested BooleanArray size be updated to {:#?}", desired_value)
+                            )?,
+                            dependency_sources,
+                        ))
+                    }
+                    StateRef::Basic(_) => panic!("reference does not match definition"),
+                }
+            },
+
+            Self::MathArray(def) => {
+                match state_ref {
+                    StateRef::ArrayElement(_, index) => {
+                        Ok((def.request_element_dependencies_to_update_value)(
+                            element_id(index, def.inner_dimensions)
+                                .ok_or(format!("{} is outside of the array", state_ref))?,
+                            desired_value.clone().try_into().map_err(|_| // only cloned for error msg
+                                format!("Requested MathArray element be updated to {:#?}", desired_value)
+                            )?,
+                            dependency_sources,
+                        ))
+                    },
+                    StateRef::SizeOf(_) => {
+                        Ok((def.request_size_dependencies_to_update_value)(
+                            desired_value.clone().try_into().map_err(|_| // only cloned for error msg
+                                format!("Requested MathArray size be updated to {:#?}", desired_value)
+                            )?,
+                            dependency_sources,
+                        ))
+                    }
+                    StateRef::Basic(_) => panic!("reference does not match definition"),
+                }
+            },
+
         }       
     }
 
@@ -843,6 +1063,9 @@ impl StateVarVariant {
         match self {
             Self::NumberArray(def) => (def.return_array_dependency_instructions)(prereq_state_values),
             Self::StringArray(def) => (def.return_array_dependency_instructions)(prereq_state_values),
+            Self::IntegerArray(def) => (def.return_array_dependency_instructions)(prereq_state_values),
+            Self::BooleanArray(def) => (def.return_array_dependency_instructions)(prereq_state_values),
+            Self::MathArray(def) => (def.return_array_dependency_instructions)(prereq_state_values),
             _ => unreachable!(),
         }
     }
@@ -855,6 +1078,9 @@ impl StateVarVariant {
         match self {
             Self::NumberArray(def) => (def.return_element_dependency_instructions)(index, prereq_state_values),
             Self::StringArray(def) => (def.return_element_dependency_instructions)(index, prereq_state_values),
+            Self::IntegerArray(def) => (def.return_element_dependency_instructions)(index, prereq_state_values),
+            Self::BooleanArray(def) => (def.return_element_dependency_instructions)(index, prereq_state_values),
+            Self::MathArray(def) => (def.return_element_dependency_instructions)(index, prereq_state_values),
             _ => unreachable!(),
         }
     }
@@ -866,6 +1092,9 @@ impl StateVarVariant {
         match self {
             Self::NumberArray(def) => (def.return_size_dependency_instructions)(prereq_state_values),
             Self::StringArray(def) => (def.return_size_dependency_instructions)(prereq_state_values),
+            Self::IntegerArray(def) => (def.return_size_dependency_instructions)(prereq_state_values),
+            Self::BooleanArray(def) => (def.return_size_dependency_instructions)(prereq_state_values),
+            Self::MathArray(def) => (def.return_size_dependency_instructions)(prereq_state_values),
             _ => unreachable!(),
         }
     }
@@ -882,6 +1111,8 @@ impl StateVarVariant {
                 let instruction = (def.determine_size_from_dependencies)(dependency_values)?;
                 Ok(match instruction {                    
                     NoChange => NoChange,
+                    // an array without a size has no elements
+                    SetNull => SetValue(StateVarValue::Integer(0)),
                     SetValue(val) => SetValue(StateVarValue::Integer(val as i64)),
                 })
             },
@@ -889,6 +1120,35 @@ impl StateVarVariant {
                 let instruction = (def.determine_size_from_dependencies)(dependency_values)?;
                 Ok(match instruction {                    
                     NoChange => NoChange,
+                    // an array without a size has no elements
+                    SetNull => SetValue(StateVarValue::Integer(0)),
+                    SetValue(val) => SetValue(StateVarValue::Integer(val as i64)),
+                })
+            },
+            Self::IntegerArray(def) => {
+                let instruction = (def.determine_size_from_dependencies)(dependency_values)?;
+                Ok(match instruction {                    
+                    NoChange => NoChange,
+                    // an array without a size has no elements
+                    SetNull => SetValue(StateVarValue::Integer(0)),
+                    SetValue(val) => SetValue(StateVarValue::Integer(val as i64)),
+                })
+            },
+            Self::BooleanArray(def) => {
+                let instruction = (def.determine_size_from_dependencies)(dependency_values)?;
+                Ok(match instruction {                    
+                    NoChange => NoChange,
+                    // an array without a size has no elements
+                    SetNull => SetValue(StateVarValue::Integer(0)),
+                    SetValue(val) => SetValue(StateVarValue::Integer(val as i64)),
+                })
+            },
+            Self::MathArray(def) => {
+                let instruction = (def.determine_size_from_dependencies)(dependency_values)?;
+                Ok(match instruction {                    
+                    NoChange => NoChange,
+                    // an array without a size has no elements
+                    SetNull => SetValue(StateVarValue::Integer(0)),
                     SetValue(val) => SetValue(StateVarValue::Integer(val as i64)),
                 })
             },
@@ -909,6 +1169,7 @@ impl StateVarVariant {
                 let instruction = (def.determine_element_from_dependencies)(id, dependency_values)?;
                 Ok(match instruction {                    
                     NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
                     SetValue(val) => SetValue(StateVarValue::Number(val)),
                 })
             },
@@ -916,9 +1177,34 @@ impl StateVarVariant {
                 let instruction = (def.determine_element_from_dependencies)(id, dependency_values)?;
                 Ok(match instruction {                    
                     NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
                     SetValue(val) => SetValue(StateVarValue::String(val)),
                 })
             },
+            Self::IntegerArray(def) => {
+                let instruction = (def.determine_element_from_dependencies)(id, dependency_values)?;
+                Ok(match instruction {                    
+                    NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
+                    SetValue(val) => SetValue(StateVarValue::Integer(val)),
+                })
+            },
+            Self::BooleanArray(def) => {
+                let instruction = (def.determine_element_from_dependencies)(id, dependency_values)?;
+                Ok(match instruction {                    
+                    NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
+                    SetValue(val) => SetValue(StateVarValue::Boolean(val)),
+                })
+            },
+            Self::MathArray(def) => {
+                let instruction = (def.determine_element_from_dependencies)(id, dependency_values)?;
+                Ok(match instruction {                    
+                    NoChange => NoChange,
+                    SetNull => SetValue(StateVarValue::Null),
+                    SetValue(val) => SetValue(StateVarValue::MathExpr(val)),
+                })
+            },
 
             _ => unreachable!(),
         }
@@ -940,6 +1226,21 @@ impl StateVarVariant {
             Self::Boolean(def) => StateVarValue::Boolean(def.initial_essential_value),
             Self::NumberArray(def) => StateVarValue::Number(def.initial_essential_element_value),
             Self::StringArray(def) => StateVarValue::String(def.initial_essential_element_value.clone()),
+            Self::IntegerArray(def) => StateVarValue::Integer(def.initial_essential_element_value),
+            Self::BooleanArray(def) => StateVarValue::Boolean(def.initial_essential_element_value),
+            Self::MathArray(def) => StateVarValue::MathExpr(def.initial_essential_element_value.clone()),
+        }
+    }
+
+
+    pub fn initial_essential_size(&self) -> usize {
+        match self {
+            Self::NumberArray(def) => def.initial_essential_size,
+            Self::StringArray(def) => def.initial_essential_size,
+            Self::IntegerArray(def) => def.initial_essential_size,
+            Self::BooleanArray(def) => def.initial_essential_size,
+            Self::MathArray(def) => def.initial_essential_size,
+            _ => panic!("Only arrays have an initial essential size"),
         }
     }
 
@@ -952,18 +1253,51 @@ impl StateVarVariant {
             Self::Boolean(def) => def.for_renderer,
             Self::NumberArray(def) => def.for_renderer,
             Self::StringArray(def) => def.for_renderer,
+            Self::IntegerArray(def) => def.for_renderer,
+            Self::BooleanArray(def) => def.for_renderer,
+            Self::MathArray(def) => def.for_renderer,
         }
     }
 
 
-    pub fn is_array(&self) -> bool {
+    pub fn reshape_for_renderer(&self, value: serde_json::Value) -> serde_json::Value {
         match self {
-            Self::NumberArray(_) |
-            Self::StringArray(_) => true,
-            _ => false,
+            Self::String(def) =>  (def.reshape_for_renderer)(value),
+            Self::Integer(def) => (def.reshape_for_renderer)(value),
+            Self::Number(def) =>  (def.reshape_for_renderer)(value),
+            Self::Boolean(def) => (def.reshape_for_renderer)(value),
+            Self::NumberArray(def) => (def.reshape_for_renderer)(value),
+            Self::StringArray(def) => (def.reshape_for_renderer)(value),
+            Self::IntegerArray(def) => (def.reshape_for_renderer)(value),
+            Self::BooleanArray(def) => (def.reshape_for_renderer)(value),
+            Self::MathArray(def) => (def.reshape_for_renderer)(value),
         }
     }
 
+
+    /// The sizes of an array's dimensions after the first
+    pub fn inner_dimensions(&self) -> &'static [usize] {
+        match self {
+            Self::NumberArray(def) => def.inner_dimensions,
+            Self::StringArray(def) => def.inner_dimensions,
+            Self::IntegerArray(def) => def.inner_dimensions,
+            Self::BooleanArray(def) => def.inner_dimensions,
+            Self::MathArray(def) => def.inner_dimensions,
+            _ => &[],
+        }
+    }
+
+
+    pub fn is_array(&self) -> bool {
+        matches!(self,
+            Self::NumberArray(_) |
+            Self::StringArray(_) |
+            Self::IntegerArray(_) |
+            Self::BooleanArray(_) |
+            Self::MathArray(_)
+        )
+    }
+
 }
 
 
@@ -975,7 +1309,10 @@ impl Display for StateRef {
         match self {
             Self::Basic(sv_name) => write!(f, "{}", sv_name),
             Self::SizeOf(sv_name) => write!(f, "{}(size)", sv_name),
-            Self::ArrayElement(sv_name, i) => write!(f, "{}[{}]", sv_name, i),
+            Self::ArrayElement(sv_name, index) => {
+                write!(f, "{}", sv_name)?;
+                index.iter().try_for_each(|i| write!(f, "[{}]", i))
+            },
         }
     }
 }
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+
+
+/// How a graphical component is drawn, selected by its `styleNumber`.
+/// The words describe the style in text, such as "thick blue".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleDefinition {
+    pub line_color: String,
+    pub line_color_word: String,
+    pub line_opacity: f64,
+    pub line_width: f64,
+    pub line_width_word: String,
+    /// solid, dashed or dotted
+    pub line_style: String,
+    pub line_style_word: String,
+    pub marker_color: String,
+    pub marker_color_word: String,
+    pub marker_opacity: f64,
+    /// cross, circle, square, plus, diamond, triangle (alias for triangleUp),
+    /// triangleUp, triangleDown, triangleLeft or triangleRight
+    pub marker_style: String,
+    pub marker_style_word: String,
+    pub marker_size: f64,
+    pub fill_color: String,
+    pub fill_color_word: String,
+    pub fill_opacity: f64,
+}
+
+/// Style definitions keyed by style number
+pub type StyleDefinitions = BTreeMap<i64, StyleDefinition>;
+
+/// Changes to style definitions, keyed by style number, holding only the properties that were given
+pub type StyleChanges = BTreeMap<i64, Map<String, Value>>;
+
+
+/// The properties of a `StyleDefinition` that are numbers
+pub const NUMBER_STYLE_PROPERTIES: [&str; 5] = [
+    "lineOpacity",
+    "lineWidth",
+    "markerOpacity",
+    "markerSize",
+    "fillOpacity",
+];
+
+/// The properties of a `StyleDefinition` that are strings
+pub const STRING_STYLE_PROPERTIES: [&str; 11] = [
+    "lineColor",
+    "lineColorWord",
+    "lineWidthWord",
+    "lineStyle",
+    "lineStyleWord",
+    "markerColor",
+    "markerColorWord",
+    "markerStyle",
+    "markerStyleWord",
+    "fillColor",
+    "fillColorWord",
+];
+
+
+impl StyleDefinition {
+    fn new(color: &str, color_word: &str, opacity: f64, line_width: f64, line_style: &str, marker_style: &str) -> Self {
+        let mut definition = Self::default();
+        let changes = Map::from_iter([
+            ("lineColor", Value::from(color)),
+            ("lineColorWord", Value::from(color_word)),
+            ("lineOpacity", Value::from(opacity)),
+            ("lineWidth", Value::from(line_width)),
+            ("lineStyle", Value::from(line_style)),
+            ("markerColor", Value::from(color)),
+            ("markerColorWord", Value::from(color_word)),
+            ("markerOpacity", Value::from(opacity)),
+            ("markerStyle", Value::from(marker_style)),
+            ("fillColor", Value::from(color)),
+            ("fillColorWord", Value::from(color_word)),
+        ].map(|(property, value)| (property.to_string(), value)));
+
+        definition.apply_changes(&changes);
+        definition
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// The value of a property by its camelCase name
+    pub fn property(&self, property: &str) -> Option<Value> {
+        serde_json::to_value(self).unwrap().get(property).cloned()
+    }
+
+    /// Overwrite the given properties. A word that is not given is found
+    /// from the property it describes.
+    pub fn apply_changes(&mut self, changes: &Map<String, Value>) {
+        let mut changes = changes.clone();
+        fill_in_words(&mut changes);
+
+        let mut value = serde_json::to_value(&*self).unwrap();
+        let object = value.as_object_mut().unwrap();
+        for (property, new_value) in changes {
+            if let Some(old_value) = object.get_mut(&property) {
+                // Ignore values of the wrong type, such as a width that is not a number
+                if std::mem::discriminant(old_value) == std::mem::discriminant(&new_value) {
+                    *old_value = new_value;
+                }
+            }
+        }
+
+        *self = serde_json::from_value(value).unwrap();
+    }
+}
+
+impl Default for StyleDefinition {
+    /// Used for style numbers without a definition
+    fn default() -> Self {
+        StyleDefinition {
+            line_color: "#648FFF".to_string(),
+            line_color_word: "blue".to_string(),
+            line_opacity: 0.7,
+            line_width: 4.0,
+            line_width_word: "thick".to_string(),
+            line_style: "solid".to_string(),
+            line_style_word: "".to_string(),
+            marker_color: "#648FFF".to_string(),
+            marker_color_word: "blue".to_string(),
+            marker_opacity: 0.7,
+            marker_style: "circle".to_string(),
+            marker_style_word: "point".to_string(),
+            marker_size: 3.0,
+            fill_color: "#648FFF".to_string(),
+            fill_color_word: "blue".to_string(),
+            fill_opacity: 0.3,
+        }
+    }
+}
+
+
+pub fn default_style_definitions() -> StyleDefinitions {
+    let mut black = StyleDefinition::new("black", "black", 1.0, 1.0, "solid", "circle");
+    black.fill_opacity = 0.7;
+
+    BTreeMap::from([
+        (1, StyleDefinition::default()),
+        (2, StyleDefinition::new("#D4042D", "red", 0.7, 2.0, "solid", "square")),
+        (3, StyleDefinition::new("#F19143", "orange", 0.7, 3.0, "solid", "triangle")),
+        (4, StyleDefinition::new("#644CD6", "purple", 0.7, 2.0, "solid", "diamond")),
+        (5, black),
+        (6, StyleDefinition::new("gray", "gray", 0.7, 1.0, "dotted", "circle")),
+    ])
+}
+
+pub fn style_definitions_to_json(definitions: &StyleDefinitions) -> String {
+    serde_json::to_string(definitions).unwrap()
+}
+
+pub fn style_definitions_from_json(json: &str) -> StyleDefinitions {
+    serde_json::from_str(json).unwrap_or_else(|_| default_style_definitions())
+}
+
+/// The definition for a style number, or the default style when it has none
+pub fn select_style(definitions: &StyleDefinitions, style_number: i64) -> StyleDefinition {
+    definitions.get(&style_number).cloned().unwrap_or_default()
+}
+
+pub fn style_changes_to_json(changes: &StyleChanges) -> String {
+    serde_json::to_string(changes).unwrap()
+}
+
+pub fn style_changes_from_json(json: &str) -> StyleChanges {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+/// Later changes overwrite the properties of earlier ones
+pub fn merge_style_changes(changes: &mut StyleChanges, later: StyleChanges) {
+    for (style_number, properties) in later {
+        changes.entry(style_number).or_default().extend(properties);
+    }
+}
+
+/// Style numbers that are not defined yet start from the default style
+pub fn apply_style_changes(definitions: &mut StyleDefinitions, changes: &StyleChanges) {
+    for (style_number, properties) in changes {
+        definitions.entry(*style_number).or_default().apply_changes(properties);
+    }
+}
+
+
+fn fill_in_words(changes: &mut Map<String, Value>) {
+    for item in ["line", "marker", "fill"] {
+        if let Some(color) = changes.get(&format!("{}Color", item)).cloned() {
+            changes.entry(format!("{}ColorWord", item)).or_insert(color);
+        }
+    }
+
+    if let Some(width) = changes.get("lineWidth").and_then(Value::as_f64) {
+        let word = if width >= 4.0 {
+            "thick"
+        } else if width <= 1.0 {
+            "thin"
+        } else {
+            ""
+        };
+        changes.entry("lineWidthWord").or_insert(word.into());
+    }
+
+    if let Some(line_style) = changes.get("lineStyle").and_then(Value::as_str) {
+        let word = match line_style {
+            "dashed" | "dotted" => line_style.to_string(),
+            _ => "".to_string(),
+        };
+        changes.entry("lineStyleWord").or_insert(word.into());
+    }
+
+    if let Some(marker_style) = changes.get("markerStyle").and_then(Value::as_str) {
+        let word = if marker_style == "circle" {
+            "point".to_string()
+        } else if marker_style.starts_with("triangle") {
+            "triangle".to_string()
+        } else {
+            marker_style.to_string()
+        };
+        changes.entry("markerStyleWord").or_insert(word.into());
+    }
+}
@@ -113,6 +113,13 @@ pub fn package_subtree_as_json(
                 format!("{:?} {:?}", source_name, math_expression)
             ),
             Some(CopySource::MapSources(sources_name)) => Value::String(sources_name.to_string()),
+            Some(CopySource::MapSourcesIndex(sources_name)) => Value::String(format!("index of {}", sources_name)),
+            Some(CopySource::CollectionStateVar(collection, state_ref)) => Value::String(
+                format!("{:?} of each in {}", state_ref, collection)
+            ),
+            Some(CopySource::StateVarArray(array_relative)) => Value::String(
+                format!("{:?}", array_relative)
+            ),
             None => Value::Null,
         });
 
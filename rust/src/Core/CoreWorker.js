@@ -1,7 +1,9 @@
 import init, { PublicDoenetCore } from "../../pkg";
 import { parseAndCompile } from "../Parser/parser";
+import { get as idb_get, set as idb_set } from "idb-keyval";
 
 let doenetCore;
+let coreArgs;
 
 onmessage = function (e) {
   console.log('received message', e);
@@ -19,19 +21,32 @@ onmessage = function (e) {
     // For debugging only
     // this.debugStateValues = JSON.parse(doenetCore.display_all_state());
 
+  } else if (e.data.messageType === "undo") {
+
+    undo();
+
   }
 }
 
 
 async function createCore(args) {
 
+  coreArgs = args;
+
   const DoenetTextJson = parseAndCompile(args.doenetML);
   // console.log("DoenetML as JSON\n", DoenetTextJson);
 
   await init();
 
+  // The state saved by an earlier visit to this attempt, if any
+  let savedState;
+  if (args.flags?.allowLoadState) {
+    savedState = await idb_get(essentialDataKey());
+  }
+
   try {
-    doenetCore = PublicDoenetCore.new(JSON.stringify(DoenetTextJson));
+    let flags = { ...args.flags, variantIndex: args.requestedVariantIndex ?? 1 };
+    doenetCore = PublicDoenetCore.new(JSON.stringify(DoenetTextJson), JSON.stringify(flags), savedState);
 
   } catch(err) {
     console.error(err);
@@ -59,9 +74,7 @@ async function createCore(args) {
     let componentRenderState = render_tree[key];
 
     if(componentRenderState.componentName === "/_document1") {
-      // We don't know how to represent null SVs in Rust yet
       componentRenderState.childrenInstructions.push("\n\n ");
-      componentRenderState.stateValues.titleChildName = null;
 
     }
     rendererStates.push(componentRenderState);
@@ -172,7 +185,45 @@ function handleAction(args) {
   let completedActionId = doenetCore.handle_action(JSON.stringify(args));
   
 
-  // Right now this returns every component
+  updateRenderers();
+  saveState();
+}
+
+
+function undo() {
+
+  if (doenetCore.undo()) {
+    updateRenderers();
+    saveState();
+  }
+}
+
+
+// Only when the allowSaveState flag is set and a committed action changed the state
+async function saveState() {
+
+  const essentialData = doenetCore.take_essential_data_to_save();
+  if (essentialData === undefined) {
+    return;
+  }
+
+  await idb_set(essentialDataKey(), essentialData);
+
+  postMessage({
+    messageType: "savedState",
+    coreId: coreArgs.coreId,
+  });
+}
+
+
+function essentialDataKey() {
+  return `${coreArgs.doenetId}|${coreArgs.pageNumber}|${coreArgs.attemptNumber}|essentialData`;
+}
+
+
+function updateRenderers() {
+
+  // Components inside map instances are only included when they changed
   const render_tree_string = doenetCore.update_renderers();
   const render_tree = JSON.parse(render_tree_string);
   console.log("CoreWorker recieved render tree", render_tree);
@@ -183,9 +234,7 @@ function handleAction(args) {
     let componentRenderState = render_tree[key];
 
     if(componentRenderState.componentName === "/_document1") {
-      // We don't know how to represent null SVs in Rust yet
       componentRenderState.childrenInstructions.push("\n\n ");
-      componentRenderState.stateValues.titleChildName = null;
 
     }
     rendererStates.push(componentRenderState);
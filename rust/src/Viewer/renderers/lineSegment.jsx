@@ -21,7 +21,7 @@ export default React.memo(function LineSegment(props) {
 
   let lastPositionsFromCore = useRef(null);
 
-  lastPositionsFromCore.current = SVs.numericalEndpoints;
+  lastPositionsFromCore.current = SVs.numericalVertices;
 
   useEffect(() => {
 
@@ -38,8 +38,8 @@ export default React.memo(function LineSegment(props) {
 
   function createLineSegmentJXG() {
 
-    if (SVs.numericalEndpoints.length !== 2 ||
-      SVs.numericalEndpoints.some(x => x.length !== 2)
+    if (SVs.numericalVertices.length !== 2 ||
+      SVs.numericalVertices.some(x => x.length !== 2)
     ) {
       lineSegmentJXG.current = null;
       point1JXG.current = null;
@@ -96,8 +96,8 @@ export default React.memo(function LineSegment(props) {
 
 
     let endpoints = [
-      [...SVs.numericalEndpoints[0]],
-      [...SVs.numericalEndpoints[1]]
+      [...SVs.numericalVertices[0]],
+      [...SVs.numericalVertices[1]]
     ];
 
     // create invisible points at endpoints
@@ -255,15 +255,15 @@ export default React.memo(function LineSegment(props) {
   if (board) {
     if (lineSegmentJXG.current === null) {
       createLineSegmentJXG();
-    } else if (SVs.numericalEndpoints.length !== 2 ||
-      SVs.numericalEndpoints.some(x => x.length !== 2)
+    } else if (SVs.numericalVertices.length !== 2 ||
+      SVs.numericalVertices.some(x => x.length !== 2)
     ) {
       deleteLineSegmentJXG();
     } else {
 
       let validCoords = true;
 
-      for (let coords of [SVs.numericalEndpoints[0], SVs.numericalEndpoints[1]]) {
+      for (let coords of [SVs.numericalVertices[0], SVs.numericalVertices[1]]) {
         if (!Number.isFinite(coords[0])) {
           validCoords = false;
         }
@@ -272,8 +272,8 @@ export default React.memo(function LineSegment(props) {
         }
       }
 
-      lineSegmentJXG.current.point1.coords.setCoordinates(JXG.COORDS_BY_USER, SVs.numericalEndpoints[0]);
-      lineSegmentJXG.current.point2.coords.setCoordinates(JXG.COORDS_BY_USER, SVs.numericalEndpoints[1]);
+      lineSegmentJXG.current.point1.coords.setCoordinates(JXG.COORDS_BY_USER, SVs.numericalVertices[0]);
+      lineSegmentJXG.current.point2.coords.setCoordinates(JXG.COORDS_BY_USER, SVs.numericalVertices[1]);
 
       let visible = !SVs.hidden;
 
@@ -39,7 +39,7 @@ export default React.memo(function Polygon(props) {
 
   function createPolygonJXG() {
 
-    if (!(SVs.nVertices >= 2)) {
+    if (!(SVs.numVertices >= 2)) {
       return null;
     }
 
@@ -134,7 +134,7 @@ export default React.memo(function Polygon(props) {
 
     board.unsuspendUpdate();
 
-    previousNVertices.current = SVs.nVertices;
+    previousNVertices.current = SVs.numVertices;
 
     return newPolygonJXG;
 
@@ -142,7 +142,7 @@ export default React.memo(function Polygon(props) {
 
 
   function initializePoints(polygon) {
-    for (let i = 0; i < SVs.nVertices; i++) {
+    for (let i = 0; i < SVs.numVertices; i++) {
       let vertex = polygon.vertices[i];
       vertex.off('drag');
       vertex.on('drag', () => dragHandler(i));
@@ -154,7 +154,7 @@ export default React.memo(function Polygon(props) {
   }
 
   function deletePolygonJXG() {
-    for (let i = 0; i < SVs.nVertices; i++) {
+    for (let i = 0; i < SVs.numVertices; i++) {
       let vertex = polygonJXG.current.vertices[i];
       if (vertex) {
         vertex.off('drag');
@@ -181,7 +181,7 @@ export default React.memo(function Polygon(props) {
         }
       })
 
-      for (let j = 0; j < SVs.nVertices; j++) {
+      for (let j = 0; j < SVs.numVertices; j++) {
         polygonJXG.current.vertices[j].coords.setCoordinates(JXG.COORDS_BY_USER, [...lastPositionsFromCore.current[j]]);
       }
     } else {
@@ -250,7 +250,7 @@ export default React.memo(function Polygon(props) {
 
     if (!polygonJXG.current) {
       polygonJXG.current = createPolygonJXG();
-    } else if (!(SVs.nVertices >= 2)) {
+    } else if (!(SVs.numVertices >= 2)) {
       deletePolygonJXG();
     } else {
 
@@ -267,15 +267,15 @@ export default React.memo(function Polygon(props) {
       }
 
       // add or delete points as required and change data array size
-      if (SVs.nVertices > previousNVertices.current) {
-        for (let i = previousNVertices.current; i < SVs.nVertices; i++) {
+      if (SVs.numVertices > previousNVertices.current) {
+        for (let i = previousNVertices.current; i < SVs.numVertices; i++) {
           let newPoint = board.create('point', [...SVs.numericalVertices[i]], jsxPointAttributes.current)
           polygonJXG.current.addPoints(newPoint);
         }
         initializePoints(polygonJXG.current);
 
-      } else if (SVs.nVertices < previousNVertices.current) {
-        for (let i = previousNVertices.current - 1; i >= SVs.nVertices; i--) {
+      } else if (SVs.numVertices < previousNVertices.current) {
+        for (let i = previousNVertices.current - 1; i >= SVs.numVertices; i--) {
           polygonJXG.current.vertices[i].off('drag')
           polygonJXG.current.vertices[i].off('down')
           polygonJXG.current.vertices[i].off('up')
@@ -287,7 +287,7 @@ export default React.memo(function Polygon(props) {
       let fixed = !SVs.draggable || SVs.fixed;
       let verticesVisible = !fixed && !SVs.hidden;
 
-      for (let i = 0; i < SVs.nVertices; i++) {
+      for (let i = 0; i < SVs.numVertices; i++) {
         polygonJXG.current.vertices[i].coords.setCoordinates(JXG.COORDS_BY_USER, [...SVs.numericalVertices[i]]);
         polygonJXG.current.vertices[i].needsUpdate = true;
         polygonJXG.current.vertices[i].update();
@@ -393,7 +393,7 @@ export default React.memo(function Polygon(props) {
         }
       }
 
-      previousNVertices.current = SVs.nVertices;
+      previousNVertices.current = SVs.numVertices;
 
       board.updateRenderer();
 
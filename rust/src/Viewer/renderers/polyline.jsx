@@ -40,7 +40,7 @@ export default React.memo(function Polyline(props) {
 
   function createPolylineJXG() {
 
-    if (SVs.numericalVertices.length !== SVs.nVertices ||
+    if (SVs.numericalVertices.length !== SVs.numVertices ||
       SVs.numericalVertices.some(x => x.length !== 2)
     ) {
       return null;
@@ -104,7 +104,7 @@ export default React.memo(function Polyline(props) {
 
     // create invisible points at endpoints
     pointsJXG.current = [];
-    for (let i = 0; i < SVs.nVertices; i++) {
+    for (let i = 0; i < SVs.numVertices; i++) {
       pointsJXG.current.push(
         board.create('point', [...SVs.numericalVertices[i]], jsxPointAttributes.current)
       );
@@ -115,7 +115,7 @@ export default React.memo(function Polyline(props) {
 
     let newPolylineJXG = board.create('curve', [x, y], jsxPolylineAttributes);
 
-    for (let i = 0; i < SVs.nVertices; i++) {
+    for (let i = 0; i < SVs.numVertices; i++) {
       pointsJXG.current[i].on('drag', () => dragHandler(i));
       pointsJXG.current[i].on('up', () => upHandler(i));
       pointsJXG.current[i].on('down', () => draggedPoint.current = null);
@@ -132,7 +132,7 @@ export default React.memo(function Polyline(props) {
 
     });
 
-    previousNVertices.current = SVs.nVertices;
+    previousNVertices.current = SVs.numVertices;
 
     return newPolylineJXG;
 
@@ -147,7 +147,7 @@ export default React.memo(function Polyline(props) {
     board.removeObject(polylineJXG.current);
     polylineJXG.current = null;
 
-    for (let i = 0; i < SVs.nVertices; i++) {
+    for (let i = 0; i < SVs.numVertices; i++) {
       pointsJXG.current[i].off('drag');
       pointsJXG.current[i].off('down');
       pointsJXG.current[i].off('up');
@@ -176,7 +176,7 @@ export default React.memo(function Polyline(props) {
       let shiftY = polylineJXG.current.transformMat[2][0];
 
 
-      for (let j = 0; j < SVs.nVertices; j++) {
+      for (let j = 0; j < SVs.numVertices; j++) {
         pointsJXG.current[j].coords.setCoordinates(JXG.COORDS_BY_USER, [...lastPositionsFromCore.current[j]]);
         polylineJXG.current.dataX[j] = lastPositionsFromCore.current[j][0] - shiftX;
         polylineJXG.current.dataY[j] = lastPositionsFromCore.current[j][1] - shiftY;
@@ -250,7 +250,7 @@ export default React.memo(function Polyline(props) {
 
     if (!polylineJXG.current) {
       polylineJXG.current = createPolylineJXG();
-    } else if (SVs.numericalVertices.length !== SVs.nVertices ||
+    } else if (SVs.numericalVertices.length !== SVs.numVertices ||
       SVs.numericalVertices.some(x => x.length !== 2)
     ) {
       deletePolylineJXG();
@@ -283,29 +283,29 @@ export default React.memo(function Polyline(props) {
 
 
       // add or delete points as required and change data array size
-      if (SVs.nVertices > previousNVertices.current) {
-        for (let i = previousNVertices.current; i < SVs.nVertices; i++) {
+      if (SVs.numVertices > previousNVertices.current) {
+        for (let i = previousNVertices.current; i < SVs.numVertices; i++) {
           pointsJXG.current.push(
             board.create('point', [...SVs.numericalVertices[i]], jsxPointAttributes.current)
           );
-          polylineJXG.current.dataX.length = SVs.nVertices;
+          polylineJXG.current.dataX.length = SVs.numVertices;
 
           pointsJXG.current[i].on('drag', x => dragHandler(i, true));
           pointsJXG.current[i].on('up', x => dragHandler(i, false));
           pointsJXG.current[i].on('down', x => draggedPoint.current = null);
         }
-      } else if (SVs.nVertices < previousNVertices.current) {
-        for (let i = SVs.nVertices; i < previousNVertices.current; i++) {
+      } else if (SVs.numVertices < previousNVertices.current) {
+        for (let i = SVs.numVertices; i < previousNVertices.current; i++) {
           let pt = pointsJXG.current.pop()
           pt.off('drag');
           pt.off('down');
           pt.off('up');
           board.removeObject(pt);
         }
-        polylineJXG.current.dataX.length = SVs.nVertices;
+        polylineJXG.current.dataX.length = SVs.numVertices;
       }
 
-      previousNVertices.current = SVs.nVertices;
+      previousNVertices.current = SVs.numVertices;
 
 
       polylineJXG.current.updateTransformMatrix();
@@ -313,7 +313,7 @@ export default React.memo(function Polyline(props) {
       let shiftY = polylineJXG.current.transformMat[2][0];
 
 
-      for (let i = 0; i < SVs.nVertices; i++) {
+      for (let i = 0; i < SVs.numVertices; i++) {
         pointsJXG.current[i].coords.setCoordinates(JXG.COORDS_BY_USER, [...SVs.numericalVertices[i]]);
         polylineJXG.current.dataX[i] = SVs.numericalVertices[i][0] - shiftX;
         polylineJXG.current.dataY[i] = SVs.numericalVertices[i][1] - shiftY;
@@ -329,7 +329,7 @@ export default React.memo(function Polyline(props) {
 
         let pointsVisible = visible && !fixed;
 
-        for (let i = 0; i < SVs.nVertices; i++) {
+        for (let i = 0; i < SVs.numVertices; i++) {
           pointsJXG.current[i].visProp["visible"] = pointsVisible;
           pointsJXG.current[i].visPropCalc["visible"] = pointsVisible;
         }
@@ -339,7 +339,7 @@ export default React.memo(function Polyline(props) {
         polylineJXG.current.visPropCalc["visible"] = false;
         // polylineJXG.current.setAttribute({visible: false})
 
-        for (let i = 0; i < SVs.nVertices; i++) {
+        for (let i = 0; i < SVs.numVertices; i++) {
           pointsJXG.current[i].visProp["visible"] = false;
           pointsJXG.current[i].visPropCalc["visible"] = false;
         }
@@ -387,7 +387,7 @@ export default React.memo(function Polyline(props) {
 
       polylineJXG.current.needsUpdate = true;
       polylineJXG.current.update().updateVisibility();
-      for (let i = 0; i < SVs.nVertices; i++) {
+      for (let i = 0; i < SVs.numVertices; i++) {
         if (layerChanged) {
           pointsJXG.current[i].setAttribute({ layer: polylineLayer + 2 });
         }
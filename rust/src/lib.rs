@@ -33,22 +33,36 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 
 
+/// The core, its DoenetML warnings, and the program it was created from,
+/// which creates it again to undo an action
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Debug)]
-pub struct PublicDoenetCore(DoenetCore, pub js_sys::Array);
+pub struct PublicDoenetCore(DoenetCore, pub js_sys::Array, String);
 
 
 
 #[wasm_bindgen]
 impl PublicDoenetCore {
     /// Create components from JSON tree and create all dependencies.
-    pub fn new(program: &str) -> Result<PublicDoenetCore, String> {
+    /// The flags are a JSON object, and any flag left out keeps its default.
+    /// The saved state is the JSON from `take_essential_data_to_save` of an earlier visit.
+    pub fn new(program: &str, flags: &str, saved_state: Option<String>) -> Result<PublicDoenetCore, String> {
 
         utils::set_panic_hook();
 
+        let flags: doenet_core::CoreFlags = serde_json::from_str(flags)
+            .map_err(|err| format!("Invalid core flags: {}", err))?;
+
+        // State that cannot be read is left out, so the document starts over
+        let saved_state: Vec<doenet_core::SavedEssentialState> = saved_state
+            .and_then(|saved_state| serde_json::from_str(&saved_state)
+                .map_err(|err| web_sys::console::warn_1(&format!("Ignoring saved state: {}", err).into()))
+                .ok())
+            .unwrap_or_default();
+
         web_sys::console::time_with_label("DoenetCore creation");
-                
-        let core_or_error = doenet_core::create_doenet_core(program, None);
+
+        let core_or_error = doenet_core::create_doenet_core_from_saved_state(program, flags, saved_state);
 
         web_sys::console::time_end_with_label("DoenetCore creation");
 
@@ -59,7 +73,7 @@ impl PublicDoenetCore {
                 for (i, warning) in ml_warnings.iter().enumerate() {
                     warnings_array.set(i as u32, JsValue::from(warning.to_string()));
                 }
-                Ok(PublicDoenetCore(core, warnings_array))
+                Ok(PublicDoenetCore(core, warnings_array, program.to_string()))
             }
         }
     }   
@@ -86,8 +100,25 @@ impl PublicDoenetCore {
 
     pub fn handle_action(&self, action: &str) -> String {
 
-        let completed_action_id = doenet_core::handle_action_from_json(&self.0, action);
-        completed_action_id
+        doenet_core::handle_action_from_json(&self.0, action)
+    }
+
+
+    /// JSON of the essential data, when it has changed since it was last saved
+    /// and the flags allow saving state
+    pub fn take_essential_data_to_save(&self) -> Option<String> {
+
+        doenet_core::take_essential_data_to_save(&self.0)
+            .and_then(|essential_data| serde_json::to_string(
+                &doenet_core::saved_state(&essential_data)
+            ).ok())
+    }
+
+
+    /// Undo the most recent committed action. False when there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+
+        doenet_core::undo(&mut self.0, &self.2)
     }
 
 }
@@ -5,11 +5,11 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 
-use doenet_core::{EssentialDataOrigin, Action, Instance};
+use doenet_core::{EssentialDataOrigin, Action, Instance, CoreFlags, SavedEssentialState};
 use doenet_core::ComponentName;
 use doenet_core::parse_json::{DoenetMLError, DoenetMLWarning};
 use doenet_core::state::EssentialStateVar;
-use doenet_core::state_variables::StateRef;
+use doenet_core::state_variables::{StateRef, elements_len};
 use doenet_core::{DoenetCore, state_variables::StateVarValue, state::{StateForStateVar, State}};
 use serde_json::Value;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
@@ -40,27 +40,56 @@ pub fn doenet_core_with_no_warnings(data: &str) -> DoenetCore {
 }
 
 pub fn doenet_core_from(data: &str) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
+    doenet_core_with_flags(data, CoreFlags::default())
+}
+
+pub fn doenet_core_with_flags(data: &str, flags: CoreFlags) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
     let parsed = parseAndCompile(data.to_string());
     let program: String = js_sys::JSON::stringify(&parsed).unwrap().into();
-    doenet_core::create_doenet_core(&program, None)
+    doenet_core::create_doenet_core(&program, flags, None)
 }
 
 pub fn doenet_core_with_essential_data(
     program_str: &str,
     essential_data: HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>,
 ) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
+    doenet_core_with_flags_and_essential_data(program_str, CoreFlags::default(), essential_data)
+}
+
+pub fn doenet_core_with_flags_and_essential_data(
+    program_str: &str,
+    flags: CoreFlags,
+    essential_data: HashMap<ComponentName, HashMap<EssentialDataOrigin, EssentialStateVar>>,
+) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
     
     let parsed = parseAndCompile(program_str.to_string());
     let program: String = js_sys::JSON::stringify(&parsed).unwrap().into();
-    doenet_core::create_doenet_core(&program, Some(essential_data))
+    doenet_core::create_doenet_core(&program, flags, Some(essential_data))
+}
+
+pub fn doenet_core_from_saved_state(
+    program_str: &str,
+    flags: CoreFlags,
+    saved_state: Vec<SavedEssentialState>,
+) -> Result<(DoenetCore, Vec<DoenetMLWarning>), DoenetMLError> {
+
+    let parsed = parseAndCompile(program_str.to_string());
+    let program: String = js_sys::JSON::stringify(&parsed).unwrap().into();
+    doenet_core::create_doenet_core_from_saved_state(&program, flags, saved_state)
+}
+
+pub fn undo(dc: &mut DoenetCore, data: &str) -> bool {
+    let parsed = parseAndCompile(data.to_string());
+    let program: String = js_sys::JSON::stringify(&parsed).unwrap().into();
+    doenet_core::undo(dc, &program)
 }
 
 fn get_state_var(dc: &DoenetCore, comp_name: &'static str, map: &Instance, sv_ref: &StateRef) -> State<StateVarValue> {
 
-    let state_value = dc.component_states.get(comp_name).expect(
-        &format!("Component {} does not exist", comp_name)
-    ).get(sv_ref.name()).expect(
-        &format!("State var [{}]:[{}] does not exist", comp_name, sv_ref.name())
+    let state_value = dc.component_states.get(comp_name).unwrap_or_else(||
+        panic!("Component {} does not exist", comp_name)
+    ).get(sv_ref.name()).unwrap_or_else(||
+        panic!("State var [{}]:[{}] does not exist", comp_name, sv_ref.name())
     );
 
     match sv_ref {
@@ -76,11 +105,14 @@ fn get_state_var(dc: &DoenetCore, comp_name: &'static str, map: &Instance, sv_re
                 _ => panic!("State var [{}]:[{}] is SizeOf but does not have array state", comp_name, sv_name)
             }
         },
-        StateRef::ArrayElement(sv_name, id) => {
+        StateRef::ArrayElement(sv_name, index) => {
+            let [id] = index.as_slice() else {
+                panic!("State var [{}]:[{}] is only checked with one index", comp_name, sv_name)
+            };
             match state_value {
                 StateForStateVar::Array { elements, .. } => {
-                    elements.instance(map).get(*id).expect(
-                        &format!("State var [{}]:[{}] does not have element index {}", comp_name, sv_name, id)
+                    elements.instance(map).get(*id).unwrap_or_else(||
+                        panic!("State var [{}]:[{}] does not have element index {}", comp_name, sv_name, id)
                     ).get_state()
                 },
 
@@ -97,8 +129,8 @@ fn assert_state_var_is(dc: &DoenetCore, comp_name: &'static str, map: &Instance,
 
     match value {
         StateVarValue::Number(num_val) => {
-            let resolved_sv_val = state.into_resolved().expect(
-                &format!("Value [{}]:[{}] was stale, expected {}", comp_name, sv_ref, value)
+            let resolved_sv_val = state.into_resolved().unwrap_or_else(|_|
+                panic!("Value [{}]:[{}] was stale, expected {}", comp_name, sv_ref, value)
             );
 
             let actual_num: f64 = if let StateVarValue::Number(actual_num) = resolved_sv_val {
@@ -145,7 +177,7 @@ pub fn assert_sv_is_integer(dc: &DoenetCore, comp_name: &'static str, sv_name: &
     assert_state_var_is(dc, comp_name, &Instance::default(), &StateRef::Basic(sv_name), StateVarValue::Integer(value));
 }
 pub fn assert_sv_array_element_is_number(dc: &DoenetCore, comp_name: &'static str, sv_name: &'static str, id: usize, value: f64) {
-    assert_state_var_is(dc, comp_name, &Instance::default(), &StateRef::ArrayElement(sv_name, id), StateVarValue::Number(value));
+    assert_state_var_is(dc, comp_name, &Instance::default(), &StateRef::ArrayElement(sv_name, vec![id]), StateVarValue::Number(value));
 }
 pub fn assert_sv_array_size_is(dc: &DoenetCore, comp_name: &'static str, sv_name: &'static str, size: usize,) {
     assert_state_var_is(dc, comp_name, &Instance::default(), &StateRef::SizeOf(sv_name), StateVarValue::Integer(size as i64));
@@ -153,6 +185,18 @@ pub fn assert_sv_array_size_is(dc: &DoenetCore, comp_name: &'static str, sv_name
 pub fn assert_sv_array_is_number_list(dc: &DoenetCore, comp_name: &'static str, sv_name: &'static str, values: Vec<f64>) {
     assert_sv_array_is_number_list_with_map(dc, comp_name, Instance::default(), sv_name, values)
 }
+pub fn assert_sv_array_is_boolean_list(dc: &DoenetCore, comp_name: &'static str, sv_name: &'static str, values: Vec<bool>) {
+    let (size, element_values) = get_array_state(dc, comp_name, &Instance::default(), sv_name);
+
+    let bool_values: Vec<bool> = element_values.into_iter().map(|elem|
+        elem.try_into().unwrap_or_else(|_|
+            panic!("Not all elements of [{}]:[{}] were booleans", comp_name, sv_name)
+        )
+    ).collect();
+
+    assert_eq!(values.len(), size, "Boolean list was wrong size");
+    assert_eq!(values, bool_values);
+}
 
 pub fn assert_sv_is_string_with_map(dc: &DoenetCore, comp_name: &'static str, map: Instance, sv_name: &'static str, value: &'static str) {
     assert_state_var_is(dc, comp_name, &map, &StateRef::Basic(sv_name), StateVarValue::String(value.into()));
@@ -167,7 +211,7 @@ pub fn assert_sv_is_integer_with_map(dc: &DoenetCore, comp_name: &'static str, m
     assert_state_var_is(dc, comp_name, &map, &StateRef::Basic(sv_name), StateVarValue::Integer(value));
 }
 pub fn assert_sv_array_element_is_number_with_map(dc: &DoenetCore, comp_name: &'static str, map: Instance, sv_name: &'static str, id: usize, value: f64) {
-    assert_state_var_is(dc, comp_name, &map, &StateRef::ArrayElement(sv_name, id), StateVarValue::Number(value));
+    assert_state_var_is(dc, comp_name, &map, &StateRef::ArrayElement(sv_name, vec![id]), StateVarValue::Number(value));
 }
 pub fn assert_sv_array_size_is_with_map(dc: &DoenetCore, comp_name: &'static str, map: Instance, sv_name: &'static str, size: usize,) {
     assert_state_var_is(dc, comp_name, &map, &StateRef::SizeOf(sv_name), StateVarValue::Integer(size as i64));
@@ -177,8 +221,8 @@ pub fn assert_sv_array_is_number_list_with_map(dc: &DoenetCore, comp_name: &'sta
     let (size, element_values) = get_array_state(dc, comp_name, &map, sv_name);
 
     let num_values: Vec<f64> = element_values.into_iter().map(|elem|
-        elem.try_into().expect(
-            &format!("Not all elements of [{}]:[{}] were numbers", comp_name, sv_name)
+        elem.try_into().unwrap_or_else(|_|
+            panic!("Not all elements of [{}]:[{}] were numbers", comp_name, sv_name)
         )
     ).collect();
 
@@ -191,18 +235,19 @@ pub fn assert_sv_array_is_number_list_with_map(dc: &DoenetCore, comp_name: &'sta
 
 fn get_array_state(dc: &DoenetCore, comp_name: &'static str, map: &Instance, sv_name: &'static str) -> (usize, Vec<StateVarValue>) {
 
-    let state_value = dc.component_states.get(comp_name).expect(
-        &format!("Component {} does not exist", comp_name)
-    ).get(sv_name).expect(
-        &format!("State var [{}]:[{}] does not exist", comp_name, sv_name)
+    let state_value = dc.component_states.get(comp_name).unwrap_or_else(||
+        panic!("Component {} does not exist", comp_name)
+    ).get(sv_name).unwrap_or_else(||
+        panic!("State var [{}]:[{}] does not exist", comp_name, sv_name)
     );
 
     match state_value {
-        StateForStateVar::Array { size, elements, .. } => {
+        StateForStateVar::Array { size, elements, inner_dimensions, .. } => {
 
+            // the number of elements in every dimension
             let size_value = if let State::Resolved(val) = size.instance(map).get_state() {
                 let val: i64 = val.try_into().unwrap();
-                val as usize
+                elements_len(val as usize, inner_dimensions)
             } else {
                 panic!("Size of state var [{}]:[{}] is not resolved", comp_name, sv_name);
             };
@@ -233,11 +278,7 @@ fn get_array_state(dc: &DoenetCore, comp_name: &'static str, map: &Instance, sv_
 pub fn render_data_of_component<'a>(render_tree: &'a Value, component_name: &'static str) -> &'a serde_json::Map<String, Value> {
     render_tree.as_array().unwrap().iter().find(|render_item| {
         if let Some(render_obj) = render_item.as_object() {
-            if render_obj.get("componentName") == Some(&Value::String(component_name.to_string())) {
-                true
-            } else {
-                false
-            }
+            render_obj.get("componentName") == Some(&Value::String(component_name.to_string()))
         } else {
             false
         }
@@ -247,24 +288,20 @@ pub fn render_data_of_component<'a>(render_tree: &'a Value, component_name: &'st
 
 
 pub fn child_instructions_for<'a>(render_tree: &'a Value, parent: &'static str, child: &'static str) -> &'a serde_json::Map<String, Value> {
-    let children_instructions = render_data_of_component(&render_tree, parent)
+    let children_instructions = render_data_of_component(render_tree, parent)
         .get("childrenInstructions").unwrap()
         .as_array().unwrap();
 
     children_instructions.iter().find(|render_item| {
             if let Some(render_obj) = render_item.as_object() {
-                if render_obj.get("componentName") == Some(&Value::String(child.to_string())) {
-                    true
-                } else {
-                    false
-                }
+                render_obj.get("componentName") == Some(&Value::String(child.to_string()))
             } else {
                 false
             }
 
         })
-        .expect(
-            &format!("none with name {child} in {:?}", children_instructions)
+        .unwrap_or_else(||
+            panic!("none with name {child} in {:?}", children_instructions)
         ).as_object().unwrap()
 }
 
@@ -278,8 +315,9 @@ pub fn update_immediate_value_for_text(dc: &DoenetCore, component_name: &'static
         args: HashMap::from([
             ("text".to_string(), vec![StateVarValue::String(value.into())]),
         ]),
+        transient: false,
     };
-    doenet_core::handle_action(&dc, type_in_number_input);
+    doenet_core::handle_action(dc, type_in_number_input);
 }
 
 pub fn update_immediate_value_for_number(dc: &DoenetCore, component_name: &'static str, value: &'static str) {
@@ -289,8 +327,9 @@ pub fn update_immediate_value_for_number(dc: &DoenetCore, component_name: &'stat
         args: HashMap::from([
             ("text".to_string(), vec![StateVarValue::String(value.into())]),
         ]),
+        transient: false,
     };
-    doenet_core::handle_action(&dc, type_in_number_input);
+    doenet_core::handle_action(dc, type_in_number_input);
 }
 
 pub fn update_value_for_number(dc: &DoenetCore, component_name: &'static str) {
@@ -298,8 +337,9 @@ pub fn update_value_for_number(dc: &DoenetCore, component_name: &'static str) {
         component_name: component_name.to_string(),
         action_name: "updateValue".to_string(),
         args: HashMap::new(),
+        transient: false,
     };
-    doenet_core::handle_action(&dc, update_number_input_value);
+    doenet_core::handle_action(dc, update_number_input_value);
 }
 
 
@@ -311,6 +351,163 @@ pub fn move_point_2d(dc: &DoenetCore, component_name: &'static str, x: StateVarV
             ("x".to_string(), vec![x]),
             ("y".to_string(), vec![y]),
         ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_point);
+}
+
+pub fn drag_point_2d(dc: &DoenetCore, component_name: &'static str, x: f64, y: f64) {
+    let drag_point = Action {
+        component_name: component_name.to_string(),
+        action_name: "movePoint".to_string(),
+        args: HashMap::from([
+            ("x".to_string(), vec![StateVarValue::Number(x)]),
+            ("y".to_string(), vec![StateVarValue::Number(y)]),
+        ]),
+        transient: true,
+    };
+    doenet_core::handle_action(dc, drag_point);
+}
+
+pub fn move_point_3d(dc: &DoenetCore, component_name: &'static str, x: f64, y: f64, z: f64) {
+    let move_point = Action {
+        component_name: component_name.to_string(),
+        action_name: "movePoint".to_string(),
+        args: HashMap::from([
+            ("x".to_string(), vec![StateVarValue::Number(x)]),
+            ("y".to_string(), vec![StateVarValue::Number(y)]),
+            ("z".to_string(), vec![StateVarValue::Number(z)]),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_point);
+}
+
+pub fn move_point_coords(dc: &DoenetCore, component_name: &'static str, coords: Vec<StateVarValue>) {
+    let move_point = Action {
+        component_name: component_name.to_string(),
+        action_name: "movePoint".to_string(),
+        args: HashMap::from([
+            ("coords".to_string(), coords),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_point);
+}
+
+pub fn move_line(dc: &DoenetCore, component_name: &'static str, point1: Vec<f64>, point2: Vec<f64>) {
+    let move_line = Action {
+        component_name: component_name.to_string(),
+        action_name: "moveLine".to_string(),
+        args: HashMap::from([
+            ("point1coords".to_string(), point1.into_iter().map(StateVarValue::Number).collect()),
+            ("point2coords".to_string(), point2.into_iter().map(StateVarValue::Number).collect()),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_line);
+}
+
+pub fn change_axis_limits(dc: &DoenetCore, component_name: &'static str, limits: [f64; 4]) {
+    let [xmin, xmax, ymin, ymax] = limits;
+    let change_axis_limits = Action {
+        component_name: component_name.to_string(),
+        action_name: "changeAxisLimits".to_string(),
+        args: HashMap::from([
+            ("xmin".to_string(), vec![StateVarValue::Number(xmin)]),
+            ("xmax".to_string(), vec![StateVarValue::Number(xmax)]),
+            ("ymin".to_string(), vec![StateVarValue::Number(ymin)]),
+            ("ymax".to_string(), vec![StateVarValue::Number(ymax)]),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, change_axis_limits);
+}
+
+pub fn move_circle(dc: &DoenetCore, component_name: &'static str, center: Vec<f64>, radius: Option<f64>) {
+    let mut args = HashMap::from([
+        ("center".to_string(), center.into_iter().map(StateVarValue::Number).collect()),
+    ]);
+    if let Some(radius) = radius {
+        args.insert("radius".to_string(), vec![StateVarValue::Number(radius)]);
+    }
+    let move_circle = Action {
+        component_name: component_name.to_string(),
+        action_name: "moveCircle".to_string(),
+        args,
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_circle);
+}
+
+pub fn move_vertex(dc: &DoenetCore, component_name: &'static str, vertex_index: i64, x: f64, y: f64) {
+    let move_vertex = Action {
+        component_name: component_name.to_string(),
+        action_name: "moveVertex".to_string(),
+        args: HashMap::from([
+            ("vertexIndex".to_string(), vec![StateVarValue::Integer(vertex_index)]),
+            ("x".to_string(), vec![StateVarValue::Number(x)]),
+            ("y".to_string(), vec![StateVarValue::Number(y)]),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_vertex);
+}
+
+pub fn move_through_point(dc: &DoenetCore, component_name: &'static str, through_point_index: i64, x: f64, y: f64) {
+    let move_through_point = Action {
+        component_name: component_name.to_string(),
+        action_name: "moveThroughPoint".to_string(),
+        args: HashMap::from([
+            ("throughPoint".to_string(), vec![StateVarValue::Number(x), StateVarValue::Number(y)]),
+            ("throughPointInd".to_string(), vec![StateVarValue::Integer(through_point_index)]),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_through_point);
+}
+
+pub fn move_control_vector(dc: &DoenetCore, component_name: &'static str, through_point_index: i64, side: i64, x: f64, y: f64) {
+    let move_control_vector = Action {
+        component_name: component_name.to_string(),
+        action_name: "moveControlVector".to_string(),
+        args: HashMap::from([
+            ("controlVector".to_string(), vec![StateVarValue::Number(x), StateVarValue::Number(y)]),
+            ("controlVectorInds".to_string(), vec![StateVarValue::Integer(through_point_index), StateVarValue::Integer(side)]),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, move_control_vector);
+}
+
+pub fn update_selected_indices(dc: &DoenetCore, component_name: &'static str, indices: Vec<StateVarValue>) {
+    let select_choices = Action {
+        component_name: component_name.to_string(),
+        action_name: "updateSelectedIndices".to_string(),
+        args: HashMap::from([
+            ("selectedIndices".to_string(), indices),
+        ]),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, select_choices);
+}
+
+pub fn submit_answer(dc: &DoenetCore, component_name: &'static str) {
+    let submit = Action {
+        component_name: component_name.to_string(),
+        action_name: "submitAnswer".to_string(),
+        args: HashMap::new(),
+        transient: false,
+    };
+    doenet_core::handle_action(dc, submit);
+}
+
+pub fn call_action_with_no_args(dc: &DoenetCore, component_name: &'static str, action_name: &'static str) {
+    let action = Action {
+        component_name: component_name.to_string(),
+        action_name: action_name.to_string(),
+        args: HashMap::new(),
+        transient: false,
     };
-    doenet_core::handle_action(&dc, move_point);
+    doenet_core::handle_action(dc, action);
 }
@@ -5,10 +5,10 @@ mod common_node;
 use std::{collections::HashMap, thread};
 use std::panic::set_hook;
 use doenet_core::parse_json::DoenetMLWarning;
-use serde_json;
 
 use common_node::*;
-use doenet_core::{parse_json::DoenetMLError, state_variables::StateVarValue};
+use doenet_core::{state::EssentialStateVar, state_variables::StateIndex};
+use doenet_core::{parse_json::DoenetMLError, state_variables::StateVarValue, CoreFlags, SolutionDisplayMode};
 use wasm_bindgen_test::{wasm_bindgen_test, console_log};
 
 // ========= DoenetML errrors ============
@@ -47,7 +47,7 @@ fn doenet_ml_error_copy_nonexistent_state_var_gives_error() {
 
     let error = doenet_core_from(DATA).unwrap_err();
     assert_eq!(error, DoenetMLError::StateVarDoesNotExist {
-        comp_name: "a".into(),
+        comp_name: "/a".into(),
         sv_name: "qwertyqwerty".into()
     });
 }
@@ -66,6 +66,38 @@ fn doenet_ml_error_cannot_use_copy_info_as_prop() {
 }
 
 
+#[wasm_bindgen_test]
+fn doenet_ml_error_attribute_component_without_state_var() {
+    static DATA: &str = r#"
+        <graph>
+            <circle name='c1'/>
+            <circle name='c2' center='$c1'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::StateVarDoesNotExist {
+        comp_name: "/c1".into(),
+        sv_name: "xs".into()
+    });
+}
+
+#[wasm_bindgen_test]
+fn doenet_ml_error_prop_index_of_multi_dimensional_state_var() {
+    static DATA: &str = r#"
+        <polygon name='poly' vertices='0 0 4 0 4 3'/>
+        <number copySource='poly' copyProp='numericalVertices' propIndex='2'/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::CannotCopyIndexForMultiDimensionalStateVar {
+        source_comp_name: "/poly".into(),
+        source_sv_name: "numericalVertices",
+    });
+}
+
 // =========== DoenetML warnings ===========
 
 #[wasm_bindgen_test]
@@ -87,6 +119,44 @@ fn doenet_ml_warning_prop_index_not_positive_integer() {
 }
 
 
+#[wasm_bindgen_test]
+fn doenet_ml_warning_array_index_out_of_range() {
+    static DATA: &str = r#"
+    <graph><point name='p' xs='1 2' /></graph>
+    <number name='n' copySource='p' copyProp='z' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let (_, warnings) = doenet_core_from(DATA).unwrap();
+    assert_eq!(
+        warnings,
+        vec![DoenetMLWarning::ArrayIndexOutOfRange {
+            comp_name: "/n".to_string(),
+            source_comp_name: "/p".to_string(),
+            source_sv_name: "xs",
+            index: 3,
+            size: 2,
+        }]
+    )
+}
+
+// =========== Essential data ===========
+
+#[wasm_bindgen_test]
+fn essential_array_size_can_shrink() {
+    let essential = EssentialStateVar::new_array_with_state_var_values(
+        vec![StateVarValue::Integer(1), StateVarValue::Integer(2), StateVarValue::Integer(3)],
+        StateVarValue::Integer(0),
+        0,
+    );
+
+    essential.set_value(StateIndex::SizeOf, StateVarValue::Integer(1), &vec![]).unwrap();
+    assert_eq!(essential.get_value(StateIndex::SizeOf, &vec![]), Some(StateVarValue::Integer(1)));
+
+    essential.set_value(StateIndex::SizeOf, StateVarValue::Integer(2), &vec![]).unwrap();
+    assert_eq!(essential.get_value(StateIndex::SizeOf, &vec![]), Some(StateVarValue::Integer(2)));
+}
+
 // ========= <text> ==============
 
 #[wasm_bindgen_test]
@@ -101,8 +171,8 @@ fn text_preserves_spaces_between_text_tags() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "a", "value", "Hello there!");
-    assert_sv_is_string(&dc, "b", "value", "We could be there.");
+    assert_sv_is_string(&dc, "/a", "value", "Hello there!");
+    assert_sv_is_string(&dc, "/b", "value", "We could be there.");
 }
 
 
@@ -133,9 +203,9 @@ fn text_copy_component_of_copy_component() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "a", "text", "one");
-    assert_sv_is_string(&dc, "b", "text", "onetwo");
-    assert_sv_is_string(&dc, "c", "text", "onetwothree");
+    assert_sv_is_string(&dc, "/a", "text", "one");
+    assert_sv_is_string(&dc, "/b", "text", "onetwo");
+    assert_sv_is_string(&dc, "/c", "text", "onetwothree");
 }
 
 #[wasm_bindgen_test]
@@ -198,13 +268,13 @@ fn text_copy_prop_and_copy_source_combinations_with_additional_children() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "a", "value", "hi");
-    assert_sv_is_string(&dc, "b", "value", "hi");
-    assert_sv_is_string(&dc, "c", "value", "hi more text");
-    assert_sv_is_string(&dc, "d", "value", "hi more text");
-    assert_sv_is_string(&dc, "e", "value", "hi more text");
-    assert_sv_is_string(&dc, "f", "value", "hi");
-    assert_sv_is_string(&dc, "g", "value", "hi more text");
+    assert_sv_is_string(&dc, "/a", "value", "hi");
+    assert_sv_is_string(&dc, "/b", "value", "hi");
+    assert_sv_is_string(&dc, "/c", "value", "hi more text");
+    assert_sv_is_string(&dc, "/d", "value", "hi more text");
+    assert_sv_is_string(&dc, "/e", "value", "hi more text");
+    assert_sv_is_string(&dc, "/f", "value", "hi");
+    assert_sv_is_string(&dc, "/g", "value", "hi more text");
 }
 
 
@@ -243,7 +313,8 @@ fn text_input_update_immediate_value_and_update_value() {
     doenet_core::handle_action(&dc, doenet_core::Action {
         component_name: String::from("/_textInput1"),
         action_name: String::from("updateValue"),
-        args: HashMap::new()
+        args: HashMap::new(),
+        transient: false,
     });
     doenet_core::update_renderers(&dc);
 
@@ -276,8 +347,8 @@ fn text_input_macro() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "t", "value", "Cake");
-    assert_sv_is_string(&dc, "t", "immediateValue", "Cake");
+    assert_sv_is_string(&dc, "/t", "value", "Cake");
+    assert_sv_is_string(&dc, "/t", "immediateValue", "Cake");
     assert_sv_is_string(&dc, "/_text1", "value", "Cake is good.");
 }
 
@@ -297,15 +368,15 @@ fn number_input_immediate_value_syncs_with_value_on_update_request() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    // assert_sv_array_is_number_list(&dc, "myPoint", "xs", vec![0.0, 1.0]);
+    // assert_sv_array_is_number_list(&dc, "/myPoint", "xs", vec![0.0, 1.0]);
 
-    move_point_2d(&dc, "myPoint", StateVarValue::Number(-5.11), StateVarValue::Number(27.0));
+    move_point_2d(&dc, "/myPoint", StateVarValue::Number(-5.11), StateVarValue::Number(27.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "myPoint", "xs", vec![-5.11, 27.0]);
-    assert_sv_is_number(&dc, "the_number_input", "value", -5.11);
-    assert_sv_is_number(&dc, "the_number_input", "immediateValue", -5.11);
-    assert_sv_is_number(&dc, "myNum", "value", -5.11);
+    assert_sv_array_is_number_list(&dc, "/myPoint", "xs", vec![-5.11, 27.0]);
+    assert_sv_is_number(&dc, "/the_number_input", "value", -5.11);
+    assert_sv_is_number(&dc, "/the_number_input", "immediateValue", -5.11);
+    assert_sv_is_number(&dc, "/myNum", "value", -5.11);
 }
 
 #[wasm_bindgen_test]
@@ -318,23 +389,23 @@ fn number_input_value_remains_nan_until_update_value() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "");
-    assert_sv_is_number(&dc, "n", "immediateValue", f64::NAN);
-    assert_sv_is_number(&dc, "n", "value", f64::NAN);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "");
+    assert_sv_is_number(&dc, "/n", "immediateValue", f64::NAN);
+    assert_sv_is_number(&dc, "/n", "value", f64::NAN);
 
-    update_immediate_value_for_number(&dc, "n", "13.0");
+    update_immediate_value_for_number(&dc, "/n", "13.0");
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "13.0");
-    assert_sv_is_number(&dc, "n", "immediateValue", 13.0);
-    assert_sv_is_number(&dc, "n", "value", f64::NAN);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "13.0");
+    assert_sv_is_number(&dc, "/n", "immediateValue", 13.0);
+    assert_sv_is_number(&dc, "/n", "value", f64::NAN);
 
-    update_value_for_number(&dc, "n");
+    update_value_for_number(&dc, "/n");
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "13.0");
-    assert_sv_is_number(&dc, "n", "immediateValue", 13.0);
-    assert_sv_is_number(&dc, "n", "value", 13.0);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "13.0");
+    assert_sv_is_number(&dc, "/n", "immediateValue", 13.0);
+    assert_sv_is_number(&dc, "/n", "value", 13.0);
 }
 
 #[wasm_bindgen_test]
@@ -346,17 +417,17 @@ fn number_input_raw_renderer_value_not_overriden_on_update_value_action() {
     display_doenet_ml_on_failure!(DATA);
     let dc = doenet_core_with_no_warnings(DATA);
 
-    update_immediate_value_for_number(&dc, "n", "non numerical value");
+    update_immediate_value_for_number(&dc, "/n", "non numerical value");
     doenet_core::update_renderers(&dc);
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "non numerical value");
-    assert_sv_is_number(&dc, "n", "immediateValue", f64::NAN);
-    assert_sv_is_number(&dc, "n", "value", f64::NAN);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "non numerical value");
+    assert_sv_is_number(&dc, "/n", "immediateValue", f64::NAN);
+    assert_sv_is_number(&dc, "/n", "value", f64::NAN);
 
-    update_value_for_number(&dc, "n");
+    update_value_for_number(&dc, "/n");
     doenet_core::update_renderers(&dc);
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "non numerical value");
-    assert_sv_is_number(&dc, "n", "immediateValue", f64::NAN);
-    assert_sv_is_number(&dc, "n", "value", f64::NAN);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "non numerical value");
+    assert_sv_is_number(&dc, "/n", "immediateValue", f64::NAN);
+    assert_sv_is_number(&dc, "/n", "value", f64::NAN);
 }
 
 #[wasm_bindgen_test]
@@ -372,29 +443,29 @@ fn number_input_raw_renderer_value_updates_with_bind() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "");
-    assert_sv_is_number(&dc, "n", "immediateValue", f64::NAN);
-    assert_sv_is_number(&dc, "n", "value", f64::NAN);
-    assert_sv_array_is_number_list(&dc, "immediatePoint", "xs", vec![1.0, f64::NAN]);
-    assert_sv_array_is_number_list(&dc, "valuePoint", "xs", vec![2.0, f64::NAN]);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "");
+    assert_sv_is_number(&dc, "/n", "immediateValue", f64::NAN);
+    assert_sv_is_number(&dc, "/n", "value", f64::NAN);
+    assert_sv_array_is_number_list(&dc, "/immediatePoint", "xs", vec![1.0, f64::NAN]);
+    assert_sv_array_is_number_list(&dc, "/valuePoint", "xs", vec![2.0, f64::NAN]);
 
-    move_point_2d(&dc, "immediatePoint", StateVarValue::Number(1.0), StateVarValue::Number(4.0));
+    move_point_2d(&dc, "/immediatePoint", StateVarValue::Number(1.0), StateVarValue::Number(4.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "4");
-    assert_sv_is_number(&dc, "n", "immediateValue", 4.0);
-    assert_sv_array_is_number_list(&dc, "immediatePoint", "xs", vec![1.0, 4.0]);
-    // assert_sv_is_number(&dc, "n", "value", f64::NAN);
-    // assert_sv_array_is_number_list(&dc, "valuePoint", "xs", vec![2.0, f64::NAN]);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "4");
+    assert_sv_is_number(&dc, "/n", "immediateValue", 4.0);
+    assert_sv_array_is_number_list(&dc, "/immediatePoint", "xs", vec![1.0, 4.0]);
+    // assert_sv_is_number(&dc, "/n", "value", f64::NAN);
+    // assert_sv_array_is_number_list(&dc, "/valuePoint", "xs", vec![2.0, f64::NAN]);
 
-    move_point_2d(&dc, "valuePoint", StateVarValue::Number(2.0), StateVarValue::Number(-7.0));
+    move_point_2d(&dc, "/valuePoint", StateVarValue::Number(2.0), StateVarValue::Number(-7.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "n", "rawRendererValue", "-7");
-    assert_sv_is_number(&dc, "n", "immediateValue", -7.0);
-    assert_sv_is_number(&dc, "n", "value", -7.0);
-    assert_sv_array_is_number_list(&dc, "immediatePoint", "xs", vec![1.0, -7.0]);
-    assert_sv_array_is_number_list(&dc, "valuePoint", "xs", vec![2.0, -7.0]);
+    assert_sv_is_string(&dc, "/n", "rawRendererValue", "-7");
+    assert_sv_is_number(&dc, "/n", "immediateValue", -7.0);
+    assert_sv_is_number(&dc, "/n", "value", -7.0);
+    assert_sv_array_is_number_list(&dc, "/immediatePoint", "xs", vec![1.0, -7.0]);
+    assert_sv_array_is_number_list(&dc, "/valuePoint", "xs", vec![2.0, -7.0]);
 }
 
 
@@ -433,14 +504,14 @@ fn collect_and_copy_number_input_changes_original() {
         .as_object().unwrap()
         .get("componentName").unwrap()
         .as_str().unwrap();
-    let copy1 = child_instructions_for(&render_tree, "/_section2", "__cp:input1(/_section2)")
+    let copy1 = child_instructions_for(&render_tree, "/_section2", "__cp:/input1(/_section2)")
         .get("actions").unwrap()
         .as_object().unwrap()
         .get("updateImmediateValue").unwrap()
         .as_object().unwrap()
         .get("componentName").unwrap()
         .as_str().unwrap();
-    let copy2 = child_instructions_for(&render_tree, "/_section2", "__cp:input2(/_section2)")
+    let copy2 = child_instructions_for(&render_tree, "/_section2", "__cp:/input2(/_section2)")
         .get("actions").unwrap()
         .as_object().unwrap()
         .get("updateValue").unwrap()
@@ -448,13 +519,13 @@ fn collect_and_copy_number_input_changes_original() {
         .get("componentName").unwrap()
         .as_str().unwrap();
 
-    assert_eq!(collect1, "input1");
-    assert_eq!(collect2, "input2");
-    assert_eq!(copy1, "input1");
-    assert_eq!(copy2, "input2");
+    assert_eq!(collect1, "/input1");
+    assert_eq!(collect2, "/input2");
+    assert_eq!(copy1, "/input1");
+    assert_eq!(copy2, "/input2");
 
-    assert_sv_is_string(&dc, "input1", "immediateValue", "yolo");
-    assert_sv_is_string(&dc, "input2", "immediateValue", "3");
+    assert_sv_is_string(&dc, "/input1", "immediateValue", "yolo");
+    assert_sv_is_string(&dc, "/input2", "immediateValue", "3");
 }
 
 
@@ -472,9 +543,9 @@ fn collect_point_into_text() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p1", "xs", vec![2.0, 3.0]);
-    assert_sv_array_is_number_list(&dc, "p2", "xs", vec![3.0, 2.0]);
-    assert_sv_is_string(&dc, "t", "value", "(2, 3)(3, 2)");
+    assert_sv_array_is_number_list(&dc, "/p1", "xs", vec![2.0, 3.0]);
+    assert_sv_array_is_number_list(&dc, "/p2", "xs", vec![3.0, 2.0]);
+    assert_sv_is_string(&dc, "/t", "value", "(2, 3)(3, 2)");
 }
 
 #[wasm_bindgen_test]
@@ -497,22 +568,127 @@ fn collect_sequence_changing() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "__mcr:c1:value(/_document1)_1", "value", 8.0);
-    assert_sv_is_number(&dc, "__mcr:seq:value(/_document1)_1", "value", 8.0);
+    assert_sv_is_number(&dc, "__mcr:/c1:value(/_document1)_1", "value", 8.0);
+    assert_sv_is_number(&dc, "__mcr:/seq:value(/_document1)_1", "value", 8.0);
 
     update_immediate_value_for_number(&dc, "/_numberInput1", "30");
     update_value_for_number(&dc, "/_numberInput1");
 
     // console_log!("the update: {:?}", doenet_core::utils::json_components(&dc.component_nodes, &dc.component_states));
-    assert_state_var_stale(&dc, "seq", &vec![], &doenet_core::state_variables::StateRef::ArrayElement("value", 3));
-    assert_state_var_stale(&dc, "__mcr:seq:value(/_document1)_1", &vec![], &doenet_core::state_variables::StateRef::Basic("value"));
+    assert_state_var_stale(&dc, "/seq", &vec![], &doenet_core::state_variables::StateRef::ArrayElement("value", vec![3]));
+    assert_state_var_stale(&dc, "__mcr:/seq:value(/_document1)_1", &vec![], &doenet_core::state_variables::StateRef::Basic("value"));
 
     doenet_core::update_renderers(&dc);
 
     assert_sv_is_number(&dc, "/_numberInput1", "value", 30.0);
-    assert_sv_array_is_number_list(&dc, "seq", "value", vec![30.0, 31.0, 32.0, 33.0, 34.0, 35.0]);
-    assert_sv_is_number(&dc, "__mcr:c1:value(/_document1)_1", "value", 32.0);
-    assert_sv_is_number(&dc, "__mcr:seq:value(/_document1)_1", "value", 32.0);
+    assert_sv_array_is_number_list(&dc, "/seq", "value", vec![30.0, 31.0, 32.0, 33.0, 34.0, 35.0]);
+    assert_sv_is_number(&dc, "__mcr:/c1:value(/_document1)_1", "value", 32.0);
+    assert_sv_is_number(&dc, "__mcr:/seq:value(/_document1)_1", "value", 32.0);
+}
+
+#[wasm_bindgen_test]
+fn collect_descendants_of_several_types() {
+    static DATA: &str = r#"
+        <numberInput name="n" prefill="2"/>
+        <section name="s">
+            <p><number>10</number><text>a</text></p>
+            <section>
+                <map>
+                    <sources alias="v"><sequence to="$n.value"/></sources>
+                    <template><p><number>$v</number></p></template>
+                </map>
+            </section>
+        </section>
+
+        <p name="all"><collect source="$s" componentType="number, text"/></p>
+        <p name="few"><collect source="s" componentType="number" maxNumber="2"/></p>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    update_immediate_value_for_number(&dc, "/n", "3");
+    update_value_for_number(&dc, "/n");
+    let render_tree = doenet_core::update_renderers(&dc);
+    let render_tree: serde_json::Value = serde_json::from_str(&render_tree).unwrap();
+
+    let children_of = |parent: &'static str| -> Vec<String> {
+        render_data_of_component(&render_tree, parent)["childrenInstructions"]
+            .as_array().unwrap().iter()
+            .map(|child| child["componentName"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(children_of("/all"), vec![
+        "__number_from_(/_collect1[1])",
+        "__text_from_(/_collect1[2])",
+        "__number_from_(/_collect1[3])",
+        "__number_from_(/_collect1[4])",
+        "__number_from_(/_collect1[5])",
+    ]);
+    assert_eq!(children_of("/few"), vec![
+        "__number_from_(/_collect2[1])",
+        "__number_from_(/_collect2[2])",
+    ]);
+
+    let member = render_data_of_component(&render_tree, "__number_from_(/_collect1[5])");
+    assert_eq!(member["stateValues"]["value"], 3.0);
+    let member = render_data_of_component(&render_tree, "__text_from_(/_collect1[2])");
+    assert_eq!(member["stateValues"]["text"], "a");
+}
+
+#[wasm_bindgen_test]
+fn collect_prop_into_list() {
+    static DATA: &str = r#"
+        <graph name="g">
+            <point name="a" xs="1 2"/>
+            <point xs="3 4"/>
+        </graph>
+        <collect name="c" source="g" componentType="point"/>
+        <p name="p">$c.x</p>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "__mcr:/c:x(/p)_1", "value", vec![1.0, 3.0]);
+
+    move_point_2d(&dc, "/a", StateVarValue::Number(7.0), StateVarValue::Number(8.0));
+    let render_tree = doenet_core::update_renderers(&dc);
+    let render_tree: serde_json::Value = serde_json::from_str(&render_tree).unwrap();
+
+    assert_sv_array_is_number_list(&dc, "__mcr:/c:x(/p)_1", "value", vec![7.0, 3.0]);
+    let x = render_data_of_component(&render_tree, "__number_from_(__mcr:/c:x(/p)_1[1])");
+    assert_eq!(x["stateValues"]["value"], 7.0);
+}
+
+#[wasm_bindgen_test]
+fn doenet_ml_error_collect_prop_of_members_of_different_types() {
+    static DATA: &str = r#"
+        <section name="s"><number>1</number><text>a</text></section>
+        <collect name="c" source="s" componentType="number, text"/>
+        <p>$c.value</p>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::CannotCopyPropOfMembersOfDifferentTypes {
+        source_comp_name: "/c".into(),
+        source_sv_name: "value".into(),
+    });
+}
+
+#[wasm_bindgen_test]
+fn doenet_ml_error_collect_unknown_component_type() {
+    static DATA: &str = r#"
+        <section name="s"><number>1</number></section>
+        <collect source="s" componentType="number, nope"/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::InvalidComponentType { comp_type: "nope".into() });
 }
 
 // ========= <sequence> ==============
@@ -559,32 +735,32 @@ fn sequence_can_grow_and_shrink() {
     "#;
     display_doenet_ml_on_failure!(DATA);
     let dc = doenet_core_with_no_warnings(DATA);
-    update_immediate_value_for_number(&dc, "ni", "8.0");
-    update_value_for_number(&dc, "ni");
+    update_immediate_value_for_number(&dc, "/ni", "8.0");
+    update_value_for_number(&dc, "/ni");
     doenet_core::update_renderers(&dc);
 
     assert_sv_array_is_number_list(&dc, "/_sequence1", "value", vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
 
-    update_immediate_value_for_number(&dc, "ni", "9");
-    update_value_for_number(&dc, "ni");
+    update_immediate_value_for_number(&dc, "/ni", "9");
+    update_value_for_number(&dc, "/ni");
     doenet_core::update_renderers(&dc);
 
     assert_sv_array_is_number_list(&dc, "/_sequence1", "value", vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
 
-    update_immediate_value_for_number(&dc, "ni", "2.0");
-    update_value_for_number(&dc, "ni");
+    update_immediate_value_for_number(&dc, "/ni", "2.0");
+    update_value_for_number(&dc, "/ni");
     doenet_core::update_renderers(&dc);
 
     assert_sv_array_is_number_list(&dc, "/_sequence1", "value", vec![1.0, 2.0]);
 
-    update_immediate_value_for_number(&dc, "ni", "asdf");
-    update_value_for_number(&dc, "ni");
+    update_immediate_value_for_number(&dc, "/ni", "asdf");
+    update_value_for_number(&dc, "/ni");
     doenet_core::update_renderers(&dc);
 
     assert_sv_array_is_number_list(&dc, "/_sequence1", "value", vec![]);
 
-    update_immediate_value_for_number(&dc, "ni", "-3");
-    update_value_for_number(&dc, "ni");
+    update_immediate_value_for_number(&dc, "/ni", "-3");
+    update_value_for_number(&dc, "/ni");
     doenet_core::update_renderers(&dc);
 
     assert_sv_array_is_number_list(&dc, "/_sequence1", "value", vec![]);
@@ -626,14 +802,14 @@ fn sequence_index_copied_based_on_number_input() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    update_immediate_value_for_number(&dc, "n", "5.0");
-    update_value_for_number(&dc, "n");
+    update_immediate_value_for_number(&dc, "/n", "5.0");
+    update_value_for_number(&dc, "/n");
     doenet_core::update_renderers(&dc);
 
     assert_sv_is_number(&dc, "/_number1", "value", 14.0);
 
-    update_immediate_value_for_number(&dc, "n", "2");
-    update_value_for_number(&dc, "n");
+    update_immediate_value_for_number(&dc, "/n", "2");
+    update_value_for_number(&dc, "/n");
     doenet_core::update_renderers(&dc);
 
     assert_sv_is_number(&dc, "/_number1", "value", 11.0);
@@ -661,8 +837,8 @@ fn sequence_macro_component_index() {
     assert_sv_is_string(&dc, "/_text3", "value", "Fifth: 5");
     assert_sv_is_string(&dc, "/_text4", "value", "Fifth: 5");
 
-    update_immediate_value_for_number(&dc, "n", "6");
-    update_value_for_number(&dc, "n");
+    update_immediate_value_for_number(&dc, "/n", "6");
+    update_value_for_number(&dc, "/n");
     doenet_core::update_renderers(&dc);
 
     assert_sv_is_string(&dc, "/_text1", "value", "Fifth:10.");
@@ -696,36 +872,111 @@ fn sequence_dynamic_length() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "n", "value", 4.0);
-    assert_sv_is_string(&dc, "t", "value", "1234");
+    assert_sv_is_number(&dc, "/n", "value", 4.0);
+    assert_sv_is_string(&dc, "/t", "value", "1234");
+
+    update_immediate_value_for_number(&dc, "/n", "10.0");
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/n", "immediateValue", 10.0);
+    assert_sv_is_number(&dc, "/n", "value", 4.0);
+    assert_sv_is_string(&dc, "/t", "value", "1234");
+
+    update_value_for_number(&dc, "/n");
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/n", "immediateValue", 10.0);
+    assert_sv_is_number(&dc, "/n", "value", 10.0);
+    assert_sv_is_string(&dc, "/t", "value", "12345678910");
+
+    update_immediate_value_for_number(&dc, "/n", "8.0");
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/n", "immediateValue", 8.0);
+    assert_sv_is_number(&dc, "/n", "value", 10.0);
+    assert_sv_is_string(&dc, "/t", "value", "12345678910");
+
+    update_value_for_number(&dc, "/n");
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/n", "immediateValue", 8.0);
+    assert_sv_is_number(&dc, "/n", "value", 8.0);
+    assert_sv_is_string(&dc, "/t", "value", "12345678");
+}
+
+// ========= Lists ==============
+
+#[wasm_bindgen_test]
+fn lists_from_children_and_strings() {
+    static DATA: &str = r#"
+        <numberList name="nl" maxNumber="4">1 2 <number>3</number> 4   5</numberList>
+        <textList name="tl">a b <text>c d</text></textList>
+        <booleanList name="bl">true FALSE <boolean>true</boolean></booleanList>
+        <mathList name="ml" merge="true">1,2 3+4</mathList>
+        <mathList name="ml2">1,2 3+4</mathList>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree = doenet_core::update_renderers(&dc);
+    let render_tree: serde_json::Value = serde_json::from_str(&render_tree).unwrap();
+
+    assert_sv_array_is_number_list(&dc, "/nl", "value", vec![1.0, 2.0, 3.0, 4.0]);
+    assert_sv_array_is_boolean_list(&dc, "/bl", "value", vec![true, false, true]);
+    assert_sv_array_is_number_list(&dc, "/ml", "numericalValues", vec![1.0, 2.0, 7.0]);
+    assert_sv_array_size_is(&dc, "/ml2", "value", 2);
+    assert_sv_array_element_is_number(&dc, "/ml2", "numericalValues", 1, 7.0);
+
+    let text = render_data_of_component(&render_tree, "__text_from_(/tl[3])");
+    assert_eq!(text["stateValues"]["text"], "c d");
+}
+
+#[wasm_bindgen_test]
+fn lists_copy_array_props() {
+    static DATA: &str = r#"
+        <point name="p" xs="1 2"/>
+        <numberList name="nl" copySource="p" copyProp="xs"/>
+        <p name="q">$p.xs</p>
+        <point name="p2" xs="$nl[2] 5"/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
 
-    update_immediate_value_for_number(&dc, "n", "10.0");
+    let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "n", "immediateValue", 10.0);
-    assert_sv_is_number(&dc, "n", "value", 4.0);
-    assert_sv_is_string(&dc, "t", "value", "1234");
+    assert_sv_array_is_number_list(&dc, "/nl", "value", vec![1.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "__mcr:/p:xs(/q)_1", "value", vec![1.0, 2.0]);
 
-    update_value_for_number(&dc, "n");
+    move_point_2d(&dc, "/p2", StateVarValue::Number(7.0), StateVarValue::Number(8.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "n", "immediateValue", 10.0);
-    assert_sv_is_number(&dc, "n", "value", 10.0);
-    assert_sv_is_string(&dc, "t", "value", "12345678910");
+    assert_sv_array_is_number_list(&dc, "/p", "numericalXs", vec![1.0, 7.0]);
+    assert_sv_array_is_number_list(&dc, "/nl", "value", vec![1.0, 7.0]);
+}
+
+#[wasm_bindgen_test]
+fn list_items_update_their_child() {
+    static DATA: &str = r#"
+        <numberList name="nl">1  2 <number>3</number></numberList>
+        <point name="p" xs="$nl[2] $nl[3]"/>
+        <mathList name="ml">1 2+3</mathList>
+        <point name="q" xs="$ml[2] $ml[1]"/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
 
-    update_immediate_value_for_number(&dc, "n", "8.0");
+    move_point_2d(&dc, "/p", StateVarValue::Number(7.0), StateVarValue::Number(8.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "n", "immediateValue", 8.0);
-    assert_sv_is_number(&dc, "n", "value", 10.0);
-    assert_sv_is_string(&dc, "t", "value", "12345678910");
+    assert_sv_array_is_number_list(&dc, "/nl", "value", vec![1.0, 7.0, 8.0]);
+    assert_sv_is_number(&dc, "/_number1", "value", 8.0);
 
-    update_value_for_number(&dc, "n");
+    move_point_2d(&dc, "/q", StateVarValue::Number(4.0), StateVarValue::Number(5.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "n", "immediateValue", 8.0);
-    assert_sv_is_number(&dc, "n", "value", 8.0);
-    assert_sv_is_string(&dc, "t", "value", "12345678");
+    assert_sv_array_is_number_list(&dc, "/ml", "numericalValues", vec![5.0, 4.0]);
 }
 
 // ========= <point> ==============
@@ -740,12 +991,12 @@ fn point_moves_copy_number() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p", "xs", vec![3.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![3.0, 2.0]);
 
-    move_point_2d(&dc, "p", StateVarValue::Integer(5), StateVarValue::Number(1.0));
+    move_point_2d(&dc, "/p", StateVarValue::Integer(5), StateVarValue::Number(1.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p", "xs", vec![5.0, 1.0]);
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![5.0, 1.0]);
 }
 
 #[wasm_bindgen_test]
@@ -760,14 +1011,14 @@ fn point_copies_coords_of_another_point() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "a", "xs", vec![1.0, 2.0]);
-    assert_sv_array_is_number_list(&dc, "b", "xs", vec![-3.0, 4.0]);
+    assert_sv_array_is_number_list(&dc, "/a", "xs", vec![1.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/b", "xs", vec![-3.0, 4.0]);
 
-    move_point_2d(&dc, "a", StateVarValue::Number(-2.0), StateVarValue::Number(-5.0));
+    move_point_2d(&dc, "/a", StateVarValue::Number(-2.0), StateVarValue::Number(-5.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "a", "xs", vec![-2.0, -5.0]);
-    assert_sv_array_is_number_list(&dc, "b", "xs", vec![-6.0, -3.0]);
+    assert_sv_array_is_number_list(&dc, "/a", "xs", vec![-2.0, -5.0]);
+    assert_sv_array_is_number_list(&dc, "/b", "xs", vec![-6.0, -3.0]);
 }
 
 #[wasm_bindgen_test]
@@ -782,18 +1033,18 @@ fn point_copies_another_point_component() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p1", "xs", vec![1.0, 2.0]);
-    assert_sv_array_is_number_list(&dc, "p2", "xs", vec![1.0, 2.0]);
-    assert_sv_array_is_number_list(&dc, "p3", "xs", vec![1.0, 2.0]);
-    assert_sv_array_is_number_list(&dc, "p4", "xs", vec![1.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/p1", "xs", vec![1.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/p2", "xs", vec![1.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/p3", "xs", vec![1.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/p4", "xs", vec![1.0, 2.0]);
 
-    move_point_2d(&dc, "p2", StateVarValue::Number(-3.2), StateVarValue::Number(7.1));
+    move_point_2d(&dc, "/p2", StateVarValue::Number(-3.2), StateVarValue::Number(7.1));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p1", "xs", vec![-3.2, 7.1]);
-    assert_sv_array_is_number_list(&dc, "p2", "xs", vec![-3.2, 7.1]);
-    assert_sv_array_is_number_list(&dc, "p3", "xs", vec![-3.2, 7.1]);
-    assert_sv_array_is_number_list(&dc, "p4", "xs", vec![-3.2, 7.1]);
+    assert_sv_array_is_number_list(&dc, "/p1", "xs", vec![-3.2, 7.1]);
+    assert_sv_array_is_number_list(&dc, "/p2", "xs", vec![-3.2, 7.1]);
+    assert_sv_array_is_number_list(&dc, "/p3", "xs", vec![-3.2, 7.1]);
+    assert_sv_array_is_number_list(&dc, "/p4", "xs", vec![-3.2, 7.1]);
 }
 
 #[wasm_bindgen_test]
@@ -814,17 +1065,273 @@ fn point_used_with_prop_index() {
 
     assert_sv_is_number(&dc, "/_number2", "value", 11.0);
 
-    move_point_2d(&dc, "p", StateVarValue::Integer(5), StateVarValue::Number(4.123123));
+    move_point_2d(&dc, "/p", StateVarValue::Integer(5), StateVarValue::Number(4.123123));
     doenet_core::update_renderers(&dc);
 
     assert_sv_is_number(&dc, "/_number2", "value", f64::NAN);
 
-    move_point_2d(&dc, "p", StateVarValue::Integer(5), StateVarValue::Integer(4));
+    move_point_2d(&dc, "/p", StateVarValue::Integer(5), StateVarValue::Integer(4));
     doenet_core::update_renderers(&dc);
 
     assert_sv_is_number(&dc, "/_number2", "value", 13.0);
 }
 
+#[wasm_bindgen_test]
+fn point_dimensions_follow_coords() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='p' coords='1 2 3' />
+        <point name='q' copySource='p' />
+    </graph>
+    <number name='z' copySource='p' copyProp='z' />
+    <text name='t' copySource='q' copyProp='text' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/q", "xs", vec![1.0, 2.0, 3.0]);
+    assert_sv_is_string(&dc, "/p", "latex", "(1, 2, 3)");
+    assert_sv_is_string(&dc, "/t", "value", "(1, 2, 3)");
+    assert_sv_is_number(&dc, "/z", "value", 3.0);
+
+    move_point_3d(&dc, "/q", 4.0, 5.0, -6.5);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![4.0, 5.0, -6.5]);
+    assert_sv_is_string(&dc, "/t", "value", "(4, 5, -6.5)");
+    assert_sv_is_number(&dc, "/z", "value", -6.5);
+}
+
+#[wasm_bindgen_test]
+fn point_ignores_coordinates_past_its_dimensions() {
+    static DATA: &str = r#"
+    <graph><point name='p' xs='1 2' /></graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    move_point_3d(&dc, "/p", 3.0, 4.0, 5.0);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![3.0, 4.0]);
+    assert_sv_is_string(&dc, "/p", "latex", "(3, 4)");
+}
+
+#[wasm_bindgen_test]
+fn point_moves_to_coords_matching_its_dimensions() {
+    static DATA: &str = r#"
+    <graph><point name='p' xs='1 2 3 4 5' /></graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    move_point_coords(&dc, "/p", [5.0, 4.0, 3.0, 2.0, 1.0].map(StateVarValue::Number).to_vec());
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![5.0, 4.0, 3.0, 2.0, 1.0]);
+
+    // Coordinates that do not match the dimensions or are not numbers are ignored
+    move_point_coords(&dc, "/p", [0.0, 0.0].map(StateVarValue::Number).to_vec());
+    move_point_coords(&dc, "/p", vec![
+        StateVarValue::Number(0.0),
+        StateVarValue::String("a".to_string()),
+        StateVarValue::Number(0.0),
+        StateVarValue::Number(0.0),
+        StateVarValue::Number(0.0),
+    ]);
+    move_point_2d(&dc, "/p", StateVarValue::Number(0.0), StateVarValue::Boolean(true));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![5.0, 4.0, 3.0, 2.0, 1.0]);
+}
+
+// ========= Point constraints ===========
+
+#[wasm_bindgen_test]
+fn point_constrained_to_grid_for_actions_and_copied_values() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='free' xs='1.3 2.6'/>
+        <point name='snapped' xs='$free.x $free.y'>
+            <constraints><constrainToGrid dx='2' dy='1'/></constraints>
+        </point>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/snapped", "xs", vec![2.0, 3.0]);
+
+    move_point_2d(&dc, "/free", StateVarValue::Number(4.9), StateVarValue::Number(0.2));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/snapped", "xs", vec![4.0, 0.0]);
+
+    // The action moves the copied coordinates, which are then constrained
+    move_point_2d(&dc, "/snapped", StateVarValue::Number(-3.2), StateVarValue::Number(1.6));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/snapped", "xs", vec![-4.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/free", "xs", vec![-3.2, 1.6]);
+}
+
+#[wasm_bindgen_test]
+fn point_constrained_to_circle_and_attracted_to_point() {
+    static DATA: &str = r#"
+    <graph>
+        <circle name='c' center='0 0' radius='5'/>
+        <point name='onCircle' xs='3 0'>
+            <constraints><constrainTo>$c</constrainTo></constraints>
+        </point>
+        <point name='Q' xs='10 10'/>
+        <point name='attracted' xs='9.8 9.5'>
+            <constraints><attractTo threshold='1'>$Q</attractTo></constraints>
+        </point>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/onCircle", "xs", vec![5.0, 0.0]);
+    assert_sv_array_is_number_list(&dc, "/attracted", "xs", vec![10.0, 10.0]);
+
+    move_point_2d(&dc, "/onCircle", StateVarValue::Number(0.0), StateVarValue::Number(-2.0));
+    move_point_2d(&dc, "/attracted", StateVarValue::Number(5.0), StateVarValue::Number(5.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/onCircle", "xs", vec![0.0, -5.0]);
+    assert_sv_array_is_number_list(&dc, "/attracted", "xs", vec![5.0, 5.0]);
+
+    move_point_2d(&dc, "/attracted", StateVarValue::Number(10.3), StateVarValue::Number(10.4));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/attracted", "xs", vec![10.0, 10.0]);
+}
+
+#[wasm_bindgen_test]
+fn point_constraints_compose_in_order() {
+    static DATA: &str = r#"
+    <graph>
+        <circle name='c' center='0 0' radius='5'/>
+        <polygon name='poly' vertices='0 0 4 0 4 3'/>
+        <point name='inside' xs='10 1'>
+            <constraints><constrainToInterior>$poly</constrainToInterior></constraints>
+        </point>
+        <point name='composed' xs='0.4 0.4'>
+            <constraints><constrainToGrid/><constrainTo>$c</constrainTo></constraints>
+        </point>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/inside", "xs", vec![4.0, 1.0]);
+
+    // Snapping to (0, 0) comes first, so the circle gets its rightmost point
+    assert_sv_array_is_number_list(&dc, "/composed", "xs", vec![5.0, 0.0]);
+
+    move_point_2d(&dc, "/inside", StateVarValue::Number(3.0), StateVarValue::Number(1.0));
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/inside", "xs", vec![3.0, 1.0]);
+
+    move_point_2d(&dc, "/inside", StateVarValue::Number(2.0), StateVarValue::Number(-3.0));
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/inside", "xs", vec![2.0, 0.0]);
+}
+
+// ========= Style definitions ===========
+
+#[wasm_bindgen_test]
+fn graphical_components_select_style_by_number() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='A' xs='1 2'/>
+            <point name='B' xs='3 4' styleNumber='2'/>
+            <circle name='c' styleNumber='6'/>
+            <polygon name='poly' vertices='0 0 4 0 4 3' styleNumber='4'/>
+            <point name='v2' copySource='poly' copyCollection='vertices' componentIndex='2'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string(&dc, "/A", "markerColorWord", "blue");
+    assert_sv_is_string(&dc, "/A", "markerStyleWord", "point");
+    assert_sv_is_string(&dc, "/B", "markerColor", "#D4042D");
+    assert_sv_is_string(&dc, "/B", "markerStyle", "square");
+    assert_sv_is_string(&dc, "/c", "lineStyleWord", "dotted");
+    assert_sv_is_number(&dc, "/c", "lineWidth", 1.0);
+    assert_sv_is_string(&dc, "/poly", "lineColorWord", "purple");
+
+    // A vertex has the style of its polygon
+    assert_sv_is_string(&dc, "/v2", "markerStyle", "diamond");
+}
+
+#[wasm_bindgen_test]
+fn style_definitions_change_styles_and_fill_in_words() {
+    static DATA: &str = r#"
+        <styleDefinitions>
+            <styleDefinition styleNumber='2' lineColor='Green' lineWidth='1' markerStyle='triangleDown'/>
+            <styleDefinition styleNumber='7' markerColor='pink' markerColorWord='rose'/>
+        </styleDefinitions>
+        <graph>
+            <line name='l' p1='0 0' p2='1 1' styleNumber='2'/>
+            <point name='P' xs='0 0' styleNumber='2'/>
+            <point name='Q' xs='0 0' styleNumber='7'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string(&dc, "/l", "lineColor", "green");
+    assert_sv_is_string(&dc, "/l", "lineColorWord", "green");
+    assert_sv_is_string(&dc, "/l", "lineWidthWord", "thin");
+    assert_sv_is_string(&dc, "/P", "markerStyleWord", "triangle");
+
+    // Properties that were not given keep the existing definition
+    assert_sv_is_string(&dc, "/P", "markerColorWord", "red");
+
+    // A new style number starts from the default style
+    assert_sv_is_string(&dc, "/Q", "markerColorWord", "rose");
+    assert_sv_is_string(&dc, "/Q", "lineColorWord", "blue");
+}
+
+#[wasm_bindgen_test]
+fn selected_style_is_rendered_as_an_object() {
+    static DATA: &str = r#"
+        <styleDefinitions>
+            <styleDefinition styleNumber='3' markerSize='6'/>
+        </styleDefinitions>
+        <graph name='g'>
+            <point name='P' xs='0 0' styleNumber='3'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    let selected_style = render_data_of_component(&render_tree, "/P")
+        .get("stateValues").unwrap()
+        .get("selectedStyle").unwrap();
+    assert_eq!(selected_style.get("markerColorWord"), Some(&serde_json::json!("orange")));
+    assert_eq!(selected_style.get("markerSize"), Some(&serde_json::json!(6.0)));
+
+    // Style definitions are not rendered
+    let document_children = render_data_of_component(&render_tree, "/_document1")
+        .get("childrenInstructions").unwrap()
+        .as_array().unwrap();
+    assert!(document_children.iter().all(|child| child.get("componentType") != Some(&serde_json::json!("styleDefinitions"))));
+}
+
 // ========= Map ===========
 #[wasm_bindgen_test]
 fn map_complicated_sources() {
@@ -848,13 +1355,13 @@ fn map_complicated_sources() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string_with_map(&dc, "t", vec![1] ,"value", "3 squared is 9");
-    assert_sv_is_string_with_map(&dc, "t", vec![2] ,"value", "4 squared is 16");
-    assert_sv_is_string_with_map(&dc, "t", vec![3] ,"value", "1 squared is 1");
-    assert_sv_is_string_with_map(&dc, "t", vec![4] ,"value", "2 squared is 4");
-    assert_sv_is_string_with_map(&dc, "t", vec![5] ,"value", "3 squared is 9");
-    assert_sv_is_string_with_map(&dc, "t", vec![6] ,"value", "8 squared is 64");
-    assert_sv_is_string_with_map(&dc, "t", vec![7] ,"value", "2 squared is 4");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1] ,"value", "3 squared is 9");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2] ,"value", "4 squared is 16");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3] ,"value", "1 squared is 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![4] ,"value", "2 squared is 4");
+    assert_sv_is_string_with_map(&dc, "/t", vec![5] ,"value", "3 squared is 9");
+    assert_sv_is_string_with_map(&dc, "/t", vec![6] ,"value", "8 squared is 64");
+    assert_sv_is_string_with_map(&dc, "/t", vec![7] ,"value", "2 squared is 4");
 }
 
 #[wasm_bindgen_test]
@@ -890,22 +1397,22 @@ fn maps_in_maps() {
     doenet_core::update_renderers(&dc);
 
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![1], "value", 1);
-    assert_sv_is_string_with_map(&dc, "t", vec![1,1,1], "value", "1 is x using 1 and 1");
-    assert_sv_is_string_with_map(&dc, "t", vec![1,1,2], "value", "1 is y using 1 and 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,1,1], "value", "1 is x using 1 and 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,1,2], "value", "1 is y using 1 and 1");
 
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![2], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![2,1,1], "value", "2 is x using 2 and 1");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,1,2], "value", "1 is y using 2 and 1");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,2,1], "value", "2 is x using 2 and 2");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,2,2], "value", "2 is y using 2 and 2");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,1,1], "value", "2 is x using 2 and 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,1,2], "value", "1 is y using 2 and 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,2,1], "value", "2 is x using 2 and 2");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,2,2], "value", "2 is y using 2 and 2");
 
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![3], "value", 3);
-    assert_sv_is_string_with_map(&dc, "t", vec![3,1,1], "value", "3 is x using 3 and 1");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,1,2], "value", "1 is y using 3 and 1");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,2,1], "value", "3 is x using 3 and 2");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,2,2], "value", "2 is y using 3 and 2");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,3,1], "value", "3 is x using 3 and 3");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,3,2], "value", "3 is y using 3 and 3");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,1,1], "value", "3 is x using 3 and 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,1,2], "value", "1 is y using 3 and 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,2,1], "value", "3 is x using 3 and 2");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,2,2], "value", "2 is y using 3 and 2");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,3,1], "value", "3 is x using 3 and 3");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,3,2], "value", "3 is y using 3 and 3");
 }
 
 
@@ -936,30 +1443,30 @@ fn map_dynamic_size() {
 
     assert_sv_array_size_is_with_map(&dc, "/_sequence1", vec![], "value", 1);
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![1], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![1,1], "value", "(1, 1) with size (1, 2)");
-    assert_sv_is_string_with_map(&dc, "t", vec![1,2], "value", "(1, 2) with size (1, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,1], "value", "(1, 1) with size (1, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,2], "value", "(1, 2) with size (1, 2)");
 
 
-    update_immediate_value_for_number(&dc, "i", "3.0");
+    update_immediate_value_for_number(&dc, "/i", "3.0");
     doenet_core::update_renderers(&dc);
     assert_sv_array_size_is_with_map(&dc, "/_sequence1", vec![], "value", 1);
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![1], "value", 2);
 
-    update_value_for_number(&dc, "i");
+    update_value_for_number(&dc, "/i");
     doenet_core::update_renderers(&dc);
     assert_sv_array_size_is_with_map(&dc, "/_sequence1", vec![], "value", 3);
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![1], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![1,1], "value", "(1, 1) with size (3, 2)");
-    assert_sv_is_string_with_map(&dc, "t", vec![1,2], "value", "(1, 2) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,1], "value", "(1, 1) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,2], "value", "(1, 2) with size (3, 2)");
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![2], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![2,1], "value", "(2, 1) with size (3, 2)");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,2], "value", "(2, 2) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,1], "value", "(2, 1) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,2], "value", "(2, 2) with size (3, 2)");
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![3], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![3,1], "value", "(3, 1) with size (3, 2)");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,2], "value", "(3, 2) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,1], "value", "(3, 1) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,2], "value", "(3, 2) with size (3, 2)");
 
 
-    let action_name = r#"[2]j"#;
+    let action_name = r#"[2]/j"#;
     update_immediate_value_for_number(&dc, action_name, "4.0");
     doenet_core::update_renderers(&dc);
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![2], "value", 2);
@@ -968,16 +1475,16 @@ fn map_dynamic_size() {
     doenet_core::update_renderers(&dc);
     assert_sv_array_size_is_with_map(&dc, "/_sequence1", vec![], "value", 3);
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![1], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![1,1], "value", "(1, 1) with size (3, 2)");
-    assert_sv_is_string_with_map(&dc, "t", vec![1,2], "value", "(1, 2) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,1], "value", "(1, 1) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1,2], "value", "(1, 2) with size (3, 2)");
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![2], "value", 4);
-    assert_sv_is_string_with_map(&dc, "t", vec![2,1], "value", "(2, 1) with size (3, 4)");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,2], "value", "(2, 2) with size (3, 4)");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,3], "value", "(2, 3) with size (3, 4)");
-    assert_sv_is_string_with_map(&dc, "t", vec![2,4], "value", "(2, 4) with size (3, 4)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,1], "value", "(2, 1) with size (3, 4)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,2], "value", "(2, 2) with size (3, 4)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,3], "value", "(2, 3) with size (3, 4)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2,4], "value", "(2, 4) with size (3, 4)");
     assert_sv_array_size_is_with_map(&dc, "/_sequence2", vec![3], "value", 2);
-    assert_sv_is_string_with_map(&dc, "t", vec![3,1], "value", "(3, 1) with size (3, 2)");
-    assert_sv_is_string_with_map(&dc, "t", vec![3,2], "value", "(3, 2) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,1], "value", "(3, 1) with size (3, 2)");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3,2], "value", "(3, 2) with size (3, 2)");
 }
 
 #[wasm_bindgen_test]
@@ -999,22 +1506,22 @@ fn map_move_points() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list_with_map(&dc, "p", vec![1] , "numericalXs", vec![5.0, 2.0]);
-    assert_sv_array_is_number_list_with_map(&dc, "p", vec![2] , "numericalXs", vec![3.0, 2.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![1] , "numericalXs", vec![5.0, 2.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![2] , "numericalXs", vec![3.0, 2.0]);
 
-    let first_instance = r#"[1]p"#;
+    let first_instance = r#"[1]/p"#;
     move_point_2d(&dc, first_instance, StateVarValue::Integer(2), StateVarValue::Integer(4));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list_with_map(&dc, "p", vec![1] , "numericalXs", vec![5.0, 4.0]);
-    assert_sv_array_is_number_list_with_map(&dc, "p", vec![2] , "numericalXs", vec![3.0, 2.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![1] , "numericalXs", vec![5.0, 4.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![2] , "numericalXs", vec![3.0, 2.0]);
 
-    let second_instance = r#"[2]p"#;
+    let second_instance = r#"[2]/p"#;
     move_point_2d(&dc, second_instance, StateVarValue::Integer(1), StateVarValue::Integer(6));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list_with_map(&dc, "p", vec![1] , "numericalXs", vec![5.0, 4.0]);
-    assert_sv_array_is_number_list_with_map(&dc, "p", vec![2] , "numericalXs", vec![3.0, 6.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![1] , "numericalXs", vec![5.0, 4.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![2] , "numericalXs", vec![3.0, 6.0]);
 }
 
 #[wasm_bindgen_test]
@@ -1038,17 +1545,221 @@ fn map_inside_text() {
     assert_sv_is_string(&dc, "/_text1", "value", "some cow but then it answers yes then it answers no and some horse but then it answers yes then it answers no and they left");
 }
 
-// ========= <conditionalContent> ===========
-
 #[wasm_bindgen_test]
-fn conditional_content_updating() {
+fn map_nested_sources() {
     static DATA: &str = r#"
-    <numberinput name="n" prefill="2"/>
-    <text>Description: <conditionalContent>
-        <case condition="$n.value>=0.0">positive, </case>
-        <case condition="$n.value<0.0">negative, </case>
-        <case condition="$n.value>2.0">greater than 2, </case>
-        <case condition="$n.value>1.0">greater than 1, </case>
+    <map>
+        <sources alias="x">
+            <sources>
+                <number>5</number>
+                <sequence from="1" to="2"/>
+            </sources>
+            <number>7</number>
+        </sources>
+        <template>
+            <text name="t">got $x</text>
+        </template>
+    </map>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string_with_map(&dc, "/t", vec![1], "value", "got 5");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2], "value", "got 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3], "value", "got 2");
+    assert_sv_is_string_with_map(&dc, "/t", vec![4], "value", "got 7");
+}
+
+#[wasm_bindgen_test]
+fn map_sources_copying_other_sources() {
+    static DATA: &str = r#"
+    <map>
+        <sources name="animals" alias="a">
+            <text>cow</text>
+            <text>horse</text>
+        </sources>
+        <template><text name="t1">a $a</text></template>
+    </map>
+    <map>
+        <sources alias="b">
+            <sources copySource="animals"/>
+            <text>duck</text>
+        </sources>
+        <template><text name="t2">b $b</text></template>
+    </map>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string_with_map(&dc, "/t1", vec![2], "value", "a horse");
+    assert_sv_is_string_with_map(&dc, "/t2", vec![1], "value", "b cow");
+    assert_sv_is_string_with_map(&dc, "/t2", vec![2], "value", "b horse");
+    assert_sv_is_string_with_map(&dc, "/t2", vec![3], "value", "b duck");
+}
+
+#[wasm_bindgen_test]
+fn map_over_output_of_another_map() {
+    static DATA: &str = r#"
+    <numberInput name="n" prefill="4"/>
+    <map name="first">
+        <sources alias="x">
+            <number>$n</number>
+            <sequence from="7" to="8"/>
+        </sources>
+        <template><number name="y">$x</number></template>
+    </map>
+    <map>
+        <sources alias="z">
+            <collect source="first" componentType="number"/>
+        </sources>
+        <template><text name="t">from $z</text></template>
+    </map>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string_with_map(&dc, "/t", vec![1], "value", "from 4");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2], "value", "from 7");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3], "value", "from 8");
+
+    update_immediate_value_for_number(&dc, "/n", "6");
+    update_value_for_number(&dc, "/n");
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number_with_map(&dc, "/y", vec![1], "value", 6.0);
+    assert_sv_is_string_with_map(&dc, "/t", vec![1], "value", "from 6");
+    assert_sv_is_string_with_map(&dc, "/t", vec![3], "value", "from 8");
+}
+
+#[wasm_bindgen_test]
+fn map_sources_in_combination() {
+    static DATA: &str = r#"
+    <map>
+        <sources alias="x" indexAlias="i">
+            <number>10</number>
+            <number>20</number>
+        </sources>
+        <sources alias="y" indexAlias="j">
+            <text>a</text>
+            <text>b</text>
+            <text>c</text>
+        </sources>
+        <template>
+            <text name="t">$x$y at $i,$j</text>
+            <graph><point name="p" xs="$j 0"/></graph>
+        </template>
+    </map>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string_with_map(&dc, "/t", vec![1, 1], "value", "10a at 1,1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![1, 3], "value", "10c at 1,3");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2, 2], "value", "20b at 2,2");
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![2, 3], "numericalXs", vec![3.0, 0.0]);
+
+    // The index cannot change, but the rest of the point can
+    move_point_2d(&dc, "[2, 3]/p", StateVarValue::Integer(5), StateVarValue::Integer(6));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![2, 3], "numericalXs", vec![3.0, 6.0]);
+    assert_sv_array_is_number_list_with_map(&dc, "/p", vec![2, 2], "numericalXs", vec![2.0, 0.0]);
+}
+
+#[wasm_bindgen_test]
+fn map_sources_in_parallel() {
+    static DATA: &str = r#"
+    <map behavior="parallel">
+        <sources alias="x" indexAlias="i">
+            <number>10</number>
+            <number>20</number>
+        </sources>
+        <sources alias="y">
+            <text>a</text>
+            <text>b</text>
+            <text>c</text>
+        </sources>
+        <template>
+            <text name="t">$x$y at $i</text>
+        </template>
+    </map>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree = doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string_with_map(&dc, "/t", vec![1], "value", "10a at 1");
+    assert_sv_is_string_with_map(&dc, "/t", vec![2], "value", "20b at 2");
+
+    // The shorter sources decides how many instances there are
+    let render_tree: serde_json::Value = serde_json::from_str(&render_tree).unwrap();
+    let instances = render_tree.as_array().unwrap().iter()
+        .filter(|c| c["componentName"].as_str().unwrap().starts_with("__/t_map"))
+        .count();
+    assert_eq!(instances, 2);
+}
+
+#[wasm_bindgen_test]
+fn map_instances_removed_and_restored() {
+    static DATA: &str = r#"
+    <numberInput name="n" prefill="3"/>
+    <map>
+        <sources alias="x"><sequence from="1" to="$n.value"/></sources>
+        <template>
+            <numberInput name="j" prefill="0"/>
+            <number name="m">$x</number>
+        </template>
+    </map>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    update_immediate_value_for_number(&dc, "[3]/j", "9");
+    update_value_for_number(&dc, "[3]/j");
+    let render_tree = doenet_core::update_renderers(&dc);
+    let render_tree: serde_json::Value = serde_json::from_str(&render_tree).unwrap();
+
+    // Only the instance that changed is sent again
+    let sent: Vec<&str> = render_tree.as_array().unwrap().iter()
+        .map(|c| c["componentName"].as_str().unwrap())
+        .filter(|name| name.starts_with("__"))
+        .collect();
+    assert_eq!(sent, vec!["__/j_map[3]"]);
+
+    update_immediate_value_for_number(&dc, "/n", "1");
+    update_value_for_number(&dc, "/n");
+
+    // A removed instance releases its state without a render
+    assert_state_var_stale(&dc, "/m", &vec![3], &doenet_core::state_variables::StateRef::Basic("value"));
+
+    update_immediate_value_for_number(&dc, "/n", "3");
+    update_value_for_number(&dc, "/n");
+    let render_tree = doenet_core::update_renderers(&dc);
+    let render_tree: serde_json::Value = serde_json::from_str(&render_tree).unwrap();
+
+    // The returning instances are sent in full, with their essential data kept
+    assert_sv_is_number_with_map(&dc, "/m", vec![3], "value", 3.0);
+    let j = render_data_of_component(&render_tree, "__/j_map[3]");
+    assert_eq!(j["stateValues"]["immediateValue"], "9");
+    render_data_of_component(&render_tree, "__template_from_(/_map1[2])");
+}
+
+// ========= <conditionalContent> ===========
+
+#[wasm_bindgen_test]
+fn conditional_content_updating() {
+    static DATA: &str = r#"
+    <numberinput name="n" prefill="2"/>
+    <text>Description: <conditionalContent>
+        <case condition="$n.value>=0.0">positive, </case>
+        <case condition="$n.value<0.0">negative, </case>
+        <case condition="$n.value>2.0">greater than 2, </case>
+        <case condition="$n.value>1.0">greater than 1, </case>
         <case condition="$n.value<3.0">less than 3, </case>
     </conditionalContent>ok.</text>
     "#;
@@ -1056,90 +1767,659 @@ fn conditional_content_updating() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "/_text1", "value", "Description: positive, greater than 1, less than 3, ok.");
+    assert_sv_is_string(&dc, "/_text1", "value", "Description: positive, greater than 1, less than 3, ok.");
+
+    update_immediate_value_for_number(&dc, "/n", "10");
+    update_value_for_number(&dc, "/n");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_string(&dc, "/_text1", "value", "Description: positive, greater than 2, greater than 1, ok.");
+
+    update_immediate_value_for_number(&dc, "/n", "1");
+    update_value_for_number(&dc, "/n");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_string(&dc, "/_text1", "value", "Description: positive, less than 3, ok.");
+
+    update_immediate_value_for_number(&dc, "/n", "-1");
+    update_value_for_number(&dc, "/n");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_string(&dc, "/_text1", "value", "Description: negative, less than 3, ok.");
+}
+
+
+
+// =========== <boolean> ===========
+
+#[wasm_bindgen_test]
+fn boolean_operations() {
+    static DATA: &str = r#"
+        <booleanInput name="bool2"/>
+        <text hide="$bool2.value">Yin</text>
+        <text hide="!$bool2.value">Yang</text>
+
+        <number name="num">3</number>
+        <boolean>$num == 3.0</boolean>
+        <boolean>$num != 1.0</boolean>
+        <boolean>$num != 3.0</boolean>
+
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_boolean(&dc, "/_text1", "hidden", false);
+    assert_sv_is_boolean(&dc, "/_text2", "hidden", true);
+
+    assert_sv_is_boolean(&dc, "/_boolean1", "value", true);
+    assert_sv_is_boolean(&dc, "/_boolean2", "value", true);
+    assert_sv_is_boolean(&dc, "/_boolean3", "value", false);
+}
+
+// =========== <line> ============
+
+#[wasm_bindgen_test]
+fn line_points_collection() {
+    static DATA: &str = r#"
+        <graph>
+                <line p1="5 2" p2="3 4"/>
+	        <point copySource="/_line1" copyCollection="points" componentIndex="1"/>
+	        <point copySource="/_line1" copyCollection="points" componentIndex="2"/>
+        </graph>
+        <number copySource="/_line1" copyCollection="points" componentIndex="1" copyProp="xs" propIndex="2"/>
+        <number copySource="/_line1" copyCollection="points" componentIndex="2" copyProp="xs" propIndex="1"/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/_number1", "value", 2.0);
+    assert_sv_is_number(&dc, "/_number2", "value", 3.0);
+    assert_sv_array_is_number_list(&dc, "/_point1", "numericalXs", vec![5.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/_point2", "numericalXs", vec![3.0, 4.0]);
+
+    move_point_2d(&dc, "/_point1", StateVarValue::Number(3.0), StateVarValue::Number(1.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/_number1", "value", 1.0);
+    assert_sv_is_number(&dc, "/_number2", "value", 3.0);
+    assert_sv_array_is_number_list(&dc, "/_point1", "numericalXs", vec![3.0, 1.0]);
+    assert_sv_array_is_number_list(&dc, "/_point2", "numericalXs", vec![3.0, 4.0]);
+
+    move_point_2d(&dc, "/_point2", StateVarValue::Number(1.0), StateVarValue::Number(3.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/_number1", "value", 1.0);
+    assert_sv_is_number(&dc, "/_number2", "value", 1.0);
+    assert_sv_array_is_number_list(&dc, "/_point1", "numericalXs", vec![3.0, 1.0]);
+    assert_sv_array_is_number_list(&dc, "/_point2", "numericalXs", vec![1.0, 3.0]);
+}
+
+#[wasm_bindgen_test]
+fn line_through_points_moves_them_when_dragged() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='A' xs='1 2' />
+            <point name='B' xs='3 6' />
+            <line name='l' through='$A $B' />
+        </graph>
+        <text name='equation' copySource='l' copyProp='equation' />
+        <number name='slope' copySource='l' copyProp='slope' />
+        <number name='yIntercept' copySource='l' copyProp='yIntercept' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/l", "numericalPoints", vec![1.0, 2.0, 3.0, 6.0]);
+    assert_sv_is_string(&dc, "/equation", "value", "y = 2x");
+    assert_sv_is_number(&dc, "/slope", "value", 2.0);
+    assert_sv_is_number(&dc, "/yIntercept", "value", 0.0);
+
+    move_line(&dc, "/l", vec![2.0, 2.0], vec![4.0, 3.0]);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/A", "xs", vec![2.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/B", "xs", vec![4.0, 3.0]);
+    assert_sv_is_string(&dc, "/equation", "value", "y = 0.5x + 1");
+
+    move_point_2d(&dc, "/B", StateVarValue::Number(2.0), StateVarValue::Number(-1.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/l", "numericalPoints", vec![2.0, 2.0, 2.0, -1.0]);
+    assert_sv_is_string(&dc, "/equation", "value", "x = 2");
+    assert_sv_is_number(&dc, "/yIntercept", "value", f64::NAN);
+}
+
+// =========== <circle> ============
+
+#[wasm_bindgen_test]
+fn circle_moves_center_and_radius() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='P' xs='1 2'/>
+            <circle name='c' center='$P.x $P.y' radius='3'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/c", "numericalCenter", vec![1.0, 2.0]);
+    assert_sv_is_number(&dc, "/c", "numericalRadius", 3.0);
+
+    move_circle(&dc, "/c", vec![4.0, -1.0], Some(2.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/c", "numericalCenter", vec![4.0, -1.0]);
+    assert_sv_is_number(&dc, "/c", "numericalRadius", 2.0);
+    assert_sv_array_is_number_list(&dc, "/P", "xs", vec![4.0, -1.0]);
+}
+
+#[wasm_bindgen_test]
+fn circle_through_point_keeps_radius_when_moved() {
+    static DATA: &str = r#"
+        <graph>
+            <circle name='c' center='1 2' through='4 6'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/c", "numericalRadius", 5.0);
+
+    move_circle(&dc, "/c", vec![0.0, 0.0], None);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/c", "numericalCenter", vec![0.0, 0.0]);
+    assert_sv_array_is_number_list(&dc, "/c", "through", vec![3.0, 4.0]);
+    assert_sv_is_number(&dc, "/c", "numericalRadius", 5.0);
+}
+
+#[wasm_bindgen_test]
+fn circle_moves_its_center_point() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='P' xs='1 2'/>
+            <circle name='c' center='$P' radius='3'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/c", "numericalCenter", vec![1.0, 2.0]);
+
+    move_circle(&dc, "/c", vec![4.0, -1.0], None);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/P", "xs", vec![4.0, -1.0]);
+    assert_sv_array_is_number_list(&dc, "/c", "numericalCenter", vec![4.0, -1.0]);
+
+    // A center with the wrong number of coordinates is ignored
+    move_circle(&dc, "/c", vec![7.0], None);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/P", "xs", vec![4.0, -1.0]);
+}
+
+// =========== <polygon> ============
+
+#[wasm_bindgen_test]
+fn polygon_measurements_follow_vertices() {
+    static DATA: &str = r#"
+        <graph>
+            <polygon name='poly' vertices='0 0 4 0 4 3'/>
+        </graph>
+        <number name='area'>$poly.area</number>
+        <number name='perimeter'>$poly.perimeter</number>
+        <number name='cx' copySource='poly' copyProp='centroid' propIndex='1'/>
+        <number name='cy' copySource='poly' copyProp='centroid' propIndex='2'/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_integer(&dc, "/poly", "numVertices", 3);
+    assert_sv_is_number(&dc, "/area", "value", 6.0);
+    assert_sv_is_number(&dc, "/perimeter", "value", 12.0);
+    assert_sv_is_number(&dc, "/cx", "value", 8.0 / 3.0);
+    assert_sv_is_number(&dc, "/cy", "value", 1.0);
+
+    move_vertex(&dc, "/poly", 3, 4.0, 6.0);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/poly", "vertices", vec![0.0, 0.0, 4.0, 0.0, 4.0, 6.0]);
+    assert_sv_is_number(&dc, "/area", "value", 12.0);
+    assert_sv_is_number(&dc, "/cy", "value", 2.0);
+}
+
+#[wasm_bindgen_test]
+fn polygon_vertices_batch_links_to_points() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='P' xs='0 0'/>
+            <polygon name='poly' vertices='$P.x $P.y 4 0 4 3'/>
+            <point name='v2' copySource='poly' copyCollection='vertices' componentIndex='2'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/v2", "xs", vec![4.0, 0.0]);
+
+    move_vertex(&dc, "/poly", 1, -1.0, 2.0);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/P", "xs", vec![-1.0, 2.0]);
+
+    move_point_2d(&dc, "/v2", StateVarValue::Number(5.0), StateVarValue::Number(1.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/poly", "vertices", vec![-1.0, 2.0, 5.0, 1.0, 4.0, 3.0]);
+}
+
+#[wasm_bindgen_test]
+fn polygon_moves_with_renderer_actions() {
+    static DATA: &str = r#"
+        <graph>
+            <polygon name='poly' vertices='0 0 4 0 4 3'/>
+            <lineSegment name='seg' vertices='0 0 1 1'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    // Dragging one vertex only sends the coordinates of that (0-indexed) vertex
+    doenet_core::handle_action_from_json(&dc, r#"{"componentName": "/poly", "actionName": "movePolygon",
+        "args": {"pointCoords": {"1": [5, 1]}, "sourceInformation": {"vertex": 1}, "actionId": "1"}}"#);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/poly", "vertices", vec![0.0, 0.0, 5.0, 1.0, 4.0, 3.0]);
+
+    doenet_core::handle_action_from_json(&dc, r#"{"componentName": "/poly", "actionName": "movePolygon",
+        "args": {"pointCoords": [[1, 1], [6, 2], [5, 4]], "actionId": "2"}}"#);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/poly", "vertices", vec![1.0, 1.0, 6.0, 2.0, 5.0, 4.0]);
+
+    doenet_core::handle_action_from_json(&dc, r#"{"componentName": "/seg", "actionName": "moveLineSegment",
+        "args": {"point2coords": [3, 2], "actionId": "3"}}"#);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/seg", "vertices", vec![0.0, 0.0, 3.0, 2.0]);
+
+    // Vertices out of range are ignored
+    move_vertex(&dc, "/poly", 0, 9.0, 9.0);
+    move_vertex(&dc, "/poly", 4, 9.0, 9.0);
+    doenet_core::handle_action_from_json(&dc, r#"{"componentName": "/poly", "actionName": "movePolygon",
+        "args": {"pointCoords": {"3": [9, 9]}, "actionId": "4"}}"#);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/poly", "vertices", vec![1.0, 1.0, 6.0, 2.0, 5.0, 4.0]);
+}
+
+#[wasm_bindgen_test]
+fn polygon_numerical_vertices_have_a_row_for_each_vertex() {
+    static DATA: &str = r#"
+        <graph>
+            <polygon name='poly' vertices='0 0 4 0 4 3'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    assert_sv_array_size_is(&dc, "/poly", "numericalVertices", 3);
+    let state_values = render_data_of_component(&render_tree, "/poly").get("stateValues").unwrap();
+    assert_eq!(state_values.get("numericalVertices"), Some(&serde_json::json!([[0.0, 0.0], [4.0, 0.0], [4.0, 3.0]])));
+
+    move_vertex(&dc, "/poly", 2, 5.0, 1.0);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    let state_values = render_data_of_component(&render_tree, "/poly").get("stateValues").unwrap();
+    assert_eq!(state_values.get("numericalVertices"), Some(&serde_json::json!([[0.0, 0.0], [5.0, 1.0], [4.0, 3.0]])));
+}
+
+#[wasm_bindgen_test]
+fn polyline_and_line_segment_lengths() {
+    static DATA: &str = r#"
+        <graph>
+            <lineSegment name='s' vertices='0 0 3 4'/>
+            <polyline name='pl' vertices='0 0 3 4 3 0'/>
+        </graph>
+        <number name='length' copySource='s' copyProp='length'/>
+        <number name='perimeter' copySource='pl' copyProp='perimeter'/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/length", "value", 5.0);
+    assert_sv_is_number(&dc, "/perimeter", "value", 9.0);
+
+    move_vertex(&dc, "/s", 2, 6.0, 8.0);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/length", "value", 10.0);
+}
+
+// =========== <intersection> ============
+
+#[wasm_bindgen_test]
+fn intersection_of_lines_follows_defining_point() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='Q' xs='0 2'/>
+            <line name='l1' p1='0 0' p2='1 1'/>
+            <line name='l2' p1='$Q.x $Q.y' p2='2 0'/>
+            <intersection name='i'>$l1 $l2</intersection>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_integer(&dc, "/i", "numIntersections", 1);
+    assert_sv_array_is_number_list(&dc, "/i", "intersections", vec![1.0, 1.0]);
+
+    // Parallel lines do not intersect
+    move_point_2d(&dc, "/Q", StateVarValue::Number(0.0), StateVarValue::Number(-2.0));
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_integer(&dc, "/i", "numIntersections", 0);
+
+    move_point_2d(&dc, "/Q", StateVarValue::Number(3.0), StateVarValue::Number(0.0));
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/i", "intersections", vec![0.0, 0.0]);
+}
+
+#[wasm_bindgen_test]
+fn intersection_of_segments_and_circles() {
+    static DATA: &str = r#"
+        <graph>
+            <point name='C' xs='3 0'/>
+            <circle name='c' center='$C.x $C.y' radius='1'/>
+            <polygon name='square' vertices='-3 -3 3 -3 3 3 -3 3'/>
+            <intersection name='i1'>$square $c</intersection>
+            <circle name='c2' center='0 0' radius='3'/>
+            <intersection name='i2'>$c $c2</intersection>
+            <lineSegment name='s' vertices='0 0 1 0'/>
+            <intersection name='i3'>$s $c2</intersection>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/i1", "intersections", vec![3.0, -1.0, 3.0, 1.0]);
+    assert_sv_is_integer(&dc, "/i2", "numIntersections", 2);
+
+    // The segment stops before reaching the circle
+    assert_sv_is_integer(&dc, "/i3", "numIntersections", 0);
+
+    // Touching shapes have one intersection point
+    move_point_2d(&dc, "/C", StateVarValue::Number(0.0), StateVarValue::Number(-2.0));
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/i1", "intersections", vec![0.0, -3.0]);
+    assert_sv_array_is_number_list(&dc, "/i2", "intersections", vec![0.0, -3.0]);
+}
+
+#[wasm_bindgen_test]
+fn intersection_points_are_rendered() {
+    static DATA: &str = r#"
+        <graph name='g'>
+            <line name='l1' p1='0 0' p2='1 1'/>
+            <line name='l2' p1='0 2' p2='2 0'/>
+            <intersection name='i'>$l1 $l2</intersection>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    child_instructions_for(&render_tree, "/g", "__point_from_(/i[1])");
+    let point = render_data_of_component(&render_tree, "__point_from_(/i[1])");
+    let state_values = point.get("stateValues").unwrap();
+    assert_eq!(state_values.get("numericalXs"), Some(&serde_json::json!([1.0, 1.0])));
+    assert_eq!(state_values.get("draggable"), Some(&serde_json::json!(false)));
+}
+
+// =========== <graph> ============
+
+#[wasm_bindgen_test]
+fn graph_keeps_zoom_across_reloads() {
+    static DATA: &str = r#"
+    <graph name='g' xmin='-5' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/g", "xmin", -5.0);
+    assert_sv_is_number(&dc, "/g", "ymax", 10.0);
 
-    update_immediate_value_for_number(&dc, "n", "10");
-    update_value_for_number(&dc, "n");
+    change_axis_limits(&dc, "/g", [-1.0, 3.0, -2.0, 2.0]);
     doenet_core::update_renderers(&dc);
-    assert_sv_is_string(&dc, "/_text1", "value", "Description: positive, greater than 2, greater than 1, ok.");
 
-    update_immediate_value_for_number(&dc, "n", "1");
-    update_value_for_number(&dc, "n");
-    doenet_core::update_renderers(&dc);
-    assert_sv_is_string(&dc, "/_text1", "value", "Description: positive, less than 3, ok.");
+    assert_sv_is_number(&dc, "/g", "xmin", -1.0);
+    assert_sv_is_number(&dc, "/g", "ymax", 2.0);
 
-    update_immediate_value_for_number(&dc, "n", "-1");
-    update_value_for_number(&dc, "n");
+    let (dc, warnings) = doenet_core_with_essential_data(DATA, dc.essential_data).unwrap();
+    assert_eq!(warnings.len(), 0);
     doenet_core::update_renderers(&dc);
-    assert_sv_is_string(&dc, "/_text1", "value", "Description: negative, less than 3, ok.");
+
+    assert_sv_is_number(&dc, "/g", "xmin", -1.0);
+    assert_sv_is_number(&dc, "/g", "xmax", 3.0);
+    assert_sv_is_number(&dc, "/g", "ymin", -2.0);
+    assert_sv_is_number(&dc, "/g", "ymax", 2.0);
 }
 
+#[wasm_bindgen_test]
+fn graph_fixed_axes_and_identical_scales() {
+    static DATA: &str = r#"
+    <graph name='fixed' fixAxes='true' />
+    <graph name='identical' identicalAxisScales='true' aspectRatio='2' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
 
+    assert_sv_is_number(&dc, "/identical", "ymin", -5.0);
+    assert_sv_is_number(&dc, "/identical", "ymax", 5.0);
 
-// =========== <boolean> ===========
+    change_axis_limits(&dc, "/fixed", [-1.0, 1.0, -1.0, 1.0]);
+    change_axis_limits(&dc, "/identical", [0.0, 4.0, -3.0, 3.0]);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/fixed", "xmin", -10.0);
+    assert_sv_is_number(&dc, "/fixed", "ymax", 10.0);
+    assert_sv_is_number(&dc, "/identical", "xmax", 4.0);
+    assert_sv_is_number(&dc, "/identical", "ymin", -1.0);
+    assert_sv_is_number(&dc, "/identical", "ymax", 1.0);
+}
 
 #[wasm_bindgen_test]
-fn boolean_operations() {
+fn graph_bounds_change_through_copies() {
     static DATA: &str = r#"
-        <booleanInput name="bool2"/>
-        <text hide="$bool2.value">Yin</text>
-        <text hide="!$bool2.value">Yang</text>
+    <graph name='g' />
+    <graph name='fixed' fixAxes='true' />
+    <graph>
+        <point name='P' xs='$g.xmin $g.ymax' />
+        <point name='Q' xs='$fixed.xmin $fixed.ymax' />
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
 
-        <number name="num">3</number>
-        <boolean>$num == 3.0</boolean>
-        <boolean>$num != 1.0</boolean>
-        <boolean>$num != 3.0</boolean>
+    move_point_2d(&dc, "/P", StateVarValue::Number(-3.0), StateVarValue::Number(7.0));
+    move_point_2d(&dc, "/Q", StateVarValue::Number(-3.0), StateVarValue::Number(7.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/g", "xmin", -3.0);
+    assert_sv_is_number(&dc, "/g", "xmax", 10.0);
+    assert_sv_is_number(&dc, "/g", "ymin", -10.0);
+    assert_sv_is_number(&dc, "/g", "ymax", 7.0);
+    assert_sv_is_number(&dc, "/fixed", "xmin", -10.0);
+    assert_sv_is_number(&dc, "/fixed", "ymax", 10.0);
+
+    // Bounds that are missing or not numbers are ignored
+    doenet_core::handle_action_from_json(&dc, r#"{"componentName": "/g", "actionName": "changeAxisLimits",
+        "args": {"xmin": 0, "xmax": 1, "ymin": "a", "actionId": "1"}}"#);
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_number(&dc, "/g", "xmin", -3.0);
+}
 
+#[wasm_bindgen_test]
+fn graph_width_and_grid_are_rendered() {
+    static DATA: &str = r#"
+    <graph name='g' width='300px' grid='2 3' />
+    <graph name='h' width='80%' grid='true' />
     "#;
     display_doenet_ml_on_failure!(DATA);
     let dc = doenet_core_with_no_warnings(DATA);
-    doenet_core::update_renderers(&dc);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
 
-    assert_sv_is_boolean(&dc, "/_text1", "hidden", false);
-    assert_sv_is_boolean(&dc, "/_text2", "hidden", true);
+    let state_values = render_data_of_component(&render_tree, "/g").get("stateValues").unwrap();
+    assert_eq!(state_values.get("width"), Some(&serde_json::json!({ "size": 300.0, "isAbsolute": true })));
+    assert_eq!(state_values.get("grid"), Some(&serde_json::json!([2.0, 3.0])));
 
-    assert_sv_is_boolean(&dc, "/_boolean1", "value", true);
-    assert_sv_is_boolean(&dc, "/_boolean2", "value", true);
-    assert_sv_is_boolean(&dc, "/_boolean3", "value", false);
+    let state_values = render_data_of_component(&render_tree, "/h").get("stateValues").unwrap();
+    assert_eq!(state_values.get("width"), Some(&serde_json::json!({ "size": 80.0, "isAbsolute": false })));
+    assert_eq!(state_values.get("grid"), Some(&serde_json::json!("medium")));
 }
 
-// =========== <line> ============
+// =========== <curve> ============
 
 #[wasm_bindgen_test]
-fn line_points_collection() {
+fn function_graph_is_sampled_across_the_graph() {
     static DATA: &str = r#"
-        <graph>
-                <line p1="5 2" p2="3 4"/>
-	        <point copySource="/_line1" copyCollection="points" componentIndex="1"/>
-	        <point copySource="/_line1" copyCollection="points" componentIndex="2"/>
+        <graph name='g' xmin='-4' xmax='4'>
+            <function name='f'>x^2 - 1</function>
         </graph>
-        <number copySource="/_line1" copyCollection="points" componentIndex="1" copyProp="xs" propIndex="2"/>
-        <number copySource="/_line1" copyCollection="points" componentIndex="2" copyProp="xs" propIndex="1"/>
     "#;
     display_doenet_ml_on_failure!(DATA);
     let dc = doenet_core_with_no_warnings(DATA);
-    doenet_core::update_renderers(&dc);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
 
-    assert_sv_is_number(&dc, "/_number1", "value", 2.0);
-    assert_sv_is_number(&dc, "/_number2", "value", 3.0);
-    assert_sv_array_is_number_list(&dc, "/_point1", "numericalXs", vec![5.0, 2.0]);
-    assert_sv_array_is_number_list(&dc, "/_point2", "numericalXs", vec![3.0, 4.0]);
+    child_instructions_for(&render_tree, "/g", "/f");
+    let state_values = render_data_of_component(&render_tree, "/f").get("stateValues").unwrap();
+    assert_eq!(state_values.get("curveType"), Some(&serde_json::json!("function")));
+
+    // A little beyond the graph on both sides
+    assert_sv_is_number(&dc, "/f", "parMin", -4.8);
+    assert_sv_is_number(&dc, "/f", "parMax", 4.8);
+
+    let definition = &state_values.get("fDefinitions").unwrap()[0];
+    assert_eq!(definition["functionType"], "interpolated");
+    let xs = definition["xs"].as_array().unwrap();
+    let coeffs = definition["coeffs"].as_array().unwrap();
+    assert_eq!(xs.first().unwrap().as_f64(), Some(-4.8));
+    assert_eq!(xs.last().unwrap().as_f64(), Some(4.8));
+    assert_eq!(coeffs.len(), xs.len() - 1);
+    for (x, c) in xs.iter().zip(coeffs) {
+        let x = x.as_f64().unwrap();
+        assert!((c[0].as_f64().unwrap() - (x * x - 1.0)).abs() < 1e-9);
+    }
 
-    move_point_2d(&dc, "/_point1", StateVarValue::Number(3.0), StateVarValue::Number(1.0));
+    // Zooming the graph samples it again
+    change_axis_limits(&dc, "/g", [0.0, 10.0, -10.0, 10.0]);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "/_number1", "value", 1.0);
-    assert_sv_is_number(&dc, "/_number2", "value", 3.0);
-    assert_sv_array_is_number_list(&dc, "/_point1", "numericalXs", vec![3.0, 1.0]);
-    assert_sv_array_is_number_list(&dc, "/_point2", "numericalXs", vec![3.0, 4.0]);
+    assert_sv_is_number(&dc, "/f", "parMin", -1.0);
+    assert_sv_is_number(&dc, "/f", "parMax", 11.0);
+}
 
-    move_point_2d(&dc, "/_point2", StateVarValue::Number(1.0), StateVarValue::Number(3.0));
-    doenet_core::update_renderers(&dc);
+#[wasm_bindgen_test]
+fn parameterized_curve_samples_both_functions() {
+    static DATA: &str = r#"
+        <graph name='g'>
+            <curve name='c' parMin='0' parMax='6'>
+                <function variable='t'>cos(t)</function>
+                <function variable='t'>sin(t)</function>
+            </curve>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
 
-    assert_sv_is_number(&dc, "/_number1", "value", 1.0);
-    assert_sv_is_number(&dc, "/_number2", "value", 1.0);
-    assert_sv_array_is_number_list(&dc, "/_point1", "numericalXs", vec![3.0, 1.0]);
-    assert_sv_array_is_number_list(&dc, "/_point2", "numericalXs", vec![1.0, 3.0]);
+    let state_values = render_data_of_component(&render_tree, "/c").get("stateValues").unwrap();
+    assert_eq!(state_values.get("curveType"), Some(&serde_json::json!("parameterization")));
+    assert_sv_is_number(&dc, "/c", "parMin", 0.0);
+    assert_sv_is_number(&dc, "/c", "parMax", 6.0);
+
+    let definitions = state_values.get("fDefinitions").unwrap().as_array().unwrap();
+    assert_eq!(definitions.len(), 2);
+    let ts = definitions[0]["xs"].as_array().unwrap();
+    assert_eq!(ts, definitions[1]["xs"].as_array().unwrap());
+
+    // Every sample is on the unit circle
+    let x_coeffs = definitions[0]["coeffs"].as_array().unwrap();
+    let y_coeffs = definitions[1]["coeffs"].as_array().unwrap();
+    assert_eq!(x_coeffs.len(), ts.len() - 1);
+    for (x, y) in x_coeffs.iter().zip(y_coeffs) {
+        let x = x[0].as_f64().unwrap();
+        let y = y[0].as_f64().unwrap();
+        assert!((x * x + y * y - 1.0).abs() < 1e-9);
+    }
+}
+
+#[wasm_bindgen_test]
+fn bezier_curve_through_points_and_control_vectors() {
+    static DATA: &str = r#"
+        <graph name='g'>
+            <curve name='c' through='0 0 2 2 4 0'/>
+        </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    let state_values = render_data_of_component(&render_tree, "/c").get("stateValues").unwrap();
+    assert_eq!(state_values.get("curveType"), Some(&serde_json::json!("bezier")));
+    assert_eq!(state_values.get("numericalThroughPoints"), Some(&serde_json::json!([[0.0, 0.0], [2.0, 2.0], [4.0, 0.0]])));
+    assert_eq!(state_values.get("vectorControlDirections"), Some(&serde_json::json!(["symmetric", "symmetric", "symmetric"])));
+    assert_sv_is_number(&dc, "/c", "parMin", 0.0);
+    assert_sv_is_number(&dc, "/c", "parMax", 2.0);
+
+    // The middle point's control points are parallel to the line between its neighbors
+    let control_points = state_values.get("numericalControlPoints").unwrap();
+    let middle = &control_points[1];
+    assert!((middle[0][1].as_f64().unwrap() - 2.0).abs() < 1e-9);
+    assert!((middle[1][1].as_f64().unwrap() - 2.0).abs() < 1e-9);
+
+    move_through_point(&dc, "/c", 1, 1.0, 5.0);
+    move_control_vector(&dc, "/c", 0, 1, 0.0, 3.0);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    assert_sv_array_is_number_list(&dc, "/c", "through", vec![0.0, 0.0, 1.0, 5.0, 4.0, 0.0]);
+
+    let state_values = render_data_of_component(&render_tree, "/c").get("stateValues").unwrap();
+    let control_points = state_values.get("numericalControlPoints").unwrap();
+    assert_eq!(control_points[0], serde_json::json!([[0.0, -3.0], [0.0, 3.0]]));
+
+    // The first segment starts at the first point, heading toward its control point
+    let x_definition = &state_values.get("fDefinitions").unwrap()[0];
+    let y_definition = &state_values.get("fDefinitions").unwrap()[1];
+    assert_eq!(x_definition["functionType"], "bezier");
+    assert_eq!(x_definition["component"], 0);
+    assert_eq!(y_definition["component"], 1);
+    assert_eq!(y_definition["splineCoeffs"][0][1], serde_json::json!([0.0, 9.0, -3.0, -1.0]));
+    assert_eq!(state_values.get("hiddenControls"), Some(&serde_json::json!([false, false, false])));
+
+    // Moves of through points that do not exist are ignored
+    move_through_point(&dc, "/c", 3, 7.0, 7.0);
+    move_control_vector(&dc, "/c", 1, 2, 7.0, 7.0);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    assert_sv_array_is_number_list(&dc, "/c", "through", vec![0.0, 0.0, 1.0, 5.0, 4.0, 0.0]);
+    let state_values = render_data_of_component(&render_tree, "/c").get("stateValues").unwrap();
+    assert_eq!(state_values.get("numericalControlPoints").unwrap()[0], serde_json::json!([[0.0, -3.0], [0.0, 3.0]]));
 }
 
 // =========== <number> ============
@@ -1248,14 +2528,14 @@ fn number_can_do_arithmetic_on_strings_and_number_children() {
     assert_sv_is_number(&dc, "/_number3", "value", -15.0);
     assert_sv_is_number(&dc, "/_number4", "value", -15.0);
 
-    assert_sv_is_number(&dc, "nested1", "value", -15.0);
-    assert_sv_is_number(&dc, "nested2", "value", -21.0);
-    assert_sv_is_number(&dc, "nested3", "value", -31.5);
-    assert_sv_is_number(&dc, "nested4", "value", -51.5);
+    assert_sv_is_number(&dc, "/nested1", "value", -15.0);
+    assert_sv_is_number(&dc, "/nested2", "value", -21.0);
+    assert_sv_is_number(&dc, "/nested3", "value", -31.5);
+    assert_sv_is_number(&dc, "/nested4", "value", -51.5);
 
-    assert_sv_is_number(&dc, "combined1", "value", -59.0);
-    assert_sv_is_number(&dc, "combined2", "value", -58.0);
-    assert_sv_is_number(&dc, "combined3", "value", 1.0);
+    assert_sv_is_number(&dc, "/combined1", "value", -59.0);
+    assert_sv_is_number(&dc, "/combined2", "value", -58.0);
+    assert_sv_is_number(&dc, "/combined3", "value", 1.0);
 }
 
 
@@ -1275,84 +2555,434 @@ fn number_invalid_prop_index_does_not_crash() {
     display_doenet_ml_on_failure!(DATA);
 
 
-    let (dc, doenet_ml_warnings) = doenet_core_from(DATA).unwrap();
-    assert_eq!(doenet_ml_warnings.len(), 3);
-    for warning in doenet_ml_warnings {
-        assert!(matches!(warning, DoenetMLWarning::PropIndexIsNotPositiveInteger { .. }));
-    }
-
+    let (dc, doenet_ml_warnings) = doenet_core_from(DATA).unwrap();
+    assert_eq!(doenet_ml_warnings.len(), 3);
+    for warning in doenet_ml_warnings {
+        assert!(matches!(warning, DoenetMLWarning::PropIndexIsNotPositiveInteger { .. }));
+    }
+
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/num1", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num2", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num3", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num4", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num5", "value", 5.0);
+
+}
+
+
+#[wasm_bindgen_test]
+fn number_invalid_dynamic_prop_index_does_not_crash() {
+    static DATA: &str = r#"
+    <sequence name='s' from='3' to='5' />
+
+    <number name='n1'>100</number>
+    <number name='n2'>-23</number>
+    <number name='n3'>asdf</number>
+    <number name='n4'>2.3</number>
+    <number name='n5'>3.0000</number>
+
+    <number name='num1' copySource='s' copyProp='value' propIndex = '$n1' />
+    <number name='num2' copySource='s' copyProp='value' propIndex = '$n2' />
+    <number name='num3' copySource='s' copyProp='value' propIndex = '$n3' />
+    <number name='num4' copySource='s' copyProp='value' propIndex = '$n4' />
+
+    <!-- This one should be valid -->
+    <number name='num5' copySource='s' copyProp='value' propIndex = '$n5' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/num1", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num2", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num3", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num4", "value", f64::NAN);
+    assert_sv_is_number(&dc, "/num5", "value", 5.0);
+
+}
+
+
+#[wasm_bindgen_test]
+fn number_parses_arithmetic_from_number_input_immediate_value() {
+    static DATA: &str = r#"
+    <numberInput/>
+    <numberInput copySource='/_numberInput1' />
+    <numberInput name='myNumInput' copySource='/_numberInput2' />
+
+    <number name='n1'>$myNumInput.immediateValue + 1</number>
+
+    <number name='n2' copySource='n1' />
+    <number name='n3' copySource='n2' />
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    update_immediate_value_for_number(&dc, "/_numberInput1", "5.0");
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_number(&dc, "/n1", "value", 6.0);
+    assert_sv_is_number(&dc, "/n2", "value", 6.0);
+    assert_sv_is_number(&dc, "/n3", "value", 6.0);
+
+}
+
+
+// ========= <choiceInput> ===========
+
+#[wasm_bindgen_test]
+fn choice_input_credit_for_selected_choices() {
+    static DATA: &str = r#"
+    <choiceInput name='single'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+        <choice credit='0.5'>mouse</choice>
+    </choiceInput>
+    <choiceInput name='multiple' selectMultiple>
+        <choice credit='0.5'>a</choice>
+        <choice credit='0.5'>b</choice>
+        <choice>c</choice>
+    </choiceInput>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/single", "selectedIndices", vec![]);
+    assert_sv_is_number(&dc, "/single", "creditAchieved", 0.0);
+
+    update_selected_indices(&dc, "/single", vec![StateVarValue::Integer(3)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/single", "selectedIndices", vec![3.0]);
+    assert_sv_is_number(&dc, "/single", "creditAchieved", 0.5);
+
+    update_selected_indices(&dc, "/multiple", vec![StateVarValue::Integer(1), StateVarValue::Integer(2), StateVarValue::Integer(3)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/multiple", "selectedIndices", vec![1.0, 2.0, 3.0]);
+
+    update_selected_indices(&dc, "/multiple", vec![StateVarValue::Integer(1), StateVarValue::Integer(2)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/multiple", "selectedIndices", vec![1.0, 2.0]);
+    assert_sv_is_number(&dc, "/multiple", "creditAchieved", 1.0);
+}
+
+#[wasm_bindgen_test]
+fn choice_input_shuffle_order_is_seeded() {
+    static DATA: &str = r#"
+    <choiceInput name='a' shuffleOrder seed='5'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+        <choice>mouse</choice>
+    </choiceInput>
+    <choiceInput name='b' shuffleOrder seed='5'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+        <choice>mouse</choice>
+    </choiceInput>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/a", "choiceOrder", vec![2.0, 1.0, 3.0]);
+    assert_sv_array_is_number_list(&dc, "/b", "choiceOrder", vec![2.0, 1.0, 3.0]);
+
+    // The second displayed choice is the first child
+    update_selected_indices(&dc, "/a", vec![StateVarValue::Integer(2)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_number(&dc, "/a", "creditAchieved", 1.0);
+}
+
+#[wasm_bindgen_test]
+fn choice_input_shuffle_order_depends_on_variant() {
+    static DATA: &str = r#"
+    <choiceInput name='a' shuffleOrder>
+        <choice>cat</choice>
+        <choice>dog</choice>
+        <choice>mouse</choice>
+        <choice>bird</choice>
+    </choiceInput>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let (dc, _) = doenet_core_with_flags(DATA, CoreFlags { variant_index: 1, ..Default::default() }).unwrap();
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/a", "choiceOrder", vec![3.0, 2.0, 1.0, 4.0]);
+
+    let (dc, _) = doenet_core_with_flags(DATA, CoreFlags { variant_index: 2, ..Default::default() }).unwrap();
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/a", "choiceOrder", vec![3.0, 4.0, 2.0, 1.0]);
+}
+
+#[wasm_bindgen_test]
+fn choice_input_without_select_multiple_keeps_one_selection() {
+    static DATA: &str = r#"
+    <choiceInput name='ci'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+        <choice>mouse</choice>
+    </choiceInput>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::Integer(1), StateVarValue::Integer(3)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/ci", "selectedIndices", vec![3.0]);
+
+    // Malformed indices and unknown actions are ignored
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::String("first".into())]);
+    call_action_with_no_args(&dc, "/ci", "selectEverything");
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/ci", "selectedIndices", vec![3.0]);
+}
+
+
+// ========= <feedback> ===========
+
+#[wasm_bindgen_test]
+fn feedback_hidden_until_award_is_submitted() {
+    static DATA: &str = r#"
+    <choiceInput name='ci'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+    </choiceInput>
+    <award name='a' feedbackCodes='goodjob' condition='$ci.valueHasBeenValidated&&$ci.creditAchieved>=1'/>
+    <feedback name='f'>Good job!</feedback>
+    <feedback name='g' condition='$a.awarded'>Good job!</feedback>
+    <feedback name='h' feedbackCodes='goodjob'>Good job!</feedback>
+    <feedback name='k' feedbackCodes='other'>Not this one.</feedback>
+    <feedback name='t' condition='true'>Submitted.</feedback>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string(&dc, "/a", "feedbackCodes", "goodjob");
+    assert_sv_is_boolean(&dc, "/f", "hidden", true);
+    assert_sv_is_boolean(&dc, "/g", "hidden", true);
+    assert_sv_is_boolean(&dc, "/h", "hidden", true);
+    assert_sv_is_boolean(&dc, "/t", "hidden", true);
+
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::Integer(1)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/a", "awarded", false);
+    assert_sv_is_boolean(&dc, "/g", "hidden", true);
+
+    submit_answer(&dc, "/ci");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/a", "awarded", true);
+    assert_sv_is_number(&dc, "/a", "creditAchieved", 1.0);
+    assert_sv_is_boolean(&dc, "/f", "hidden", true);
+    assert_sv_is_boolean(&dc, "/g", "hidden", false);
+    assert_sv_is_boolean(&dc, "/h", "hidden", false);
+    assert_sv_is_boolean(&dc, "/k", "hidden", true);
+    assert_sv_is_boolean(&dc, "/t", "hidden", false);
+
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::Integer(2)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/a", "awarded", false);
+    assert_sv_is_boolean(&dc, "/g", "hidden", true);
+    assert_sv_is_boolean(&dc, "/h", "hidden", true);
+    assert_sv_is_boolean(&dc, "/t", "hidden", true);
+}
+
+// ========= <hint> and <solution> ===========
+
+#[wasm_bindgen_test]
+fn hint_opens_and_closes() {
+    static DATA: &str = r#"
+    <hint name='h'><p>Think about cats.</p></hint>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_boolean(&dc, "/h", "open", false);
+    assert_sv_is_string(&dc, "/h", "title", "Hint");
+
+    call_action_with_no_args(&dc, "/h", "revealHint");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/h", "open", true);
+
+    call_action_with_no_args(&dc, "/h", "closeHint");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/h", "open", false);
+
+    // An unknown action is ignored
+    call_action_with_no_args(&dc, "/h", "revealSolution");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/h", "open", false);
+}
+
+#[wasm_bindgen_test]
+fn solution_respects_display_permission() {
+    static DATA: &str = r#"
+    <solution name='s'><p>It was a cat.</p></solution>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_boolean(&dc, "/s", "open", false);
+
+    doenet_core::set_solution_display_permission(&dc, false);
+    call_action_with_no_args(&dc, "/s", "revealSolution");
     doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/s", "open", false);
 
-    assert_sv_is_number(&dc, "num1", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num2", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num3", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num4", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num5", "value", 5.0);
+    doenet_core::set_solution_display_permission(&dc, true);
+    call_action_with_no_args(&dc, "/s", "revealSolution");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/s", "open", true);
 
+    call_action_with_no_args(&dc, "/s", "closeSolution");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/s", "open", false);
 }
 
+// ========= Core flags ===========
 
 #[wasm_bindgen_test]
-fn number_invalid_dynamic_prop_index_does_not_crash() {
+fn core_flags_reach_components() {
     static DATA: &str = r#"
-    <sequence name='s' from='3' to='5' />
-
-    <number name='n1'>100</number>
-    <number name='n2'>-23</number>
-    <number name='n3'>asdf</number>
-    <number name='n4'>2.3</number>
-    <number name='n5'>3.0000</number>
+    <choiceInput name='ci'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+    </choiceInput>
+    <feedback name='f' condition='true'>Shown</feedback>
+    <hint name='h'><p>Cats.</p></hint>
+    <solution name='s'><p>Cat.</p></solution>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let flags = CoreFlags {
+        show_correctness: false,
+        show_feedback: false,
+        show_hints: false,
+        solution_display_mode: SolutionDisplayMode::None,
+        ..Default::default()
+    };
+    let (dc, _) = doenet_core_with_flags(DATA, flags).unwrap();
+    doenet_core::update_renderers(&dc);
 
-    <number name='num1' copySource='s' copyProp='value' propIndex = '$n1' />
-    <number name='num2' copySource='s' copyProp='value' propIndex = '$n2' />
-    <number name='num3' copySource='s' copyProp='value' propIndex = '$n3' />
-    <number name='num4' copySource='s' copyProp='value' propIndex = '$n4' />
+    assert_sv_is_boolean(&dc, "/ci", "showCorrectness", false);
+    assert_sv_is_boolean(&dc, "/f", "hidden", true);
+    assert_sv_is_boolean(&dc, "/h", "showHints", false);
+    assert_sv_is_boolean(&dc, "/s", "hidden", true);
+}
 
-    <!-- This one should be valid -->
-    <number name='num5' copySource='s' copyProp='value' propIndex = '$n5' />
+#[wasm_bindgen_test]
+fn read_only_core_rejects_actions() {
+    static DATA: &str = r#"
+    <choiceInput name='ci'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+    </choiceInput>
+    <hint name='h'><p>Cats.</p></hint>
     "#;
     display_doenet_ml_on_failure!(DATA);
+    let flags = CoreFlags { read_only: true, ..Default::default() };
+    let (dc, _) = doenet_core_with_flags(DATA, flags).unwrap();
+    doenet_core::update_renderers(&dc);
 
+    assert_sv_is_boolean(&dc, "/_document1", "fixed", true);
 
-    let dc = doenet_core_with_no_warnings(DATA);
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::Integer(1)]);
+    call_action_with_no_args(&dc, "/h", "revealHint");
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_number(&dc, "num1", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num2", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num3", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num4", "value", f64::NAN);
-    assert_sv_is_number(&dc, "num5", "value", 5.0);
-
+    assert_sv_array_is_number_list(&dc, "/ci", "selectedIndices", vec![]);
+    assert_sv_is_boolean(&dc, "/h", "open", false);
 }
 
-
 #[wasm_bindgen_test]
-fn number_parses_arithmetic_from_number_input_immediate_value() {
+fn solution_requires_permission_when_flagged() {
     static DATA: &str = r#"
-    <numberInput/>
-    <numberInput copySource='/_numberInput1' />
-    <numberInput name='myNumInput' copySource='/_numberInput2' />
+    <solution name='s'><p>Cat.</p></solution>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let flags = CoreFlags {
+        solution_display_mode: SolutionDisplayMode::ButtonRequirePermission,
+        ..Default::default()
+    };
+    let (dc, _) = doenet_core_with_flags(DATA, flags).unwrap();
+    doenet_core::update_renderers(&dc);
 
-    <number name='n1'>$myNumInput.immediateValue + 1</number>
+    call_action_with_no_args(&dc, "/s", "revealSolution");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/s", "open", false);
 
-    <number name='n2' copySource='n1' />
-    <number name='n3' copySource='n2' />
+    doenet_core::set_solution_display_permission(&dc, true);
+    call_action_with_no_args(&dc, "/s", "revealSolution");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/s", "open", true);
+}
+
+#[wasm_bindgen_test]
+fn solution_permission_recomputed_on_reload_unless_granted() {
+    static DATA: &str = r#"
+    <solution name='s'><p>Cat.</p></solution>
     "#;
     display_doenet_ml_on_failure!(DATA);
+    let require_permission = || CoreFlags {
+        solution_display_mode: SolutionDisplayMode::ButtonRequirePermission,
+        ..Default::default()
+    };
+    let (dc, _) = doenet_core_with_flags(DATA, require_permission()).unwrap();
+    doenet_core::update_renderers(&dc);
+
+    // The reloaded core follows its own flags
+    let (reloaded, _) = doenet_core_with_flags_and_essential_data(DATA, CoreFlags::default(), dc.essential_data).unwrap();
+    doenet_core::update_renderers(&reloaded);
+    call_action_with_no_args(&reloaded, "/s", "revealSolution");
+    doenet_core::update_renderers(&reloaded);
+    assert_sv_is_boolean(&reloaded, "/s", "open", true);
+
+    // A permission that was granted is kept
+    let (dc, _) = doenet_core_with_flags(DATA, require_permission()).unwrap();
+    doenet_core::set_solution_display_permission(&dc, true);
+    let (reloaded, _) = doenet_core_with_flags_and_essential_data(DATA, require_permission(), dc.essential_data).unwrap();
+    doenet_core::update_renderers(&reloaded);
+    call_action_with_no_args(&reloaded, "/s", "revealSolution");
+    doenet_core::update_renderers(&reloaded);
+    assert_sv_is_boolean(&reloaded, "/s", "open", true);
+}
 
-    let dc = doenet_core_with_no_warnings(DATA);
+#[wasm_bindgen_test]
+fn document_submit_state_and_labels() {
+    static DATA: &str = r#"
+    <choiceInput name='ci'>
+        <choice credit='1'>cat</choice>
+        <choice>dog</choice>
+    </choiceInput>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let flags = CoreFlags {
+        submit_label: "Check".to_string(),
+        ..Default::default()
+    };
+    let (dc, _) = doenet_core_with_flags(DATA, flags).unwrap();
     doenet_core::update_renderers(&dc);
 
-    update_immediate_value_for_number(&dc, "/_numberInput1", "5.0");
-    doenet_core::update_renderers(&dc);
+    assert_sv_is_string(&dc, "/_document1", "submitLabel", "Check");
+    assert_sv_is_string(&dc, "/_document1", "submitLabelNoCorrectness", "Submit Response");
+    assert_sv_is_boolean(&dc, "/_document1", "justSubmitted", false);
 
-    assert_sv_is_number(&dc, "n1", "value", 6.0);
-    assert_sv_is_number(&dc, "n2", "value", 6.0);
-    assert_sv_is_number(&dc, "n3", "value", 6.0);
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::Integer(1)]);
+    submit_answer(&dc, "/ci");
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/_document1", "justSubmitted", true);
 
+    update_selected_indices(&dc, "/ci", vec![StateVarValue::Integer(2)]);
+    doenet_core::update_renderers(&dc);
+    assert_sv_is_boolean(&dc, "/_document1", "justSubmitted", false);
 }
 
-
 // ========= <sources> ===========
 
 #[wasm_bindgen_test]
@@ -1442,14 +3072,168 @@ fn macro_invalid_component_or_state_var_or_index_does_not_crash() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_is_string(&dc, "a", "value", "$asdfasdf");
-    assert_sv_is_string(&dc, "b", "value", "$a.qwertyqwerty");
-    assert_sv_is_string(&dc, "c", "value", "$a.value[5]");
-    assert_sv_is_string(&dc, "d", "value", "$a[5].value");
+    assert_sv_is_string(&dc, "/a", "value", "$asdfasdf");
+    assert_sv_is_string(&dc, "/b", "value", "$a.qwertyqwerty");
+    assert_sv_is_string(&dc, "/c", "value", "$a.value[5]");
+    assert_sv_is_string(&dc, "/d", "value", "$a[5].value");
+}
+
+
+
+
+// ========= Namespaces ===========
+
+#[wasm_bindgen_test]
+fn doenet_ml_error_duplicate_name_in_same_namespace() {
+    static DATA: &str = r#"
+    <section><text name='x'>one</text></section>
+    <section><text name='x'>two</text></section>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::DuplicateName { name: "/x".into() });
+}
+
+#[wasm_bindgen_test]
+fn names_resolve_relative_to_namespaces() {
+    static DATA: &str = r#"
+    <section name='sec1' newNamespace>
+        <text name='x'>one</text>
+        <text name='inner'>$x</text>
+        <text name='outer'>$../x</text>
+        <text name='absolute'>$/sec2/x</text>
+        <text name='copy' copySource='x'/>
+    </section>
+    <section name='sec2' newNamespace>
+        <text name='x'>two</text>
+        <text name='through'>$sec1/x</text>
+    </section>
+    <text name='x'>root</text>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string(&dc, "/sec1/inner", "value", "one");
+    assert_sv_is_string(&dc, "/sec1/outer", "value", "root");
+    assert_sv_is_string(&dc, "/sec1/absolute", "value", "two");
+    assert_sv_is_string(&dc, "/sec1/copy", "value", "one");
+    assert_sv_is_string(&dc, "/sec2/through", "value", "one");
+}
+
+#[wasm_bindgen_test]
+fn macro_slash_is_a_path_only_when_the_path_resolves() {
+    static DATA: &str = r#"
+    <number name='x'>6</number>
+    <text name='divided'>$x/y</text>
+    <section name='s' newNamespace><text name='y'>a</text></section>
+    <text name='path'>$s/y</text>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_is_string(&dc, "/divided", "value", "6/y");
+    assert_sv_is_string(&dc, "/path", "value", "a");
+}
+
+#[wasm_bindgen_test]
+fn doenet_ml_error_unresolved_path_names_the_reference() {
+    static DATA: &str = r#"
+    <section name='s' newNamespace/>
+    <text copySource='s/y'/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::ComponentDoesNotExist { comp_name: "s/y".into() });
+}
+
+#[wasm_bindgen_test]
+fn copy_with_new_namespace_renders_qualified_names() {
+    static DATA: &str = r#"
+    <section name='sec' newNamespace>
+        <text name='x'>one</text>
+    </section>
+    <section name='c' copySource='sec' newNamespace/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    child_instructions_for(&render_tree, "/sec", "/sec/x");
+    child_instructions_for(&render_tree, "/c", "/c/x");
+
+    let copied_text = render_data_of_component(&render_tree, "/c/x");
+    assert_eq!(copied_text.get("stateValues").unwrap().get("text"), Some(&serde_json::json!("one")));
+}
+
+// ========= assignNames ===========
+
+#[wasm_bindgen_test]
+fn sequence_assign_names_name_its_members() {
+    static DATA: &str = r#"
+    <sequence name='s' from='5' to='7' assignNames='a b'/>
+    <number name='n' copySource='b'/>
+    <text name='t'>$a</text>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    assert_sv_is_number(&dc, "/n", "value", 6.0);
+    assert_sv_is_string(&dc, "/t", "value", "5");
+
+    child_instructions_for(&render_tree, "/_document1", "/a");
+    child_instructions_for(&render_tree, "/_document1", "/b");
+    child_instructions_for(&render_tree, "/_document1", "__number_from_(/s[3])");
 }
 
+#[wasm_bindgen_test]
+fn map_assign_names_name_each_instance() {
+    static DATA: &str = r#"
+    <map assignNames='first second'>
+    <sources componentType="number" alias="x">
+        <number>5</number>
+        <number>3</number>
+    </sources>
+    <template>
+        <number name='n'>$x</number>
+    </template>
+    </map>
+    <number name='copy' copySource='second/n'/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    assert_sv_is_number(&dc, "/copy", "value", 3.0);
+
+    child_instructions_for(&render_tree, "/first", "/first/n");
+    let second = render_data_of_component(&render_tree, "/second/n");
+    assert_eq!(second.get("stateValues").unwrap().get("text"), Some(&serde_json::json!("3")));
+}
 
+#[wasm_bindgen_test]
+fn doenet_ml_error_assigned_name_used_by_component() {
+    static DATA: &str = r#"
+    <sequence from='1' to='2' assignNames='a b'/>
+    <text name='a'>taken</text>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
 
+    let error = doenet_core_from(DATA).unwrap_err();
+    assert_eq!(error, DoenetMLError::DuplicateName { name: "/a".into() });
+}
 
 // ========= Reloading essential data ============
 
@@ -1466,12 +3250,12 @@ fn reload_essential_data_after_point_moves() {
     let dc = doenet_core_with_no_warnings(DATA);
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p", "xs", vec![3.0, 2.0]);
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![3.0, 2.0]);
 
-    move_point_2d(&dc, "p", StateVarValue::Integer(5), StateVarValue::Number(1.0));
+    move_point_2d(&dc, "/p", StateVarValue::Integer(5), StateVarValue::Number(1.0));
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p", "xs", vec![5.0, 1.0]);
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![5.0, 1.0]);
 
 
     let (dc, possible_warnings) = doenet_core_with_essential_data(DATA, dc.essential_data).unwrap();
@@ -1479,7 +3263,185 @@ fn reload_essential_data_after_point_moves() {
 
     doenet_core::update_renderers(&dc);
 
-    assert_sv_array_is_number_list(&dc, "p", "xs", vec![5.0, 1.0]);
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![5.0, 1.0]);
+}
+
+#[wasm_bindgen_test]
+fn saved_state_is_restored_from_json() {
+    static DATA: &str = r#"
+    <numberInput name='n'/>
+    <number name='sum'>1 + $n.value</number>
+    <textInput name='t'/>
+    <graph name='g'>
+        <point name='p' xs='1 2'/>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let flags = CoreFlags { allow_save_state: true, ..Default::default() };
+    let (dc, _) = doenet_core_with_flags(DATA, flags.clone()).unwrap();
+    doenet_core::update_renderers(&dc);
+
+    update_immediate_value_for_number(&dc, "/n", "4");
+    update_value_for_number(&dc, "/n");
+    update_immediate_value_for_text(&dc, "/t", "hello");
+    move_point_2d(&dc, "/p", StateVarValue::Number(3.0), StateVarValue::Number(-4.5));
+    change_axis_limits(&dc, "/g", [-1.0, 3.0, -2.0, 2.0]);
+    doenet_core::update_renderers(&dc);
+
+    let essential_data = doenet_core::take_essential_data_to_save(&dc).unwrap();
+    let saved_json = serde_json::to_string(&doenet_core::saved_state(&essential_data)).unwrap();
+
+    let (reloaded, _) = doenet_core_from_saved_state(DATA, flags, serde_json::from_str(&saved_json).unwrap()).unwrap();
+    doenet_core::update_renderers(&reloaded);
+
+    assert_eq!(doenet_core::saved_state(&reloaded.essential_data), doenet_core::saved_state(&dc.essential_data));
+    assert_sv_is_number(&reloaded, "/sum", "value", 5.0);
+    assert_sv_is_string(&reloaded, "/t", "immediateValue", "hello");
+    assert_sv_array_is_number_list(&reloaded, "/p", "xs", vec![3.0, -4.5]);
+    assert_sv_is_number(&reloaded, "/g", "xmax", 3.0);
+
+    // Saved state that the document does not have is ignored
+    let (reloaded, _) = doenet_core_from_saved_state(DATA, CoreFlags::default(), serde_json::from_str(r#"[
+        {"componentName": "/missing", "origin": {"stateVar": "value"}, "instance": [], "value": {"single": {"number": 1.0}}},
+        {"componentName": "/p", "origin": {"stateVar": "xs"}, "instance": [], "value": {"single": {"string": "a"}}}
+    ]"#).unwrap()).unwrap();
+    doenet_core::update_renderers(&reloaded);
+    assert_sv_array_is_number_list(&reloaded, "/p", "xs", vec![1.0, 2.0]);
+}
+
+#[wasm_bindgen_test]
+fn actions_that_change_nothing_are_not_recorded() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='p' xs='1 2'/>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    doenet_core::handle_action_from_json(&dc, r#"{"componentName": "/p", "actionName": "pointClicked",
+        "args": {"actionId": "1"}}"#);
+    assert_eq!(dc.action_history.borrow().recorded_actions.len(), 0);
+
+    move_point_2d(&dc, "/p", StateVarValue::Number(3.0), StateVarValue::Number(4.0));
+    assert_eq!(dc.action_history.borrow().recorded_actions.len(), 1);
+}
+
+// ========= Transient actions ============
+
+#[wasm_bindgen_test]
+fn transient_actions_are_committed_by_the_final_action() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='p' xs='1 2'/>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let flags = CoreFlags { allow_save_state: true, ..Default::default() };
+    let (mut dc, _) = doenet_core_with_flags(DATA, flags).unwrap();
+    doenet_core::update_renderers(&dc);
+
+    drag_point_2d(&dc, "/p", 3.0, 4.0);
+    drag_point_2d(&dc, "/p", 5.0, 6.0);
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![5.0, 6.0]);
+    assert!(doenet_core::take_essential_data_to_save(&dc).is_none());
+    assert!(doenet_core::pop_undo_state(&dc).is_none());
+    assert_eq!(dc.action_history.borrow().recorded_actions.len(), 0);
+
+    move_point_2d(&dc, "/p", StateVarValue::Number(7.0), StateVarValue::Number(8.0));
+    doenet_core::update_renderers(&dc);
+
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![7.0, 8.0]);
+    assert_eq!(dc.action_history.borrow().recorded_actions.len(), 1);
+
+    let saved_data = doenet_core::take_essential_data_to_save(&dc).unwrap();
+    assert!(doenet_core::take_essential_data_to_save(&dc).is_none());
+
+    let (saved_dc, _) = doenet_core_with_essential_data(DATA, saved_data).unwrap();
+    doenet_core::update_renderers(&saved_dc);
+    assert_sv_array_is_number_list(&saved_dc, "/p", "xs", vec![7.0, 8.0]);
+
+    // The whole drag is undone at once
+    assert!(undo(&mut dc, DATA));
+    assert!(!undo(&mut dc, DATA));
+    doenet_core::update_renderers(&dc);
+    assert_sv_array_is_number_list(&dc, "/p", "xs", vec![1.0, 2.0]);
+    assert_eq!(dc.action_history.borrow().recorded_actions.len(), 1);
+    assert!(doenet_core::take_essential_data_to_save(&dc).is_some());
+}
+
+#[wasm_bindgen_test]
+fn state_is_saved_only_when_allowed() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='p' xs='1 2'/>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    move_point_2d(&dc, "/p", StateVarValue::Number(7.0), StateVarValue::Number(8.0));
+    assert!(doenet_core::take_essential_data_to_save(&dc).is_none());
+}
+
+#[wasm_bindgen_test]
+fn action_history_is_capped() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='p' xs='1 2'/>
+    </graph>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    doenet_core::update_renderers(&dc);
+
+    for i in 0..doenet_core::MAX_ACTION_HISTORY + 5 {
+        move_point_2d(&dc, "/p", StateVarValue::Number(i as f64), StateVarValue::Number(0.0));
+    }
+
+    let history = dc.action_history.borrow();
+    assert_eq!(history.recorded_actions.len(), doenet_core::MAX_ACTION_HISTORY);
+    assert_eq!(history.undo_states.len(), doenet_core::MAX_ACTION_HISTORY);
+}
+
+#[wasm_bindgen_test]
+fn hidden_components_are_not_rerendered_during_a_drag() {
+    static DATA: &str = r#"
+    <graph>
+        <point name='p' xs='1 2'/>
+    </graph>
+    <text name='hidden' hide='true' copySource='p' copyProp='text'/>
+    <text name='shown' copySource='p' copyProp='text'/>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+
+    let dc = doenet_core_with_no_warnings(DATA);
+    let rendered_text = |dc: &doenet_core::DoenetCore, name: &'static str| {
+        let render_tree_string = doenet_core::update_renderers(dc);
+        let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+        render_data_of_component(&render_tree, name)
+            .get("stateValues").unwrap()
+            .get("text").unwrap()
+            .clone()
+    };
+
+    assert_eq!(rendered_text(&dc, "/hidden"), serde_json::json!("(1, 2)"));
+
+    drag_point_2d(&dc, "/p", 3.0, 4.0);
+    assert_eq!(rendered_text(&dc, "/shown"), serde_json::json!("(3, 4)"));
+    assert_eq!(rendered_text(&dc, "/hidden"), serde_json::json!("(1, 2)"));
+
+    move_point_2d(&dc, "/p", StateVarValue::Number(5.0), StateVarValue::Number(6.0));
+    assert_eq!(rendered_text(&dc, "/hidden"), serde_json::json!("(5, 6)"));
 }
 
 // =============== Render tree ===================
@@ -1518,6 +3480,24 @@ fn render_tree_formats_state_vars_correctly() {
         "Render tree string state var incorrect");
 }
 
+#[wasm_bindgen_test]
+fn render_tree_has_null_for_absent_state_vars() {
+    static DATA: &str = r#"
+    <hint name='h'><p>Try again</p></hint>
+    "#;
+    display_doenet_ml_on_failure!(DATA);
+    let dc = doenet_core_with_no_warnings(DATA);
+    let render_tree_string = doenet_core::update_renderers(&dc);
+    let render_tree = serde_json::from_str(&render_tree_string).unwrap();
+
+    let state_values = render_data_of_component(&render_tree, "/_document1").get("stateValues").unwrap();
+    assert_eq!(state_values.get("titleChildName"), Some(&serde_json::Value::Null));
+
+    let state_values = render_data_of_component(&render_tree, "/h").get("stateValues").unwrap();
+    assert_eq!(state_values.get("titleChildName"), Some(&serde_json::Value::Null));
+    assert_eq!(state_values.get("title"), Some(&serde_json::json!("Hint")));
+}
+
 
 // Make sure that the $n variable name is not var0
 // <number name='n'>3.1</number>